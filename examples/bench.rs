@@ -65,8 +65,8 @@ fn run(opt: OptLevel) {
     println!("Running ...");
 
     let compiler = Compiler::default();
-    let mut module = compiler.module_from_path(SCRIPT_PATH).unwrap();
-    let result = module.exec::<i64>();
+    let module = compiler.module_from_path(SCRIPT_PATH).unwrap();
+    let result = module.exec::<f64>();
 
     println!("Result: {}", result.unwrap());
 }
@@ -10,12 +10,10 @@ pub fn main() {
             r#"
                 if false {
                     5.0
+                } else if true {
+                    2.0
                 } else {
-                    if true {
-                        2.0
-                    } else {
-                        1.0
-                    }
+                    1.0
                 }
 		    "#,
         )
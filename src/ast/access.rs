@@ -15,6 +15,16 @@ pub struct Access<'i> {
 
 //
 
+impl<'i> Access<'i> {
+    pub fn new(name: Ident<'i>) -> Self {
+        Self {
+            span: name.span(),
+            name,
+            ty: None,
+        }
+    }
+}
+
 impl<'i> Ast<'i> for Access<'i> {
     fn span(&self) -> Span<'i> {
         self.span.clone()
@@ -35,16 +45,34 @@ impl<'i> Ast<'i> for Access<'i> {
 
 impl<'i> TypeOf<'i> for Access<'i> {
     fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        if let Some(ty) = vars.get_var(&self.name.value) {
+            if let Some(name) = vars.get_static_name(&self.name.value) {
+                self.name.value = name;
+            }
+            vars.check_host_var(self.span(), &self.name.value)?;
+            self.ty = Some(ty);
+            return Ok(());
+        }
+
+        // functions can be used as values, if they are not generic
+        self.name.value = vars.resolve_name(&self.name.value);
         let name = self.name.value.as_str();
-        self.ty = match vars.get_var(name) {
-            Some(ty) => Some(ty),
+        let ty = match vars.get_fn_params(name) {
+            Some(params) if params.contains(&Type::Unresolved) => {
+                return Err(Error::new_generic_fn_value(self.span(), name))
+            }
+            Some(params) => {
+                let ret = vars.get_fn_ty(self.span(), name, &params)?;
+                Type::Fn(params, Box::new(ret))
+            }
             None => return Err(Error::new_var_not_found(self.span(), name)),
         };
+        self.ty = Some(ty);
         Ok(())
     }
 
     fn type_of_impl(&self) -> Option<Type> {
-        self.ty
+        self.ty.clone()
     }
 }
 
@@ -0,0 +1,127 @@
+use super::{match_rule, Ast, Error, Expr, Result, Rule, Type, TypeOf, VisibleVars};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrayLit<'i> {
+    pub elements: Vec<Expr<'i>>,
+
+    /// `[element; N]` repeats the only element `N` times
+    pub repeat: Option<usize>,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> ArrayLit<'i> {
+    pub fn len(&self) -> usize {
+        self.repeat.unwrap_or(self.elements.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'i> Ast<'i> for ArrayLit<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::array_lit)?;
+
+        let mut elements = vec![];
+        let mut repeat = None;
+        for token in token.into_inner() {
+            match token.as_rule() {
+                Rule::array_len => repeat = Some(token.as_str().parse().unwrap()),
+                _ => elements.push(Expr::parse(token)?),
+            }
+        }
+
+        Ok(Self {
+            elements,
+            repeat,
+
+            span,
+            ty: None,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for ArrayLit<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        for element in self.elements.iter_mut() {
+            element.type_check(vars)?;
+        }
+
+        // `[1, 2u8]` is `[1u8, 2u8]`
+        let hint = self
+            .elements
+            .iter()
+            .map(|element| element.type_of())
+            .find(|ty| ty.is_number() && !ty.is_lit_default());
+        if let Some(hint) = hint {
+            for element in self.elements.iter_mut() {
+                element.infer_lit(&hint)?;
+            }
+        }
+
+        self.ty = Some(Type::Array(Box::new(self.unify()?), self.len()));
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> ArrayLit<'i> {
+    /// `[1, 2]` where a `[u8; 2]` is expected is `[1u8, 2u8]`, see [`Expr::infer_lit`]
+    pub fn infer_lit(&mut self, ty: &Type) -> Result<()> {
+        if let Type::Array(ty, _) = ty {
+            for element in self.elements.iter_mut() {
+                element.infer_lit(ty)?;
+            }
+            self.ty = Some(Type::Array(Box::new(self.unify()?), self.len()));
+        }
+        Ok(())
+    }
+
+    /// the type of the elements, they all need to have the same one
+    fn unify(&self) -> Result<Type> {
+        let mut ty = Type::Never;
+        for element in self.elements.iter() {
+            let got = element.type_of();
+            match (&ty, got) {
+                (ty, got) if *ty == got => {}
+                (Type::Unresolved | Type::Never, got) => ty = got,
+                (_, Type::Unresolved | Type::Never) => {}
+                (ty, got) => return Err(Error::new_type_mismatch(element.span(), ty, &got)),
+            }
+        }
+        Ok(ty)
+    }
+}
+
+impl<'i> Display for ArrayLit<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.repeat {
+            Some(len) => write!(f, "[{}; {len}]", self.elements[0]),
+            None => {
+                write!(f, "[")?;
+                for element in self.elements.iter() {
+                    write!(f, "{element}, ")?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use super::{match_rule, Ast, Expr, Ident, Result, Rule, Type, TypeOf, VisibleVars};
+use super::{match_rule, Ast, Error, Expr, Pattern, Result, Rule, Type, TypeOf, VisibleVars};
 use pest::{iterators::Pair, Span};
 use std::fmt::Display;
 
@@ -6,7 +6,8 @@ use std::fmt::Display;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Assign<'i> {
-    pub name: Ident<'i>,
+    pub pattern: Pattern<'i>,
+    pub type_decl: Option<Type>,
     pub expr: Expr<'i>,
 
     span: Span<'i>,
@@ -25,11 +26,17 @@ impl<'i> Ast<'i> for Assign<'i> {
         match_rule(&span, token.as_rule(), Rule::assign)?;
         let mut tokens = token.into_inner();
 
-        let name = Ident::parse(tokens.next().unwrap())?;
+        let pattern = Pattern::parse(tokens.next().unwrap())?;
+        let type_decl = if let Some(Rule::ty) = tokens.peek().map(|token| token.as_rule()) {
+            Some(Type::parse(tokens.next().unwrap())?)
+        } else {
+            None
+        };
         let expr = Expr::parse(tokens.next().unwrap())?;
 
         Ok(Self {
-            name,
+            pattern,
+            type_decl,
             expr,
 
             span,
@@ -40,21 +47,46 @@ impl<'i> Ast<'i> for Assign<'i> {
 
 impl<'i> TypeOf<'i> for Assign<'i> {
     fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        // `let x: T = ..` in a generic function
+        self.type_decl = self.type_decl.as_ref().map(|ty| vars.resolve_ty(ty));
+        if let Some(expect) = &self.type_decl {
+            self.expr.infer_params(expect);
+        }
         self.expr.type_check(vars)?;
+        if let Some(expect) = &self.type_decl {
+            self.expr.infer_lit(expect)?;
+        }
         let ty = self.expr.type_of();
+        let ty = match &self.type_decl {
+            Some(expect) if *expect != ty && ty != Type::Never => {
+                return Err(Error::new_type_mismatch(self.expr.span(), expect, &ty))
+            }
+            Some(expect) => {
+                vars.check_ty(self.span(), expect)?;
+                expect.clone()
+            }
+            None => ty,
+        };
+        self.pattern.check(vars, &ty)?;
+        if let Some(witness) = Pattern::missing(vars, &[&self.pattern], &ty) {
+            return Err(Error::new_refutable_pattern(self.pattern.span(), &witness));
+        }
         self.ty = Some(ty);
-        vars.push_var(self.name.value.as_str(), ty);
 
         Ok(())
     }
 
     fn type_of_impl(&self) -> Option<Type> {
-        self.ty
+        self.ty.clone()
     }
 }
 
 impl<'i> Display for Assign<'i> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "let {} = {}", self.name, self.expr)
+        write!(f, "let {}", self.pattern)?;
+        if let Some(ty) = &self.type_decl {
+            write!(f, ": {ty}")?;
+        }
+        write!(f, " = {}", self.expr)
     }
 }
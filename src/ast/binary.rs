@@ -23,6 +23,10 @@ pub enum BinaryOp {
     /// divided by
     Div,
 
+    /// <operand> '%' <operand>
+    /// remainder of division by
+    Rem,
+
     /// <operand> '==' <operand>
     /// equal to
     Eq,
@@ -54,6 +58,26 @@ pub enum BinaryOp {
     /// <operand> '&&' <operand>
     /// and
     And,
+
+    /// <operand> '&' <operand>
+    /// bitwise and
+    BitAnd,
+
+    /// <operand> '|' <operand>
+    /// bitwise or
+    BitOr,
+
+    /// <operand> '^' <operand>
+    /// bitwise xor
+    BitXor,
+
+    /// <operand> '<<' <operand>
+    /// shifted left by
+    Shl,
+
+    /// <operand> '>>' <operand>
+    /// shifted right by
+    Shr,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -80,6 +104,7 @@ impl Display for BinaryOp {
             BinaryOp::Sub => write!(f, "-"),
             BinaryOp::Mul => write!(f, "*"),
             BinaryOp::Div => write!(f, "/"),
+            BinaryOp::Rem => write!(f, "%"),
 
             BinaryOp::Eq => write!(f, "=="),
             BinaryOp::Ne => write!(f, "!="),
@@ -90,18 +115,24 @@ impl Display for BinaryOp {
 
             BinaryOp::Or => write!(f, "||"),
             BinaryOp::And => write!(f, "&&"),
+
+            BinaryOp::BitAnd => write!(f, "&"),
+            BinaryOp::BitOr => write!(f, "|"),
+            BinaryOp::BitXor => write!(f, "^"),
+            BinaryOp::Shl => write!(f, "<<"),
+            BinaryOp::Shr => write!(f, ">>"),
         }
     }
 }
 
-impl<'i> BinaryExpr<'i> {
-    pub fn new(span: Span<'i>, lhs: Expr<'i>, op: Pair<'i, Rule>, rhs: Expr<'i>) -> Result<Self> {
-        let operands = Box::new(Sides { lhs, rhs });
-        let operator = match op.as_rule() {
+impl BinaryOp {
+    pub fn from_rule(rule: Rule) -> Self {
+        match rule {
             Rule::add => BinaryOp::Add,
             Rule::sub => BinaryOp::Sub,
             Rule::mul => BinaryOp::Mul,
             Rule::div => BinaryOp::Div,
+            Rule::rem => BinaryOp::Rem,
 
             Rule::eq => BinaryOp::Eq,
             Rule::ne => BinaryOp::Ne,
@@ -113,16 +144,72 @@ impl<'i> BinaryExpr<'i> {
             Rule::or => BinaryOp::Or,
             Rule::and => BinaryOp::And,
 
-            _ => unreachable!("{:?}", op),
-        };
+            Rule::bit_and => BinaryOp::BitAnd,
+            Rule::bit_or => BinaryOp::BitOr,
+            Rule::bit_xor => BinaryOp::BitXor,
+            Rule::shl => BinaryOp::Shl,
+            Rule::shr => BinaryOp::Shr,
+
+            _ => unreachable!("{:?}", rule),
+        }
+    }
+}
+
+impl<'i> BinaryExpr<'i> {
+    pub fn new(span: Span<'i>, lhs: Expr<'i>, op: Pair<'i, Rule>, rhs: Expr<'i>) -> Result<Self> {
+        Ok(Self::from_op(
+            span,
+            lhs,
+            BinaryOp::from_rule(op.as_rule()),
+            rhs,
+        ))
+    }
+
+    pub fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
 
-        Ok(BinaryExpr {
+    pub fn from_op(span: Span<'i>, lhs: Expr<'i>, operator: BinaryOp, rhs: Expr<'i>) -> Self {
+        BinaryExpr {
             operator,
-            operands,
+            operands: Box::new(Sides { lhs, rhs }),
 
             span,
             ty: None,
-        })
+        }
+    }
+}
+
+impl<'i> BinaryExpr<'i> {
+    /// `1 + 2` where a `u8` is expected is `1u8 + 2u8`, see [`Expr::infer_lit`]
+    pub fn infer_lit(&mut self, ty: &Type) -> Result<()> {
+        // operands without a suffix are `i64` or `f64` until then
+        if !self.ty.as_ref().is_some_and(Type::is_lit_default) {
+            return Ok(());
+        }
+        match self.operator {
+            BinaryOp::Add
+            | BinaryOp::Sub
+            | BinaryOp::Mul
+            | BinaryOp::Div
+            | BinaryOp::Rem
+            | BinaryOp::BitAnd
+            | BinaryOp::BitOr
+            | BinaryOp::BitXor => {
+                self.operands.lhs.infer_lit(ty)?;
+                self.operands.rhs.infer_lit(ty)?;
+            }
+            // the amount keeps its own type
+            BinaryOp::Shl | BinaryOp::Shr => self.operands.lhs.infer_lit(ty)?,
+            _ => return Ok(()),
+        }
+
+        let lhs = self.operands.lhs.type_of();
+        let rhs = self.operands.rhs.type_of();
+        if lhs == rhs || matches!(self.operator, BinaryOp::Shl | BinaryOp::Shr) {
+            self.ty = Some(lhs);
+        }
+        Ok(())
     }
 }
 
@@ -131,11 +218,32 @@ impl<'i> TypeOf<'i> for BinaryExpr<'i> {
         self.operands.lhs.type_check(vars)?;
         self.operands.rhs.type_check(vars)?;
 
+        // `x + 1` with `x: u8` is `x + 1u8`
         let op = self.operator;
         let lhs = self.operands.lhs.type_of();
+        self.operands.rhs.infer_lit(&lhs)?;
         let rhs = self.operands.rhs.type_of();
+        if !matches!(op, BinaryOp::Shl | BinaryOp::Shr) {
+            // the shifted value keeps its own type
+            self.operands.lhs.infer_lit(&rhs)?;
+        }
+
+        let lhs = self.operands.lhs.type_of();
+
+        let ty = match (&lhs, op, &rhs) {
+            // string ops
+            (Type::Str, BinaryOp::Add, Type::Str) => Ok(Type::Str),
+            (
+                Type::Str,
+                BinaryOp::Eq
+                | BinaryOp::Ne
+                | BinaryOp::Gt
+                | BinaryOp::Ge
+                | BinaryOp::Lt
+                | BinaryOp::Le,
+                Type::Str,
+            ) => Ok(Type::Bool),
 
-        let ty = match (lhs, op, rhs) {
             // boolean ops
             (
                 a,
@@ -144,28 +252,52 @@ impl<'i> TypeOf<'i> for BinaryExpr<'i> {
                 | BinaryOp::Gt
                 | BinaryOp::Ge
                 | BinaryOp::Lt
-                | BinaryOp::Le
-                | BinaryOp::Or
-                | BinaryOp::And,
+                | BinaryOp::Le,
                 b,
-            ) if a == b => Ok(Type::Bool),
+            ) if a == b
+                && !matches!(
+                    a,
+                    Type::Str
+                        | Type::Unit
+                        | Type::Named(_)
+                        | Type::Tuple(_)
+                        | Type::Array(..)
+                        | Type::Fn(..)
+                ) =>
+            {
+                Ok(Type::Bool)
+            }
+
+            // logic ops, `||` and `&&` skip the rhs when the lhs already decides the result
+            (Type::Bool | Type::Never, BinaryOp::Or | BinaryOp::And, Type::Bool | Type::Never) => {
+                Ok(Type::Bool)
+            }
+
+            // arithmetic ops, both sides need the same type, mixing widths needs an `as`
+            (
+                a,
+                BinaryOp::Add | BinaryOp::Sub | BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem,
+                b,
+            ) if a == b && a.is_number() => Ok(a.clone()),
+
+            // bitwise ops
+            (a, BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor, b)
+                if a == b && (a.is_int() || *a == Type::Bool) =>
+            {
+                Ok(a.clone())
+            }
+
+            // shifts, the amount can be any integer like in rust
+            (a, BinaryOp::Shl | BinaryOp::Shr, b) if a.is_int() && b.is_int() => Ok(a.clone()),
 
-            // arithmetic ops
-            (Type::I64, _, Type::I64) => Ok(Type::I64),
-            (Type::I64, _, Type::F64) => Ok(Type::F64),
-            (Type::F64, _, Type::I64) => Ok(Type::F64),
-            (Type::F64, _, Type::F64) => Ok(Type::F64),
+            // an operand that jumps out never produces a value to operate on
+            (Type::Never, _, _) | (_, _, Type::Never) => Ok(Type::Never),
 
-            // generic ops
-            (Type::Unresolved, _, Type::Unresolved) => Ok(Type::Unresolved),
+            // generic ops, recursive calls are unresolved until the function type is known
+            (Type::Unresolved, _, _) | (_, _, Type::Unresolved) => Ok(Type::Unresolved),
 
             // invalid ops
-            (lhs, op, rhs) => Err(Error::new_invalid_binary_op(
-                Span::new("unreachable", 0, 0).unwrap(),
-                lhs,
-                op,
-                rhs,
-            )),
+            (lhs, op, rhs) => Err(Error::new_invalid_binary_op(self.span(), lhs, op, rhs)),
         }?;
         log::debug!("{lhs} {op} {rhs} = {ty}");
         self.ty = Some(ty);
@@ -174,7 +306,7 @@ impl<'i> TypeOf<'i> for BinaryExpr<'i> {
     }
 
     fn type_of_impl(&self) -> Option<Type> {
-        self.ty
+        self.ty.clone()
     }
 }
 
@@ -1,4 +1,4 @@
-use super::{Ast, Expr, Result, Rule, Scope, VisibleVars};
+use super::{Ast, Expr, Result, Rule, Scope, Statement, Term, VisibleVars};
 use crate::ast::{match_rule, Error, Type, TypeOf};
 use pest::{iterators::Pair, Span};
 use std::fmt::Display;
@@ -9,7 +9,7 @@ use std::fmt::Display;
 pub struct BranchInternal<'i> {
     pub test: Expr<'i>,
     pub on_true: Scope<'i>,
-    pub on_false: Scope<'i>,
+    pub on_false: Option<Scope<'i>>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -34,7 +34,17 @@ impl<'i> Ast<'i> for Branch<'i> {
 
         let test = Expr::parse(tokens.next().unwrap())?;
         let on_true = Scope::parse(tokens.next().unwrap())?;
-        let on_false = Scope::parse(tokens.next().unwrap())?;
+        let on_false = match tokens.next() {
+            // `else if` is lowered into `else { if .. }`
+            Some(token) if token.as_rule() == Rule::branch => {
+                let branch = Branch::parse(token)?;
+                let span = branch.span();
+                let statement = Statement::from(Expr::from(Term::from(branch)));
+                Some(Scope::global(vec![statement], span))
+            }
+            Some(token) => Some(Scope::parse(token)?),
+            None => None,
+        };
 
         Ok(Self {
             internal: Box::new(BranchInternal {
@@ -53,46 +63,81 @@ impl<'i> TypeOf<'i> for Branch<'i> {
     fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
         self.internal.test.type_check(vars)?;
         self.internal.on_true.type_check(vars)?;
-        self.internal.on_false.type_check(vars)?;
+        if let Some(on_false) = self.internal.on_false.as_mut() {
+            on_false.type_check(vars)?;
+        }
+
+        // `if c { x } else { 1 }` with `x: u8` is `if c { x } else { 1u8 }`
+        if let Some(on_false) = self.internal.on_false.as_mut() {
+            on_false.infer_lit(&self.internal.on_true.type_of())?;
+            self.internal.on_true.infer_lit(&on_false.type_of())?;
+        }
 
         let ty_test = self.internal.test.type_of();
-        let ty_true = self.internal.on_true.type_of();
-        let ty_false = self.internal.on_false.type_of();
+        if !matches!(ty_test, Type::Bool | Type::Never | Type::Unresolved) {
+            return Err(Error::new_type_mismatch(
+                self.internal.test.span(),
+                &Type::Bool,
+                &ty_test,
+            ));
+        }
 
-        self.ty = match (ty_test, ty_true, ty_false, ty_true == ty_false) {
-            (Type::Bool, _, _, true) => Some(ty_true),
-            (ty, _, _, true) => {
-                return Err(Error::new_type_mismatch(
-                    self.internal.test.span(),
-                    &Type::Bool,
-                    &ty,
-                ))
-            }
-            (_, Type::Unresolved, ty_false, false) => Some(ty_false),
-            (_, ty_true, Type::Unresolved, false) => Some(ty_true),
-            (_, ty_true, ty_false, false) => {
+        self.ty = Some(self.unify()?);
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Branch<'i> {
+    /// gives the number literals both blocks evaluate to the number type `ty`,
+    /// see [`Expr::infer_lit`]
+    pub fn infer_lit(&mut self, ty: &Type) -> Result<()> {
+        if !self.ty.as_ref().is_some_and(Type::is_lit_default) {
+            return Ok(());
+        }
+        self.internal.on_true.infer_lit(ty)?;
+        if let Some(on_false) = self.internal.on_false.as_mut() {
+            on_false.infer_lit(ty)?;
+        }
+        self.ty = Some(self.unify()?);
+        Ok(())
+    }
+
+    /// the type of the value of the branch, both blocks need to produce the same one
+    fn unify(&self) -> Result<Type> {
+        let ty_true = self.internal.on_true.type_of();
+        let (ty_false, span_false) = match self.internal.on_false.as_ref() {
+            Some(on_false) => (on_false.type_of(), on_false.span()),
+            // without the else block, the on_true block cannot produce a value
+            None if matches!(ty_true, Type::Unit | Type::Never) => (Type::Unit, self.span()),
+            None => {
                 return Err(Error::new_type_mismatch(
-                    self.internal.on_false.span(),
+                    self.internal.on_true.span(),
+                    &Type::Unit,
                     &ty_true,
-                    &ty_false,
                 ))
             }
         };
 
-        Ok(())
-    }
-
-    fn type_of_impl(&self) -> Option<Type> {
-        self.ty
+        match (ty_true, ty_false) {
+            (ty_true, ty_false) if ty_true == ty_false => Ok(ty_true),
+            (Type::Unresolved | Type::Never, ty_false) => Ok(ty_false),
+            (ty_true, Type::Unresolved | Type::Never) => Ok(ty_true),
+            (ty_true, ty_false) => Err(Error::new_type_mismatch(span_false, &ty_true, &ty_false)),
+        }
     }
 }
 
 impl<'i> Display for Branch<'i> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "if {} {} else {}",
-            self.internal.test, self.internal.on_true, self.internal.on_false
-        )
+        write!(f, "if {} {}", self.internal.test, self.internal.on_true)?;
+        if let Some(on_false) = self.internal.on_false.as_ref() {
+            write!(f, " else {on_false}")?;
+        }
+        Ok(())
     }
 }
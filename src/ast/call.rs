@@ -1,4 +1,6 @@
-use super::{Ast, Expr, Function, Ident, Result, Rule, Type, TypeOf, VisibleVars};
+use super::{
+    Ast, Error, Expr, Function, Ident, Impl, Result, Rule, Term, Type, TypeOf, VisibleVars,
+};
 use crate::ast::match_rule;
 use pest::{iterators::Pair, Span};
 use std::fmt::Display;
@@ -9,6 +11,10 @@ use std::fmt::Display;
 pub struct Call<'i> {
     pub name: Ident<'i>,
     pub args: Vec<Expr<'i>>,
+    /// calls the function value of the variable `name` instead of the function `name`
+    pub indirect: bool,
+    /// `a.name(b)`, the receiver `a` is the first argument
+    pub method: bool,
 
     span: Span<'i>,
     ty: Option<Type>,
@@ -16,6 +22,18 @@ pub struct Call<'i> {
 
 //
 
+/// return type of the builtin function `name` with params `sig`, if there is one
+///
+/// `len(array)` and `len(str)` are the only builtins,
+/// the length of a string is counted in bytes,
+/// functions named like a builtin shadow it outside of method calls
+pub fn builtin_ty(name: &str, sig: &[Type]) -> Option<Type> {
+    match (name, sig) {
+        ("len", [Type::Array(..) | Type::Str]) => Some(Type::I64),
+        _ => None,
+    }
+}
+
 impl<'i> Ast<'i> for Call<'i> {
     fn span(&self) -> Span<'i> {
         self.span.clone()
@@ -28,13 +46,14 @@ impl<'i> Ast<'i> for Call<'i> {
 
         let name: Ident = Ast::parse(tokens.next().unwrap())?;
         let args = tokens
-            .next()
-            .map(|token| Ast::parse_multiple(token.into_inner()))
-            .unwrap_or_else(|| Ok(vec![]))?;
+            .map(|token| Ast::parse_single(token.into_inner()))
+            .collect::<Result<_>>()?;
 
         Ok(Self {
             name,
             args,
+            indirect: false,
+            method: false,
             span,
             ty: None,
         })
@@ -43,15 +62,51 @@ impl<'i> Ast<'i> for Call<'i> {
 
 impl<'i> TypeOf<'i> for Call<'i> {
     fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
-        for arg in self.args.iter_mut() {
+        // the receiver of a method is checked first, it decides the function
+        let checked = if self.method {
+            self.resolve_method(vars)?;
+            1
+        } else {
+            0
+        };
+
+        // variables shadow functions
+        if !self.method {
+            if let Some(ty) = vars.get_var(&self.name.value) {
+                return self.type_check_indirect(vars, ty);
+            }
+            self.name.value = vars.resolve_name(&self.name.value);
+        }
+        let fn_name = self.name.value.as_str();
+
+        // `f(|x| x + 1)` with `fn f(g: fn(i64) -> i64)` is `f(|x: i64| x + 1)`
+        let params = vars
+            .get_fn_params(fn_name)
+            .or_else(|| Some(vars.get_host_fn(fn_name)?.arg_ty.to_vec()));
+        for (arg, ty) in self.args.iter_mut().zip(params.iter().flatten()) {
+            arg.infer_params(ty);
+        }
+
+        for arg in self.args.iter_mut().skip(checked) {
             arg.type_check(vars)?;
         }
 
-        let fn_name = self.name.value.as_str();
+        // `f(1)` with `fn f(x: u8)` is `f(1u8)`
+        for (arg, ty) in self.args.iter_mut().zip(params.iter().flatten()) {
+            arg.infer_lit(ty)?;
+        }
+
         let sig: Box<[Type]> = self.args.iter().map(|arg| arg.type_of()).collect();
 
-        let ty = if let Ok(ty) = vars.get_fn_ty(self.span(), fn_name, &sig) {
+        // script and host functions shadow builtins, unless they are called as methods
+        let builtin = builtin_ty(fn_name, &sig).filter(|_| self.method || params.is_none());
+        let ty = if let Some(ty) = builtin {
+            ty
+        } else if let Some(ty) = vars.get_host_fn_ty(self.span(), fn_name, &sig)? {
             ty
+        } else if vars.has_fn(fn_name, &sig) {
+            // errors in the function body are reported by its first call
+            vars.get_fn_ty(self.span(), fn_name, &sig)?
         } else {
             let f = Function::new(vars, self.span(), fn_name, &sig)?;
             let ty = f.type_of();
@@ -65,12 +120,87 @@ impl<'i> TypeOf<'i> for Call<'i> {
     }
 
     fn type_of_impl(&self) -> Option<Type> {
-        self.ty
+        self.ty.clone()
+    }
+}
+
+impl<'i> Call<'i> {
+    pub fn new_method(
+        span: Span<'i>,
+        receiver: Term<'i>,
+        name: Ident<'i>,
+        args: Vec<Expr<'i>>,
+    ) -> Self {
+        Self {
+            name,
+            args: std::iter::once(receiver.into()).chain(args).collect(),
+            indirect: false,
+            method: true,
+            span,
+            ty: None,
+        }
+    }
+
+    /// `a.len()` with `a: Vec2` calls `Vec2::len(a)`,
+    /// builtins like `len` can be called as methods too
+    fn resolve_method(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        let receiver = &mut self.args[0];
+        receiver.type_check(vars)?;
+        let ty = receiver.type_of();
+
+        let name = Impl::method_name(&ty, &self.name.value);
+        if vars.get_fn_params(&name).is_some() {
+            self.name.value = name;
+        } else if self.args.len() != 1
+            || builtin_ty(&self.name.value, std::slice::from_ref(&ty)).is_none()
+        {
+            return Err(Error::new_method_not_found(
+                self.name.span(),
+                &ty,
+                &self.name.value,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// calls through a variable holding a function value of type `ty`
+    fn type_check_indirect(&mut self, vars: &mut VisibleVars<'i>, ty: Type) -> Result<()> {
+        let (params, ret) = match ty {
+            Type::Fn(params, ret) => (params, *ret),
+            _ => return Err(Error::new_not_callable(self.span(), &self.name.value)),
+        };
+        if params.len() != self.args.len() {
+            return Err(Error::new_argc_mismatch(
+                self.span(),
+                params.len(),
+                self.args.len(),
+            ));
+        }
+
+        for (arg, ty) in self.args.iter_mut().zip(params.iter()) {
+            arg.infer_params(ty);
+            arg.type_check(vars)?;
+            arg.infer_lit(ty)?;
+
+            let got = arg.type_of();
+            if got != *ty && got != Type::Never {
+                return Err(Error::new_type_mismatch(arg.span(), ty, &got));
+            }
+        }
+
+        self.indirect = true;
+        self.ty = Some(ret);
+
+        Ok(())
     }
 }
 
 impl<'i> Display for Call<'i> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.method {
+            return write!(f, "{}.{}()", self.args[0], self.name);
+        }
         write!(f, "{}()", self.name)
     }
 }
@@ -0,0 +1,94 @@
+use super::{match_rule, Ast, Error, Expr, Result, Rule, Type, TypeOf, VisibleVars};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cast<'i> {
+    pub expr: Box<Expr<'i>>,
+    pub target: Type,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> Cast<'i> {
+    /// whether values of `from` can be cast into `to` with `as`
+    ///
+    /// numbers cast into each other and bools cast into integers, like in rust
+    pub fn is_valid(from: &Type, to: &Type) -> bool {
+        match (from, to) {
+            (Type::Never | Type::Unresolved, _) => true,
+            (from, to) if from.is_number() => to.is_number(),
+            (Type::Bool, to) => to.is_int() || *to == Type::Bool,
+            _ => false,
+        }
+    }
+}
+
+impl<'i> Ast<'i> for Cast<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::cast)?;
+        let mut tokens = token.into_inner();
+
+        let mut expr = Expr::parse_operand(tokens.next().unwrap())?;
+
+        // `a as b as c` is `(a as b) as c`
+        let mut targets = tokens
+            .map(|token| Ok((token.as_span(), Type::parse(token)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let (_, target) = targets.pop().unwrap();
+        for (target_span, target) in targets {
+            let span = span.start_pos().span(&target_span.end_pos());
+            expr = Expr::from(Cast {
+                expr: Box::new(expr),
+                target,
+
+                span,
+                ty: None,
+            });
+        }
+
+        Ok(Self {
+            expr: Box::new(expr),
+            target,
+
+            span,
+            ty: None,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for Cast<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        self.expr.type_check(vars)?;
+        self.target = vars.resolve_ty(&self.target);
+
+        let from = self.expr.type_of();
+        if !Self::is_valid(&from, &self.target) {
+            return Err(Error::new_invalid_cast(self.span(), &from, &self.target));
+        }
+
+        self.ty = Some(self.target.clone());
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Display for Cast<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({} as {})", self.expr, self.target)
+    }
+}
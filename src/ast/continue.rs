@@ -0,0 +1,46 @@
+use super::{match_rule, Ast, Result, Rule, Type, TypeOf, VisibleVars};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Continue<'i> {
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> Ast<'i> for Continue<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::continue_expr)?;
+
+        Ok(Self { span, ty: None })
+    }
+}
+
+impl<'i> TypeOf<'i> for Continue<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        vars.continue_loop(self.span())?;
+
+        self.ty = Some(Type::Never);
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Display for Continue<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "continue")
+    }
+}
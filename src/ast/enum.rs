@@ -0,0 +1,105 @@
+use super::{match_rule, Ast, Error, Ident, Result, Rule, Type, TypeOf, VisibleVars};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enum<'i> {
+    pub name: Ident<'i>,
+    pub variants: Vec<Variant<'i>>,
+    /// the index of the file the enum is declared in, see [`super::FunctionGenInternal::file`]
+    pub file: usize,
+
+    span: Span<'i>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant<'i> {
+    pub name: Ident<'i>,
+    pub fields: Vec<Type>,
+}
+
+//
+
+impl<'i> Enum<'i> {
+    /// index of the variant called `name`, which is also its tag
+    pub fn variant(&self, name: &str) -> Option<(usize, &Variant<'i>)> {
+        self.variants
+            .iter()
+            .enumerate()
+            .find(|(_, variant)| variant.name.value == name)
+    }
+}
+
+impl<'i> Ast<'i> for Enum<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::enum_decl)?;
+        let mut tokens = token.into_inner();
+
+        let name = Ident::parse(tokens.next().unwrap())?;
+        let variants = tokens
+            .map(|token| {
+                let mut tokens = token.into_inner();
+                let name = Ident::parse(tokens.next().unwrap())?;
+                let fields = tokens.map(Type::parse).collect::<Result<_>>()?;
+                Ok(Variant { name, fields })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            name,
+            variants,
+            file: 0,
+            span,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for Enum<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        for (i, variant) in self.variants.iter().enumerate() {
+            let name = variant.name.value.as_str();
+            if self.variants[..i]
+                .iter()
+                .any(|other| other.name.value == name)
+            {
+                return Err(Error::new_duplicate_variant(variant.name.span(), name));
+            }
+
+            for ty in variant.fields.iter() {
+                vars.check_ty(variant.name.span(), ty)?;
+            }
+        }
+        vars.check_recursion(self.span(), &self.name.value)?;
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        Some(Type::Named(self.name.value.clone()))
+    }
+}
+
+impl<'i> Display for Enum<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "enum {} {{ ", self.name)?;
+        for variant in self.variants.iter() {
+            write!(f, "{}", variant.name)?;
+            if !variant.fields.is_empty() {
+                write!(f, "(")?;
+                for field in variant.fields.iter() {
+                    write!(f, "{field}, ")?;
+                }
+                write!(f, ")")?;
+            }
+            write!(f, ", ")?;
+        }
+        write!(f, "}}")
+    }
+}
@@ -0,0 +1,104 @@
+use super::{match_rule, Ast, Error, Expr, Ident, Result, Rule, Type, TypeOf, VisibleVars};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumLit<'i> {
+    pub name: Ident<'i>,
+    pub variant: Ident<'i>,
+    pub args: Vec<Expr<'i>>,
+
+    /// index of the variant in the enum declaration, which is also its tag
+    pub index: usize,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> Ast<'i> for EnumLit<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::enum_lit)?;
+        let mut tokens = token.into_inner();
+
+        let name = Ident::parse(tokens.next().unwrap())?;
+        let variant = Ident::parse(tokens.next().unwrap())?;
+        let args = tokens
+            .map(|token| Ast::parse_single(token.into_inner()))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            name,
+            variant,
+            args,
+            index: 0,
+
+            span,
+            ty: None,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for EnumLit<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        self.name.value = vars.resolve_name(&self.name.value);
+        let ty = Type::Named(self.name.value.clone());
+        let decl = vars.get_enum(self.name.span(), &self.name.value)?;
+        let (index, variant) = match decl.variant(&self.variant.value) {
+            Some(variant) => variant,
+            None => {
+                return Err(Error::new_variant_not_found(
+                    self.variant.span(),
+                    &ty,
+                    &self.variant.value,
+                ))
+            }
+        };
+        let fields = variant.fields.clone();
+        self.index = index;
+
+        let expect = fields.len();
+        let got = self.args.len();
+        if expect != got {
+            return Err(Error::new_argc_mismatch(self.span(), expect, got));
+        }
+
+        for (arg, expect) in self.args.iter_mut().zip(fields.iter()) {
+            arg.type_check(vars)?;
+            let got = arg.type_of();
+            if *expect != got && got != Type::Never {
+                return Err(Error::new_type_mismatch(arg.span(), expect, &got));
+            }
+        }
+
+        self.ty = Some(ty);
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Display for EnumLit<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}::{}", self.name, self.variant)?;
+        if !self.args.is_empty() {
+            write!(f, "(")?;
+            for arg in self.args.iter() {
+                write!(f, "{arg}, ")?;
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
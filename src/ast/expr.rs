@@ -1,5 +1,5 @@
 use super::{
-    match_rule, Ast, BinaryExpr, Result, Rule, Term, Type, TypeOf, UnaryExpr, VisibleVars,
+    match_rule, Ast, BinaryExpr, Cast, Result, Rule, Term, Type, TypeOf, UnaryExpr, VisibleVars,
 };
 use lazy_static::lazy_static;
 use pest::{
@@ -12,12 +12,13 @@ use std::fmt::Display;
 lazy_static! {
     static ref PREC_CLIMBER: PrecClimber<Rule> = {
         use pest::prec_climber::Assoc::*;
+        // from loosest to tightest, like in rust
         PrecClimber::new(vec![
             //
-            Operator::new(Rule::add, Left) | Operator::new(Rule::sub, Left),
+            Operator::new(Rule::or, Left),
 
             //
-            Operator::new(Rule::mul, Left) | Operator::new(Rule::div, Left),
+            Operator::new(Rule::and, Left),
 
             //
             Operator::new(Rule::eq, Left)
@@ -28,10 +29,24 @@ lazy_static! {
                 | Operator::new(Rule::le, Left),
 
             //
-            Operator::new(Rule::and, Left),
+            Operator::new(Rule::bit_or, Left),
 
             //
-            Operator::new(Rule::or, Left),
+            Operator::new(Rule::bit_xor, Left),
+
+            //
+            Operator::new(Rule::bit_and, Left),
+
+            //
+            Operator::new(Rule::shl, Left) | Operator::new(Rule::shr, Left),
+
+            //
+            Operator::new(Rule::add, Left) | Operator::new(Rule::sub, Left),
+
+            //
+            Operator::new(Rule::mul, Left)
+                | Operator::new(Rule::div, Left)
+                | Operator::new(Rule::rem, Left),
         ])
     };
 }
@@ -40,6 +55,7 @@ lazy_static! {
 pub enum ExprInternal<'i> {
     BinaryExpr(BinaryExpr<'i>),
     UnaryExpr(UnaryExpr<'i>),
+    Cast(Cast<'i>),
     Term(Term<'i>),
 }
 
@@ -62,29 +78,7 @@ impl<'i> Ast<'i> for Expr<'i> {
 
         PREC_CLIMBER.climb(
             token.into_inner(),
-            |token: Pair<Rule>| match token.as_rule() {
-                Rule::term => {
-                    let inner = Term::parse(token)?;
-
-                    Ok(Expr {
-                        internal: Box::new(ExprInternal::Term(inner)),
-
-                        span: span.clone(),
-                        ty: None,
-                    })
-                }
-                Rule::unary => {
-                    let inner = UnaryExpr::parse(token)?;
-
-                    Ok(Expr {
-                        internal: Box::new(ExprInternal::UnaryExpr(inner)),
-
-                        span: span.clone(),
-                        ty: None,
-                    })
-                }
-                _ => unreachable!("{:?}", token),
-            },
+            Expr::parse_operand,
             |lhs: Result<Expr>, op: Pair<Rule>, rhs: Result<Expr>| {
                 let inner = BinaryExpr::new(span.clone(), lhs?, op, rhs?)?;
 
@@ -99,11 +93,93 @@ impl<'i> Ast<'i> for Expr<'i> {
     }
 }
 
+impl<'i> Expr<'i> {
+    /// parses a single operand of a binary, unary or cast expression
+    pub fn parse_operand(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        let internal = match token.as_rule() {
+            Rule::term => ExprInternal::Term(Term::parse(token)?),
+            Rule::unary => ExprInternal::UnaryExpr(UnaryExpr::parse(token)?),
+            Rule::cast => ExprInternal::Cast(Cast::parse(token)?),
+            Rule::expr => return Expr::parse(token),
+            _ => unreachable!("{:?}", token),
+        };
+
+        Ok(Expr {
+            internal: Box::new(internal),
+
+            span,
+            ty: None,
+        })
+    }
+
+    /// gives the number literals without a suffix the expression evaluates to
+    /// the number type `ty`, see [`Term::infer_lit`]
+    pub fn infer_lit(&mut self, ty: &Type) -> Result<()> {
+        let inferred = match self.internal.as_mut() {
+            ExprInternal::Term(term) => {
+                term.infer_lit(ty)?;
+                term.type_of()
+            }
+            ExprInternal::BinaryExpr(binary) => {
+                binary.infer_lit(ty)?;
+                binary.type_of()
+            }
+            ExprInternal::UnaryExpr(unary) => {
+                unary.infer_lit(ty)?;
+                unary.type_of()
+            }
+            ExprInternal::Cast(_) => return Ok(()),
+        };
+        self.ty = Some(inferred);
+        Ok(())
+    }
+
+    /// gives a lambda without param type annotations
+    /// the param types of `ty`, see [`Term::infer_params`]
+    pub fn infer_params(&mut self, ty: &Type) {
+        if let ExprInternal::Term(term) = self.internal.as_mut() {
+            term.infer_params(ty);
+        }
+    }
+}
+
+impl<'i> From<Term<'i>> for Expr<'i> {
+    fn from(term: Term<'i>) -> Self {
+        Expr {
+            span: term.span(),
+            internal: Box::new(ExprInternal::Term(term)),
+            ty: None,
+        }
+    }
+}
+
+impl<'i> From<BinaryExpr<'i>> for Expr<'i> {
+    fn from(expr: BinaryExpr<'i>) -> Self {
+        Expr {
+            span: expr.span(),
+            internal: Box::new(ExprInternal::BinaryExpr(expr)),
+            ty: None,
+        }
+    }
+}
+
+impl<'i> From<Cast<'i>> for Expr<'i> {
+    fn from(cast: Cast<'i>) -> Self {
+        Expr {
+            span: cast.span(),
+            internal: Box::new(ExprInternal::Cast(cast)),
+            ty: None,
+        }
+    }
+}
+
 impl<'i> TypeOf<'i> for Expr<'i> {
     fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
         let internal = match self.internal.as_mut() {
             ExprInternal::BinaryExpr(expr) => expr as &mut dyn TypeOf,
             ExprInternal::UnaryExpr(expr) => expr as _,
+            ExprInternal::Cast(cast) => cast as _,
             ExprInternal::Term(term) => term as _,
         };
 
@@ -114,7 +190,7 @@ impl<'i> TypeOf<'i> for Expr<'i> {
     }
 
     fn type_of_impl(&self) -> Option<Type> {
-        self.ty
+        self.ty.clone()
     }
 }
 
@@ -123,6 +199,7 @@ impl<'i> Display for Expr<'i> {
         match self.internal.as_ref() {
             ExprInternal::BinaryExpr(v) => v as &dyn Display,
             ExprInternal::UnaryExpr(v) => v as _,
+            ExprInternal::Cast(v) => v as _,
             ExprInternal::Term(v) => v as _,
         }
         .fmt(f)
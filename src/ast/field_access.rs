@@ -0,0 +1,73 @@
+use super::{Ast, Error, Ident, Result, Term, Type, TypeOf, VisibleVars};
+use pest::Span;
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldAccess<'i> {
+    pub term: Term<'i>,
+    pub field: Ident<'i>,
+
+    /// index of the field in the struct declaration or tuple
+    pub index: usize,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> FieldAccess<'i> {
+    pub fn new(span: Span<'i>, term: Term<'i>, field: Ident<'i>) -> Self {
+        Self {
+            term,
+            field,
+            index: 0,
+
+            span,
+            ty: None,
+        }
+    }
+
+    pub fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+}
+
+impl<'i> TypeOf<'i> for FieldAccess<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        self.term.type_check(vars)?;
+        let ty = self.term.type_of();
+        let name = self.field.value.as_str();
+
+        let (index, field_ty) = match &ty {
+            Type::Named(struct_name) => vars
+                .get_struct(self.term.span(), struct_name)
+                .ok()
+                .and_then(|s| s.field(name))
+                .map(|(index, field_ty)| (index, field_ty.clone())),
+            Type::Tuple(tys) => name
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| Some((index, tys.get(index)?.clone()))),
+            _ => None,
+        }
+        .ok_or_else(|| Error::new_field_not_found(self.field.span(), &ty, name))?;
+
+        self.index = index;
+        self.ty = Some(field_ty);
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Display for FieldAccess<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.term, self.field)
+    }
+}
@@ -0,0 +1,140 @@
+use super::{Error, Result, Rule, ToyLangParser};
+use pest::{Parser, Span};
+use std::path::{Path, PathBuf};
+
+//
+
+/// extension of script files, `mod a::b;` loads `a/b.tls`
+pub const EXTENSION: &str = "tls";
+
+/// the root file of a script and every file it uses through `mod`
+#[derive(Debug, Clone)]
+pub struct Files {
+    files: Vec<File>,
+}
+
+#[derive(Debug, Clone)]
+pub struct File {
+    pub path: PathBuf,
+    /// `a::b` for the file `a/b.tls`, empty for the root file
+    pub module: String,
+    pub source: String,
+}
+
+/// one source of a script, the items of `module` are called `module::item`
+#[derive(Debug, Clone, Copy)]
+pub struct Source<'i> {
+    pub path: Option<&'i Path>,
+    pub module: &'i str,
+    pub source: &'i str,
+}
+
+//
+
+impl Files {
+    pub fn new(path: PathBuf, source: String) -> Self {
+        Self {
+            files: vec![File {
+                path,
+                module: String::new(),
+                source,
+            }],
+        }
+    }
+
+    /// reads every file the root file declares with `mod`, and the files they declare,
+    /// each file is read once even if multiple files declare it
+    ///
+    /// modules that declare each other are an error
+    pub fn load_modules(&mut self) -> Result<()> {
+        self.load(0, &mut vec![])
+    }
+
+    pub fn sources(&self) -> Vec<Source<'_>> {
+        self.files
+            .iter()
+            .map(|file| Source {
+                path: Some(file.path.as_path()),
+                module: file.module.as_str(),
+                source: file.source.as_str(),
+            })
+            .collect()
+    }
+
+    fn load(&mut self, i: usize, stack: &mut Vec<usize>) -> Result<()> {
+        stack.push(i);
+        for (module, start, end) in self.mod_decls(i)? {
+            let path = self.module_path(&module);
+            if let Some(j) = self.files.iter().position(|file| file.path == path) {
+                if let Some(cycle) = stack.iter().position(|&k| k == j) {
+                    let cycle = stack[cycle..]
+                        .iter()
+                        .chain(std::iter::once(&j))
+                        .map(|&k| self.files[k].path.display().to_string())
+                        .collect::<Vec<_>>();
+                    let span = Span::new(&self.files[i].source, start, end).unwrap();
+                    return Err(Error::new_cyclic_modules(span, &cycle.join(" -> "))
+                        .with_file(&self.files[i].path));
+                }
+                continue;
+            }
+
+            let source = match std::fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(err) => {
+                    let span = Span::new(&self.files[i].source, start, end).unwrap();
+                    return Err(Error::new_module_not_loaded(span, &module, &path, &err)
+                        .with_file(&self.files[i].path));
+                }
+            };
+            self.files.push(File {
+                path,
+                module,
+                source,
+            });
+            self.load(self.files.len() - 1, stack)?;
+        }
+        stack.pop();
+        Ok(())
+    }
+
+    /// the modules the file `i` declares, with the positions of their names
+    fn mod_decls(&self, i: usize) -> Result<Vec<(String, usize, usize)>> {
+        let file = &self.files[i];
+        let mut tokens = ToyLangParser::parse(Rule::input, &file.source)
+            .map_err(|err| Error::new_pest(err).with_file(&file.path))?;
+
+        Ok(tokens
+            .next()
+            .unwrap()
+            .into_inner()
+            .filter(|token| token.as_rule() == Rule::mod_decl)
+            .map(|token| {
+                let path = token.into_inner().next().unwrap();
+                let span = path.as_span();
+                (module_name(path), span.start(), span.end())
+            })
+            .collect())
+    }
+
+    /// modules are looked up relative to the directory of the root file
+    fn module_path(&self, module: &str) -> PathBuf {
+        let mut path = self.files[0]
+            .path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        path.extend(module.split("::"));
+        path.set_extension(EXTENSION);
+        path
+    }
+}
+
+/// `a::b` from a `mod_path`, without the whitespace it might have
+pub(super) fn module_name(token: pest::iterators::Pair<Rule>) -> String {
+    token
+        .into_inner()
+        .map(|token| token.as_str())
+        .collect::<Vec<_>>()
+        .join("::")
+}
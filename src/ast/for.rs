@@ -0,0 +1,96 @@
+use super::{
+    match_rule, Ast, Error, Expr, Pattern, Result, Rule, Scope, Type, TypeOf, VisibleVars,
+};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForInternal<'i> {
+    pub pattern: Pattern<'i>,
+    pub expr: Expr<'i>,
+    pub scope: Scope<'i>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct For<'i> {
+    pub internal: Box<ForInternal<'i>>,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> Ast<'i> for For<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::for_loop)?;
+        let mut tokens = token.into_inner();
+
+        let pattern = Pattern::parse(tokens.next().unwrap())?;
+        let expr = Expr::parse(tokens.next().unwrap())?;
+        let scope = Scope::parse(tokens.next().unwrap())?;
+
+        Ok(Self {
+            internal: Box::new(ForInternal {
+                pattern,
+                expr,
+                scope,
+            }),
+
+            span,
+            ty: None,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for For<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        self.internal.expr.type_check(vars)?;
+
+        let ty = match self.internal.expr.type_of() {
+            Type::Array(ty, _) => *ty,
+            ty => return Err(Error::new_not_iterable(self.internal.expr.span(), &ty)),
+        };
+
+        // the elements are only visible in the loop
+        vars.push();
+        self.internal.pattern.check(vars, &ty)?;
+        if let Some(witness) = Pattern::missing(vars, &[&self.internal.pattern], &ty) {
+            return Err(Error::new_refutable_pattern(
+                self.internal.pattern.span(),
+                &witness,
+            ));
+        }
+
+        // for loops can exit without a break, so breaks cannot carry values
+        vars.push_loop(Some(Type::Unit));
+        self.internal.scope.type_check(vars)?;
+        vars.pop_loop();
+        vars.pop();
+
+        self.ty = Some(Type::Unit);
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Display for For<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "for {} in {} {}",
+            self.internal.pattern, self.internal.expr, self.internal.scope
+        )
+    }
+}
@@ -1,7 +1,7 @@
 use super::{FunctionGen, Ident, ParamGen, Scope, Statement, Type, VisibleVars};
-use crate::ast::{generic_mangle, Error, Result, TypeOf};
+use crate::ast::{generic_mangle, Ast, Error, Result, TypeOf};
 use pest::Span;
-use std::fmt::Display;
+use std::{collections::HashMap, fmt::Display};
 
 //
 
@@ -11,6 +11,11 @@ pub struct FunctionInternal<'i> {
     pub params: Vec<Param<'i>>,
     pub fn_ty: FnTy<'i>,
     pub scope: Scope<'i>,
+    /// the type of `self` and `Self` in methods
+    pub self_ty: Option<Type>,
+    /// the index of the file the function is declared in,
+    /// its body refers to items by their names in that file
+    pub file: usize,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -29,6 +34,21 @@ pub struct Param<'i> {
     pub ty: Type,
 }
 
+impl<'i> Param<'i> {
+    /// `()` and `!` have no value that could be passed
+    pub(super) fn check_passable(&self) -> Result<()> {
+        if matches!(self.ty, Type::Unit | Type::Never) {
+            Err(Error::new_invalid_param_ty(
+                self.span.clone(),
+                self.ident.value.as_str(),
+                &self.ty,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct FnTy<'i> {
     span: Span<'i>,
@@ -38,14 +58,19 @@ pub struct FnTy<'i> {
 //
 
 impl<'i> Function<'i> {
-    pub fn new_non_generic(gen: FunctionGen<'i>) -> std::result::Result<Self, FunctionGen> {
-        if gen.internal.fn_ty.ty == Type::Unresolved {
+    pub fn new_non_generic(gen: FunctionGen<'i>) -> std::result::Result<Self, FunctionGen<'i>> {
+        if !gen.internal.generics.is_empty() || gen.internal.fn_ty.ty == Type::Unresolved {
             return Err(gen);
         }
 
-        let sig: Box<[Type]> = gen.internal.params.iter().map(|param| param.ty).collect();
+        let sig: Box<[Type]> = gen
+            .internal
+            .params
+            .iter()
+            .map(|param| param.ty.clone())
+            .collect();
 
-        if sig.iter().any(|ty| matches!(ty, Type::Unresolved)) {
+        if sig.iter().any(|ty| ty.is_generic(&[])) {
             return Err(gen);
         }
 
@@ -60,7 +85,7 @@ impl<'i> Function<'i> {
             .collect();
 
         let span = Span::new("", 0, 0).unwrap();
-        let ty = gen.internal.fn_ty.ty;
+        let ty = gen.internal.fn_ty.ty.clone();
         Ok(Self {
             internal: Box::new(FunctionInternal {
                 name: gen.internal.name,
@@ -70,6 +95,8 @@ impl<'i> Function<'i> {
                     ty: gen.internal.fn_ty.ty,
                 },
                 scope: gen.internal.scope,
+                self_ty: gen.internal.self_ty,
+                file: gen.internal.file,
             }),
             type_checking: false,
             span,
@@ -94,32 +121,118 @@ impl<'i> Function<'i> {
             return Err(Error::new_argc_mismatch(call_site, expect, got));
         }
 
+        // `fn f<T>(a: T, b: T)` called as `f(1, 2)` binds `T` to `i64`
+        let generics = gen.generic_names();
+        let mut bindings = HashMap::new();
+        if let Some(self_ty) = gen.internal.self_ty.as_ref() {
+            bindings.insert("Self".into(), self_ty.clone());
+        }
+        for (param, ty) in gen.internal.params.iter().zip(sig.iter()) {
+            if !param.ty.bind_generics(ty, &generics, &mut bindings) {
+                let expect = param.ty.resolve_generics(&bindings);
+                return Err(Error::new_type_mismatch(call_site, &expect, ty));
+            }
+        }
+        for generic in gen.internal.generics.iter() {
+            let name = generic.ident.value.as_str();
+            let ty = bindings
+                .get(name)
+                .ok_or_else(|| Error::new_unbound_generic(call_site.clone(), name))?;
+            for bound in generic.bounds.iter() {
+                if !vars.has_bound(bound, ty)? {
+                    return Err(Error::new_unsatisfied_bound(
+                        call_site,
+                        ty,
+                        name,
+                        &bound.value,
+                    ));
+                }
+            }
+        }
+
         let params: Vec<Param> = gen
             .internal
             .params
             .iter()
             .zip(sig.iter())
-            .map(|(param, &ty)| Param {
+            .map(|(param, ty)| Param {
                 ident: param.ident.clone(),
                 span: param.span.clone(),
-                ty,
+                ty: ty.clone(),
             })
             .collect();
+        let file = gen.internal.file;
+        params
+            .iter()
+            .try_for_each(Param::check_passable)
+            .map_err(|err| err.in_source(file))?;
 
-        let mut scope = gen.internal.scope.clone();
+        let body = gen.internal.scope.clone();
+        let self_ty = gen.internal.self_ty.clone();
 
         let name = gen.internal.name.value.clone();
+        let name_span = gen.internal.name.span();
+        let declared = gen
+            .internal
+            .fn_ty
+            .declared()
+            .map(|ty| ty.resolve_generics(&bindings));
+        let declared_span = gen.internal.fn_ty.span.clone();
 
-        // type checking
+        // type checking, recursive calls see the declared type
+        let mangled = generic_mangle(sig, &name);
+        let mut recursive_ty = declared.clone().unwrap_or(Type::Unresolved);
+        let instances = vars.instance_names();
+        let (scope, ty) = loop {
+            vars.fn_ty_cache
+                .insert(mangled.clone(), recursive_ty.clone());
+            let mut scope = body.clone();
+            let outer = vars.push_fn_body(declared.clone());
+            vars.enter_file(file);
+            vars.bind_generics(bindings.clone());
+            vars.push();
+            params.iter().for_each(|param| {
+                vars.push_var(param.ident.value.as_str(), param.ty.clone());
+            });
+            if let Some(declared) = declared.as_ref() {
+                scope.infer_params(declared);
+            }
+            scope.type_check(vars).map_err(|err| err.in_source(file))?;
+            vars.pop();
+            let returned = vars.pop_fn_body(outer);
 
-        vars.push();
-        params.iter().for_each(|param| {
-            vars.push_var(param.ident.value.as_str(), param.ty);
-        });
-        scope.type_check(vars)?;
-        vars.pop();
+            let ty = match declared.clone() {
+                Some(expect) => {
+                    scope
+                        .infer_lit(&expect)
+                        .map_err(|err| err.in_source(file))?;
+                    let got = scope.type_of();
+                    if expect != got && got != Type::Never {
+                        return Err(
+                            Error::new_type_mismatch(declared_span, &expect, &got).in_source(file)
+                        );
+                    }
+                    expect
+                }
+                None => unify_return(name_span.clone(), scope.type_of(), returned)
+                    .map_err(|err| err.in_source(file))?,
+            };
 
-        let ty = scope.type_of();
+            // without a declared type, recursive calls saw an unresolved one,
+            // the body is checked again with the type the other paths decided,
+            // like the instances it called, they may have used the unresolved type
+            let recursive = vars.unresolved_calls.remove(&mangled);
+            match ty {
+                Type::Unresolved if recursive => {
+                    return Err(Error::new_untyped_return(name_span, &name).in_source(file));
+                }
+                ty if recursive && recursive_ty == Type::Unresolved => {
+                    recursive_ty = ty;
+                    vars.forget_instances(&instances);
+                }
+                ty => break (scope, ty),
+            }
+        };
 
         let span = Span::new("", 0, 0).unwrap();
         Ok(Self {
@@ -128,9 +241,11 @@ impl<'i> Function<'i> {
                 params,
                 fn_ty: FnTy {
                     span: span.clone(),
-                    ty,
+                    ty: ty.clone(),
                 },
                 scope,
+                self_ty,
+                file,
             }),
             type_checking: false,
             span,
@@ -144,17 +259,21 @@ impl<'i> Function<'i> {
         span: Span<'i>,
     ) -> Result<Self> {
         let mut scope = Scope::global(statements, span.clone());
+        let outer = vars.push_fn_body(None);
         scope.type_check(vars)?;
-        let ty = scope.type_of();
+        let returned = vars.pop_fn_body(outer);
+        let ty = unify_return(span.clone(), scope.type_of(), returned)?;
         Ok(Self {
             internal: Box::new(FunctionInternal {
                 name: Ident::new("__global"),
                 params: vec![],
                 fn_ty: FnTy {
                     span: Span::new("__global", 0, 5).unwrap(),
-                    ty,
+                    ty: ty.clone(),
                 },
                 scope,
+                self_ty: None,
+                file: 0,
             }),
             type_checking: false,
             span,
@@ -163,31 +282,68 @@ impl<'i> Function<'i> {
     }
 }
 
+/// the return type of a function without a declared one,
+/// the value of its body has to match the values of its `return`s
+pub(super) fn unify_return(span: Span, body: Type, returned: Option<Type>) -> Result<Type> {
+    match (body, returned) {
+        (body, None) => Ok(body),
+        (Type::Never, Some(returned)) => Ok(returned),
+        (body, Some(returned)) if body == returned || returned == Type::Never => Ok(body),
+        (body, Some(returned)) => Err(Error::new_type_mismatch(span, &returned, &body)),
+    }
+}
+
 impl<'i> TypeOf<'i> for Function<'i> {
     fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        let file = self.internal.file;
+        self.type_check_body(vars)
+            .map_err(|err| err.in_source(file))
+    }
+
+    fn type_of(&self) -> Type {
+        self.ty.clone()
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        Some(self.type_of())
+    }
+}
+
+impl<'i> Function<'i> {
+    fn type_check_body(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        for param in self.internal.params.iter() {
+            vars.check_ty(param.span.clone(), &param.ty)?;
+            param.check_passable()?;
+        }
+        vars.check_ty(self.internal.fn_ty.span.clone(), &self.ty)?;
+
+        let outer = vars.push_fn_body(Some(self.ty.clone()));
+        vars.enter_file(self.internal.file);
+        if let Some(self_ty) = self.internal.self_ty.as_ref() {
+            vars.bind_generics(HashMap::from([("Self".into(), self_ty.clone())]));
+        }
         vars.push();
         self.internal.params.iter().for_each(|param| {
-            vars.push_var(param.ident.value.as_str(), param.ty);
+            vars.push_var(param.ident.value.as_str(), param.ty.clone());
         });
+        self.internal.scope.infer_params(&self.ty);
         self.internal.scope.type_check(vars)?;
         vars.pop();
+        vars.pop_fn_body(outer);
 
-        let expect = self.ty;
+        let expect = &self.ty;
+        self.internal.scope.infer_lit(expect)?;
         let got = self.internal.scope.type_of();
-        if expect != got {
-            Err(Error::new_type_mismatch(self.span.clone(), &expect, &got))
+        if *expect != got && got != Type::Never {
+            Err(Error::new_type_mismatch(
+                self.internal.fn_ty.span.clone(),
+                expect,
+                &got,
+            ))
         } else {
             Ok(())
         }
     }
-
-    fn type_of(&self) -> Type {
-        self.ty
-    }
-
-    fn type_of_impl(&self) -> Option<Type> {
-        Some(self.type_of())
-    }
 }
 
 impl<'i> Display for Function<'i> {
@@ -1,4 +1,4 @@
-use super::{Ast, Ident, Result, Rule, Scope, Type};
+use super::{match_rule, Ast, Ident, Result, Rule, Scope, Type};
 use pest::{iterators::Pair, Span};
 use std::fmt::Display;
 
@@ -7,9 +7,15 @@ use std::fmt::Display;
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionGenInternal<'i> {
     pub name: Ident<'i>,
+    pub generics: Vec<Generic<'i>>,
     pub params: Vec<ParamGen<'i>>,
     pub fn_ty: FnTyGen<'i>,
     pub scope: Scope<'i>,
+    /// the type of `self` and `Self` in methods
+    pub self_ty: Option<Type>,
+    /// the index of the file the function is declared in,
+    /// its body refers to items by their names in that file
+    pub file: usize,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -32,13 +38,24 @@ impl<'i> Ast<'i> for FunctionGen<'i> {
 
         let internal = Box::new(FunctionGenInternal {
             name: Ast::parse(tokens.next().unwrap())?,
-            params: if let Some(Rule::param) = tokens.peek().map(|token| token.as_rule()) {
-                Ast::parse(tokens.next().unwrap())?
-            } else {
-                vec![]
+            generics: {
+                let mut generics = vec![];
+                while let Some(Rule::generic) = tokens.peek().map(|token| token.as_rule()) {
+                    generics.push(Ast::parse(tokens.next().unwrap())?);
+                }
+                generics
+            },
+            params: {
+                let mut params = vec![];
+                while let Some(Rule::param) = tokens.peek().map(|token| token.as_rule()) {
+                    params.push(Ast::parse(tokens.next().unwrap())?);
+                }
+                params
             },
             fn_ty: Ast::parse(tokens.next().unwrap())?,
             scope: Ast::parse(tokens.next().unwrap())?,
+            self_ty: None,
+            file: 0,
         });
 
         Ok(Self { internal, span })
@@ -87,7 +104,7 @@ impl<'i> Ast<'i> for FunctionGen<'i> {
     }
 
     fn type_of_impl(&self) -> Option<Type> {
-        self.ty
+        self.ty.clone()
     }
 } */
 
@@ -98,6 +115,22 @@ impl<'i> Display for FunctionGen<'i> {
 }
 
 impl<'i> FunctionGen<'i> {
+    pub fn new(internal: FunctionGenInternal<'i>, span: Span<'i>) -> Self {
+        Self {
+            internal: Box::new(internal),
+            span,
+        }
+    }
+
+    /// names of the type parameters declared in `<..>`
+    pub fn generic_names(&self) -> Vec<&str> {
+        self.internal
+            .generics
+            .iter()
+            .map(|generic| generic.ident.value.as_str())
+            .collect()
+    }
+
     /* pub fn global(statements: Vec<Statement<'i>>, span: Span<'i>) -> Self {
         Self {
             internal: Box::new(FunctionInternal {
@@ -142,34 +175,64 @@ pub struct ParamGen<'i> {
     pub ty: Type,
 }
 
-impl<'i> Ast<'i> for Vec<ParamGen<'i>> {
+impl<'i> Ast<'i> for ParamGen<'i> {
     fn span(&self) -> Span<'i> {
-        Span::new("unreachable", 0, 11).unwrap()
+        self.span.clone()
     }
 
     fn parse(token: Pair<'i, Rule>) -> Result<Self> {
-        // let span = token.as_span();
-        // match_rule(&span, token.as_rule(), Rule::params)?;
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::param)?;
         let mut tokens = token.into_inner();
 
-        let mut params = vec![];
-        while tokens.peek().is_some() {
-            let ident = Ident::parse(tokens.next().unwrap())?;
-            if let Some(Rule::ty) = tokens.peek().map(|token| token.as_rule()) {
-                let ty = Type::parse(tokens.next().unwrap())?;
-                let span = ty.span();
-                params.push(ParamGen { ident, span, ty })
-            } else {
-                let span = ident.span();
-                params.push(ParamGen {
-                    ident,
-                    span,
-                    ty: Type::Unresolved,
-                })
-            }
-        }
+        let ident = Ident::parse(tokens.next().unwrap())?;
+        let ty = match tokens.next() {
+            Some(token) => Type::parse(token)?,
+            None => Type::Unresolved,
+        };
+
+        Ok(ParamGen { ident, span, ty })
+    }
+}
+
+//
+
+/// a type parameter `T: Num + Ord`, the bounds are checked for every instance
+#[derive(Debug, Clone, PartialEq)]
+pub struct Generic<'i> {
+    pub ident: Ident<'i>,
+    pub bounds: Vec<Ident<'i>>,
+    pub span: Span<'i>,
+}
+
+impl<'i> Ast<'i> for Generic<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::generic)?;
+        let mut tokens = token.into_inner();
 
-        Ok(params)
+        let ident = Ident::parse(tokens.next().unwrap())?;
+        let bounds = tokens.map(Ident::parse).collect::<Result<_>>()?;
+
+        Ok(Generic {
+            ident,
+            bounds,
+            span,
+        })
+    }
+}
+
+impl<'i> Display for Generic<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.ident)?;
+        for (i, bound) in self.bounds.iter().enumerate() {
+            write!(f, "{}{bound}", if i == 0 { ": " } else { " + " })?;
+        }
+        Ok(())
     }
 }
 
@@ -181,6 +244,17 @@ pub struct FnTyGen<'i> {
     pub(super) ty: Type,
 }
 
+impl<'i> FnTyGen<'i> {
+    /// the return type, `None` if it is left to the function body
+    pub fn declared(&self) -> Option<&Type> {
+        if self.span.as_str().is_empty() || self.ty == Type::Unresolved {
+            None
+        } else {
+            Some(&self.ty)
+        }
+    }
+}
+
 impl<'i> Ast<'i> for FnTyGen<'i> {
     fn span(&self) -> Span<'i> {
         self.span.clone()
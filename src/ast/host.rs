@@ -0,0 +1,25 @@
+use super::{FnSig, Type};
+use std::collections::HashMap;
+
+//
+
+/// what the host declares for scripts before they are parsed
+///
+/// host functions can be called like script functions,
+/// but they can't be used as function values
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Host {
+    pub fns: HashMap<String, FnSig>,
+    /// the fields of every struct the host passes to or from scripts,
+    /// scripts have to declare them the same way
+    pub structs: HashMap<String, Vec<(String, Type)>>,
+    /// values scripts read like variables declared around every function
+    pub globals: HashMap<String, GlobalSig>,
+}
+
+/// the type of a host global and if scripts can assign to it
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlobalSig {
+    pub ty: Type,
+    pub mutable: bool,
+}
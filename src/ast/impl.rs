@@ -0,0 +1,188 @@
+use super::{
+    check_self_param, match_rule, Ast, Error, FunctionGen, FunctionGenInternal, Ident, Result,
+    Rule, Type, TypeOf, VisibleVars,
+};
+use pest::{iterators::Pair, Span};
+use std::{collections::HashMap, fmt::Display};
+
+//
+
+/// `impl Type { .. }` or `impl Trait for Type { .. }`
+///
+/// the methods become functions called `Type::method`,
+/// with `self` as their first parameter
+#[derive(Debug, Clone, PartialEq)]
+pub struct Impl<'i> {
+    pub trait_name: Option<Ident<'i>>,
+    pub ty: Type,
+    pub functions: Vec<FunctionGen<'i>>,
+    /// the index of the file the impl block is declared in, see [`super::FunctionGenInternal::file`]
+    pub file: usize,
+
+    span: Span<'i>,
+}
+
+//
+
+impl<'i> Impl<'i> {
+    /// the name of the function behind the method `name` of values of type `ty`
+    pub fn method_name(ty: &Type, name: &str) -> String {
+        format!("{ty}::{name}")
+    }
+
+    /// checks the methods against their declarations in the trait and
+    /// adds the ones the impl leaves out to their default bodies
+    fn check_trait(&mut self, vars: &VisibleVars<'i>, trait_name: &Ident<'i>) -> Result<()> {
+        let t = vars.get_trait(trait_name.span(), &trait_name.value)?;
+
+        for f in self.functions.iter() {
+            let name = &f.internal.name;
+            let method = t.method(&name.value).ok_or_else(|| {
+                Error::new_not_trait_member(name.span(), &name.value, &t.name.value)
+            })?;
+
+            // both signatures with `Self` replaced
+            let bindings = HashMap::from([("Self".into(), self.ty.clone())]);
+            let params = &f.internal.params;
+            if method.params.len() != params.len() {
+                return Err(Error::new_argc_mismatch(
+                    name.span(),
+                    method.params.len(),
+                    params.len(),
+                ));
+            }
+            for (expect, param) in method.params.iter().zip(params).skip(1) {
+                let expect = expect.ty.resolve_generics(&bindings);
+                let got = param.ty.resolve_generics(&bindings);
+                if expect != got {
+                    return Err(Error::new_type_mismatch(param.span.clone(), &expect, &got));
+                }
+            }
+            let expect = method.fn_ty.ty.resolve_generics(&bindings);
+            let got = f.internal.fn_ty.ty.resolve_generics(&bindings);
+            if expect != got {
+                return Err(Error::new_type_mismatch(
+                    f.internal.fn_ty.span(),
+                    &expect,
+                    &got,
+                ));
+            }
+        }
+
+        let mut defaults = vec![];
+        for method in t.methods.iter() {
+            let name = method.name.value.as_str();
+            if self.functions.iter().any(|f| f.internal.name.value == name) {
+                continue;
+            }
+            let scope = method.default.clone().ok_or_else(|| {
+                Error::new_missing_method(self.span(), name, &t.name.value, &self.ty)
+            })?;
+            defaults.push(FunctionGen::new(
+                FunctionGenInternal {
+                    name: method.name.clone(),
+                    generics: vec![],
+                    params: method.params.clone(),
+                    fn_ty: method.fn_ty.clone(),
+                    scope,
+                    self_ty: None,
+                    file: t.file,
+                },
+                method.span(),
+            ));
+        }
+        self.functions.extend(defaults);
+
+        Ok(())
+    }
+}
+
+impl<'i> Ast<'i> for Impl<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::impl_block)?;
+        let mut tokens = token.into_inner();
+
+        let trait_name = match tokens.peek().map(|token| token.as_rule()) {
+            Some(Rule::impl_trait) => {
+                let token = tokens.next().unwrap();
+                Some(Ident::parse(token.into_inner().next().unwrap())?)
+            }
+            _ => None,
+        };
+        let ty = Type::parse(tokens.next().unwrap())?;
+        let functions = tokens.map(FunctionGen::parse).collect::<Result<_>>()?;
+
+        Ok(Self {
+            trait_name,
+            ty,
+            functions,
+            file: 0,
+            span,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for Impl<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        vars.check_ty(self.span(), &self.ty)?;
+
+        for (i, f) in self.functions.iter().enumerate() {
+            let name = &f.internal.name;
+            if self.functions[..i]
+                .iter()
+                .any(|other| other.internal.name.value == name.value)
+            {
+                return Err(Error::new_method_redefined(
+                    name.span(),
+                    &name.value,
+                    &self.ty.to_string(),
+                ));
+            }
+            check_self_param(name, &f.internal.params)?;
+        }
+
+        if let Some(trait_name) = self.trait_name.clone() {
+            self.check_trait(vars, &trait_name)?;
+            vars.push_impl(self.span(), &trait_name.value, &self.ty)?;
+        }
+
+        // `self` is the receiver and `Self` its type
+        let bindings = HashMap::from([("Self".into(), self.ty.clone())]);
+        for f in self.functions.iter_mut() {
+            let f = f.internal.as_mut();
+            if f.params[0].ty == Type::Unresolved {
+                f.params[0].ty = self.ty.clone();
+            }
+            for param in f.params.iter_mut() {
+                param.ty = param.ty.resolve_generics(&bindings);
+            }
+            f.fn_ty.ty = f.fn_ty.ty.resolve_generics(&bindings);
+            f.name.value = Self::method_name(&self.ty, &f.name.value);
+            f.self_ty = Some(self.ty.clone());
+        }
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        Some(self.ty.clone())
+    }
+}
+
+impl<'i> Display for Impl<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.trait_name.as_ref() {
+            Some(trait_name) => write!(f, "impl {trait_name} for {} {{ ", self.ty)?,
+            None => write!(f, "impl {} {{ ", self.ty)?,
+        }
+        for function in self.functions.iter() {
+            write!(f, "{function} ")?;
+        }
+        write!(f, "}}")
+    }
+}
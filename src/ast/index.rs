@@ -0,0 +1,67 @@
+use super::{Ast, Error, Expr, Result, Term, Type, TypeOf, VisibleVars};
+use pest::Span;
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Index<'i> {
+    pub term: Term<'i>,
+    pub index: Expr<'i>,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> Index<'i> {
+    pub fn new(span: Span<'i>, term: Term<'i>, index: Expr<'i>) -> Self {
+        Self {
+            term,
+            index,
+
+            span,
+            ty: None,
+        }
+    }
+
+    pub fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+}
+
+impl<'i> TypeOf<'i> for Index<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        self.term.type_check(vars)?;
+        self.index.type_check(vars)?;
+
+        let ty = match self.term.type_of() {
+            Type::Array(ty, _) => *ty,
+            ty => return Err(Error::new_not_indexable(self.term.span(), &ty)),
+        };
+
+        let ty_index = self.index.type_of();
+        if !(ty_index.is_int() || ty_index == Type::Never) {
+            return Err(Error::new_type_mismatch(
+                self.index.span(),
+                &Type::I64,
+                &ty_index,
+            ));
+        }
+
+        self.ty = Some(ty);
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Display for Index<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}[{}]", self.term, self.index)
+    }
+}
@@ -0,0 +1,151 @@
+use super::{
+    match_rule, unify_return, Ast, Error, Expr, Param, ParamGen, Result, Rule, Scope, Type, TypeOf,
+    VisibleVars,
+};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lambda<'i> {
+    pub params: Vec<Param<'i>>,
+    pub ret: Option<Type>,
+    pub scope: Scope<'i>,
+    /// variables from the scopes around the lambda, copied into it when it is created
+    pub captures: Vec<(String, Type)>,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> Lambda<'i> {
+    /// gives params without a type annotation the param types of the fn type `ty`,
+    /// like the `x` in `let f: fn(i64) -> i64 = |x| x + 1`
+    ///
+    /// does nothing for other types, the following type checks report mismatches
+    pub fn infer_params(&mut self, ty: &Type) {
+        let params = match ty {
+            Type::Fn(params, _) if params.len() == self.params.len() => params,
+            _ => return,
+        };
+        for (param, ty) in self.params.iter_mut().zip(params) {
+            if param.ty == Type::Unresolved {
+                param.ty = ty.clone();
+            }
+        }
+    }
+}
+
+impl<'i> Ast<'i> for Lambda<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::lambda)?;
+        let mut tokens = token.into_inner();
+
+        let mut params = vec![];
+        while let Some(Rule::param) = tokens.peek().map(|token| token.as_rule()) {
+            let ParamGen { ident, span, ty } = Ast::parse(tokens.next().unwrap())?;
+            params.push(Param { ident, span, ty });
+        }
+
+        let ret = match tokens.peek().map(|token| token.as_rule()) {
+            Some(Rule::ret_ty) => {
+                let token = tokens.next().unwrap();
+                Some(Type::parse(token.into_inner().next().unwrap())?)
+            }
+            _ => None,
+        };
+
+        // `|x| x + 1` is short for `|x| { x + 1 }`
+        let token = tokens.next().unwrap();
+        let scope = match token.as_rule() {
+            Rule::scope => Scope::parse(token)?,
+            _ => {
+                let expr = Expr::parse(token)?;
+                let span = expr.span();
+                Scope::global(vec![expr.into()], span)
+            }
+        };
+
+        Ok(Self {
+            params,
+            ret,
+            scope,
+            captures: vec![],
+
+            span,
+            ty: None,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for Lambda<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        for param in self.params.iter_mut() {
+            param.ty = vars.resolve_ty(&param.ty);
+        }
+        self.ret = self.ret.as_ref().map(|ty| vars.resolve_ty(ty));
+        for param in self.params.iter() {
+            if param.ty == Type::Unresolved {
+                return Err(Error::new_untyped_param(
+                    param.span.clone(),
+                    param.ident.value.as_str(),
+                ));
+            }
+            vars.check_ty(param.span.clone(), &param.ty)?;
+            param.check_passable()?;
+        }
+        if let Some(ret) = self.ret.as_ref() {
+            vars.check_ty(self.span(), ret)?;
+        }
+
+        let outer = vars.push_closure(self.ret.clone());
+        self.params.iter().for_each(|param| {
+            vars.push_var(param.ident.value.as_str(), param.ty.clone());
+        });
+        self.scope.type_check(vars)?;
+        let (returned, captures) = vars.pop_closure(outer);
+
+        if let Some(ret) = self.ret.as_ref() {
+            self.scope.infer_lit(ret)?;
+        }
+        let body = self.scope.type_of();
+        let ret = match self.ret.as_ref() {
+            Some(ret) if *ret != body && body != Type::Never => {
+                return Err(Error::new_type_mismatch(self.scope.span(), ret, &body));
+            }
+            Some(ret) => ret.clone(),
+            None => unify_return(self.span(), body, returned)?,
+        };
+
+        let params = self.params.iter().map(|param| param.ty.clone()).collect();
+        self.captures = captures;
+        self.ty = Some(Type::Fn(params, Box::new(ret)));
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Display for Lambda<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "|")?;
+        for (i, param) in self.params.iter().enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", param.ident)?;
+        }
+        write!(f, "| {}", self.scope)
+    }
+}
@@ -0,0 +1,56 @@
+use super::{match_rule, Ast, Result, Rule, Scope, Type, TypeOf, VisibleVars};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Loop<'i> {
+    pub scope: Scope<'i>,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> Ast<'i> for Loop<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::inf_loop)?;
+        let scope = Scope::parse_single(token.into_inner())?;
+
+        Ok(Self {
+            scope,
+
+            span,
+            ty: None,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for Loop<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        vars.push_loop(None);
+        self.scope.type_check(vars)?;
+
+        // a loop without any breaks never finishes
+        self.ty = Some(vars.pop_loop().unwrap_or(Type::Never));
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Display for Loop<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "loop {}", self.scope)
+    }
+}
@@ -0,0 +1,154 @@
+use super::{Ast, Expr, Pattern, Result, Rule, Scope, Statement, TypeOf, VisibleVars};
+use crate::ast::{match_rule, Error, Type};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm<'i> {
+    pub pattern: Pattern<'i>,
+    pub scope: Scope<'i>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchInternal<'i> {
+    pub expr: Expr<'i>,
+    pub arms: Vec<MatchArm<'i>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match<'i> {
+    pub internal: Box<MatchInternal<'i>>,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> Ast<'i> for Match<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::match_expr)?;
+        let mut tokens = token.into_inner();
+
+        let expr = Expr::parse(tokens.next().unwrap())?;
+        let arms = tokens
+            .map(|token| {
+                let mut tokens = token.into_inner();
+                let pattern = Pattern::parse(tokens.next().unwrap())?;
+                let token = tokens.next().unwrap();
+                let scope = match token.as_rule() {
+                    // `pattern => expr` is lowered into `pattern => { expr }`
+                    Rule::expr => {
+                        let expr = Expr::parse(token)?;
+                        let span = expr.span();
+                        Scope::global(vec![Statement::from(expr)], span)
+                    }
+                    _ => Scope::parse(token)?,
+                };
+                Ok(MatchArm { pattern, scope })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            internal: Box::new(MatchInternal { expr, arms }),
+
+            span,
+            ty: None,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for Match<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        self.internal.expr.type_check(vars)?;
+        let ty_expr = self.internal.expr.type_of();
+
+        for arm in self.internal.arms.iter_mut() {
+            // bindings are only visible in the arm
+            vars.push();
+            arm.pattern.check(vars, &ty_expr)?;
+            arm.scope.type_check(vars)?;
+            vars.pop();
+        }
+
+        // `match c { 0 => x, _ => 1 }` with `x: u8` is `match c { 0 => x, _ => 1u8 }`
+        let hint = self
+            .internal
+            .arms
+            .iter()
+            .map(|arm| arm.scope.type_of())
+            .find(|ty| ty.is_number() && !ty.is_lit_default());
+        if let Some(hint) = hint {
+            for arm in self.internal.arms.iter_mut() {
+                arm.scope.infer_lit(&hint)?;
+            }
+        }
+        let ty = self.unify()?;
+
+        let patterns: Vec<&Pattern> = self.internal.arms.iter().map(|arm| &arm.pattern).collect();
+        if let Some(witness) = Pattern::missing(vars, &patterns, &ty_expr) {
+            return Err(Error::new_non_exhaustive(
+                self.internal.expr.span(),
+                &witness,
+            ));
+        }
+
+        self.ty = Some(ty);
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Match<'i> {
+    /// gives the number literals every arm evaluates to the number type `ty`,
+    /// see [`Expr::infer_lit`]
+    pub fn infer_lit(&mut self, ty: &Type) -> Result<()> {
+        if !self.ty.as_ref().is_some_and(Type::is_lit_default) {
+            return Ok(());
+        }
+        for arm in self.internal.arms.iter_mut() {
+            arm.scope.infer_lit(ty)?;
+        }
+        self.ty = Some(self.unify()?);
+        Ok(())
+    }
+
+    /// the type of the value of the match, every arm needs to produce the same one
+    fn unify(&self) -> Result<Type> {
+        // a match without arms never produces a value
+        let mut ty = Type::Never;
+        for arm in self.internal.arms.iter() {
+            let ty_arm = arm.scope.type_of();
+            match (&ty, ty_arm) {
+                (ty, ty_arm) if *ty == ty_arm => {}
+                (Type::Unresolved | Type::Never, ty_arm) => ty = ty_arm,
+                (_, Type::Unresolved | Type::Never) => {}
+                (ty, ty_arm) => {
+                    return Err(Error::new_type_mismatch(arm.scope.span(), ty, &ty_arm))
+                }
+            }
+        }
+        Ok(ty)
+    }
+}
+
+impl<'i> Display for Match<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "match {} {{ ", self.internal.expr)?;
+        for arm in self.internal.arms.iter() {
+            write!(f, "{} => {}, ", arm.pattern, arm.scope)?;
+        }
+        write!(f, "}}")
+    }
+}
@@ -9,41 +9,91 @@ use std::{
     hash::{Hash, Hasher},
 };
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fmt::{Debug, Display},
+    io,
+    path::{Path, PathBuf},
 };
 
 pub use self::access::*;
+pub use self::array_lit::*;
 pub use self::assign::*;
 pub use self::binary::*;
 pub use self::branch::*;
 pub use self::call::*;
+pub use self::cast::*;
+pub use self::enum_lit::*;
 pub use self::expr::*;
+pub use self::field_access::*;
+pub use self::files::*;
 pub use self::function::*;
 pub use self::function_gen::*;
+pub use self::host::*;
 pub use self::ident::*;
+pub use self::index::*;
+pub use self::lambda::*;
 pub use self::module::*;
+pub use self::pattern::*;
+pub use self::r#break::*;
+pub use self::r#continue::*;
+pub use self::r#enum::*;
+pub use self::r#for::*;
+pub use self::r#impl::*;
+pub use self::r#loop::*;
+pub use self::r#match::*;
+pub use self::r#return::*;
+pub use self::r#static::*;
+pub use self::r#struct::*;
+pub use self::r#trait::*;
 pub use self::r#type::*;
+pub use self::r#while::*;
+pub use self::reassign::*;
 pub use self::scope::*;
 pub use self::statement::*;
+pub use self::struct_lit::*;
 pub use self::term::*;
+pub use self::tuple_lit::*;
 pub use self::unary::*;
 
 pub mod access;
+pub mod array_lit;
 pub mod assign;
 pub mod binary;
 pub mod branch;
+pub mod r#break;
 pub mod call;
+pub mod cast;
+pub mod r#continue;
+pub mod r#enum;
+pub mod enum_lit;
 pub mod expr;
+pub mod field_access;
+pub mod files;
+pub mod r#for;
 pub mod function;
 pub mod function_gen;
+pub mod host;
 pub mod ident;
+pub mod r#impl;
+pub mod index;
+pub mod lambda;
+pub mod r#loop;
+pub mod r#match;
 pub mod module;
+pub mod pattern;
+pub mod reassign;
+pub mod r#return;
 pub mod scope;
 pub mod statement;
+pub mod r#static;
+pub mod r#struct;
+pub mod struct_lit;
 pub mod term;
+pub mod r#trait;
+pub mod tuple_lit;
 pub mod r#type;
 pub mod unary;
+pub mod r#while;
 
 // ------
 // Parser
@@ -54,12 +104,113 @@ pub mod unary;
 struct ToyLangParser;
 
 pub fn parse<'i>(input: &'i str) -> Result<Module<'i>> {
-    let mut tokens = ToyLangParser::parse(Rule::input, input).map_err(Error::new_pest)?;
-    let mut module = Module::<'i>::parse(tokens.next().unwrap())?;
+    parse_with_host(input, &Host::default())
+}
+
+/// parses a script that can use what the host declares
+pub fn parse_with_host<'i>(input: &'i str, host: &Host) -> Result<Module<'i>> {
+    let source = Source {
+        path: None,
+        module: "",
+        source: input,
+    };
+    parse_sources(&[source], host)
+}
+
+/// parses the root file and the files it loaded with [`Files::load_modules`]
+pub fn parse_files<'i>(files: &'i Files, host: &Host) -> Result<Module<'i>> {
+    parse_sources(&files.sources(), host)
+}
+
+/// parses a script made of multiple sources, the first one is the root file
+///
+/// the items of every other source are called `module::item`,
+/// errors name the file they are in
+pub fn parse_sources<'i>(sources: &[Source<'i>], host: &Host) -> Result<Module<'i>> {
+    parse_modules(sources, host, None).map_err(|err| err.with_source_file(sources))
+}
+
+/// parses a script like [`parse_sources`] and type checks the instance
+/// of the generic function `name` for params `sig`, see [`Module::instantiate`]
+pub fn parse_instance<'i>(
+    sources: &[Source<'i>],
+    host: &Host,
+    name: &str,
+    sig: &[Type],
+) -> Result<Module<'i>> {
+    parse_modules(sources, host, Some((name, sig))).map_err(|err| err.with_source_file(sources))
+}
+
+fn parse_modules<'i>(
+    sources: &[Source<'i>],
+    host: &Host,
+    instance: Option<(&str, &[Type])>,
+) -> Result<Module<'i>> {
+    let modules = sources
+        .iter()
+        .enumerate()
+        .map(|(i, source)| {
+            ToyLangParser::parse(Rule::input, source.source)
+                .map_err(Error::new_pest)
+                .and_then(|mut tokens| Module::<'i>::parse(tokens.next().unwrap()))
+                .map_err(|err| err.in_source(i))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    // every file sees its own items and the ones it imports with `use`
+    let locals: Vec<_> = modules
+        .iter()
+        .zip(sources)
+        .map(|(module, source)| module.local_names(source.module))
+        .collect();
+    let mut files = locals.clone();
+    let find_module = |module: &Ident| {
+        sources
+            .iter()
+            .position(|source| source.module == module.value)
+    };
+    for (i, (module, names)) in modules.iter().zip(files.iter_mut()).enumerate() {
+        // only sources loaded from files have their modules loaded
+        if let Some(m) = module.mods.iter().find(|m| find_module(m).is_none()) {
+            return Err(Error::new_module_not_found(m.span(), &m.value).in_source(i));
+        }
+        for u in module.uses.iter() {
+            let module = find_module(&u.module).ok_or_else(|| {
+                Error::new_module_not_declared(u.module.span(), &u.module.value).in_source(i)
+            })?;
+            let name = locals[module].get(&u.item.value).ok_or_else(|| {
+                Error::new_item_not_found(u.item.span(), &u.item.value, &u.module.value)
+                    .in_source(i)
+            })?;
+            if names.insert(u.alias.value.clone(), name.clone()).is_some() {
+                return Err(Error::new_name_redefined(u.alias.span(), &u.alias.value).in_source(i));
+            }
+        }
+    }
+
+    let mut modules = modules.into_iter().enumerate().map(|(i, mut module)| {
+        module.namespace(i, &files[i]);
+        module
+    });
+    let mut root = modules.next().unwrap();
+    for (module, (i, source)) in modules.zip(sources.iter().enumerate().skip(1)) {
+        if let Some(statement) = module.statements().first() {
+            return Err(
+                Error::new_statement_in_module(statement.span(), source.module).in_source(i),
+            );
+        }
+        root.merge(module);
+    }
+
     let mut vars = VisibleVars::new();
-    module.type_check(&mut vars)?;
+    vars.set_files(files);
+    vars.set_host(host.clone());
+    root.type_check(&mut vars)?;
+    if let Some((name, sig)) = instance {
+        root.instantiate(&mut vars, name, sig)?;
+    }
 
-    Ok(module)
+    Ok(root)
 }
 
 pub fn generic_mangle(sig: &[Type], name: &str) -> String {
@@ -74,27 +225,84 @@ pub fn generic_mangle(sig: &[Type], name: &str) -> String {
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct FnSig {
-    arg_ty: Box<[Type]>,
-    out_ty: Type,
+    pub arg_ty: Box<[Type]>,
+    pub out_ty: Type,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct VisibleVar {
+    ty: Type,
+    mutable: bool,
+}
+
+/// variables a closure uses from the scopes around it
+#[derive(Debug, Clone, PartialEq)]
+struct Captures {
+    /// variables in `vars[..depth]` are declared outside of the closure
+    depth: usize,
+    vars: Vec<(String, Type)>,
+}
+
+/// the state of the code around a function body, see [`VisibleVars::push_fn_body`]
+#[derive(Debug)]
+pub struct OuterFn {
+    vars: Vec<HashMap<String, VisibleVar>>,
+    closures: Vec<Captures>,
+    loops: Vec<Option<Type>>,
+    return_ty: Option<Type>,
+    generics: HashMap<String, Type>,
+    file: usize,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct VisibleVars<'i> {
-    vars: Vec<HashMap<String, Type>>,
+    vars: Vec<HashMap<String, VisibleVar>>,
+    closures: Vec<Captures>,
+    /// the types the type parameters of the current function instance stand for
+    generics: HashMap<String, Type>,
     function_gens: HashMap<String, FunctionGen<'i>>,
     functions: HashMap<String, Function<'i>>,
+    structs: HashMap<String, Struct<'i>>,
+    enums: HashMap<String, Enum<'i>>,
+    traits: HashMap<String, Trait<'i>>,
+    /// `(trait, type)` for every `impl Trait for Type`
+    impls: HashSet<(String, Type)>,
+    /// the names items have in each file and the names they have in the script
+    files: Vec<HashMap<String, String>>,
+    /// the file the current function is declared in
+    file: usize,
+    host: Host,
+    /// statics by their names in the script, every function sees them
+    statics: HashMap<String, VisibleVar>,
+    loops: Vec<Option<Type>>,
+    return_ty: Option<Type>,
 
     fn_ty_cache: HashMap<String, Type>,
+    /// instances a recursive call saw with an unresolved type, see [`Function::new`]
+    unresolved_calls: HashSet<String>,
 }
 
 impl<'i> Default for VisibleVars<'i> {
     fn default() -> Self {
         Self {
             vars: vec![Default::default()],
+            closures: Default::default(),
+            generics: Default::default(),
             function_gens: Default::default(),
             functions: Default::default(),
+            structs: Default::default(),
+            enums: Default::default(),
+            traits: Default::default(),
+            impls: Default::default(),
+            files: Default::default(),
+            file: Default::default(),
+            host: Default::default(),
+            statics: Default::default(),
+            loops: Default::default(),
+            return_ty: Default::default(),
 
             fn_ty_cache: Default::default(),
+            unresolved_calls: Default::default(),
         }
     }
 }
@@ -110,6 +318,7 @@ impl<'i> VisibleVars<'i> {
 
     pub fn push_fn(&mut self, name: &str, sig: &[Type], f: Function<'i>) {
         let mangled = generic_mangle(sig, name);
+        self.fn_ty_cache.remove(&mangled);
         self.functions.insert(mangled, f);
     }
 
@@ -138,12 +347,311 @@ impl<'i> VisibleVars<'i> {
         }
     }
 
+    /// the instances type checked so far
+    pub fn instance_names(&self) -> HashSet<String> {
+        self.functions.keys().cloned().collect()
+    }
+
+    /// forgets the instances not in `keep`, the next calls type check them again
+    pub fn forget_instances(&mut self, keep: &HashSet<String>) {
+        self.functions.retain(|name, _| keep.contains(name));
+    }
+
+    /// parameter types as they are declared, `Type::Unresolved` for generic ones
+    pub fn get_fn_params(&self, name: &str) -> Option<Vec<Type>> {
+        if let Some(f) = self.function_gens.get(name) {
+            let generics = f.generic_names();
+            let params = f.internal.params.iter().map(|p| match &p.ty {
+                ty if ty.is_generic(&generics) => Type::Unresolved,
+                ty => ty.clone(),
+            });
+            return Some(params.collect());
+        }
+        // non generic functions have exactly one instance
+        let f = self
+            .functions
+            .values()
+            .find(|f| f.internal.name.value == name)?;
+        Some(f.internal.params.iter().map(|p| p.ty.clone()).collect())
+    }
+
+    /// instances that are still being type checked count,
+    /// so recursive calls don't instantiate them again
+    pub fn has_fn(&self, name: &str, sig: &[Type]) -> bool {
+        let mangled = generic_mangle(sig, name);
+        self.functions.contains_key(&mangled) || self.fn_ty_cache.contains_key(&mangled)
+    }
+
     pub fn get_fn_ty(&mut self, call_site: Span, name: &str, sig: &[Type]) -> Result<Type> {
-        if let Some(&ty) = self.fn_ty_cache.get(name) {
+        let mangled = generic_mangle(sig, name);
+        if let Some(ty) = self.fn_ty_cache.get(&mangled) {
+            if *ty == Type::Unresolved {
+                self.unresolved_calls.insert(mangled);
+            }
+            Ok(ty.clone())
+        } else {
+            // recursive calls see the type as unresolved until the first call is resolved
+            self.fn_ty_cache.insert(mangled.clone(), Type::Unresolved);
+            let ty = self.get_fn(call_site, name, sig)?.type_of();
+            self.fn_ty_cache.insert(mangled, ty.clone());
             Ok(ty)
+        }
+    }
+
+    pub fn set_host(&mut self, host: Host) {
+        self.host = host;
+    }
+
+    pub fn get_host_fn(&self, name: &str) -> Option<&FnSig> {
+        self.host.fns.get(name)
+    }
+
+    /// the return type of the host function `name` called with `sig`,
+    /// `None` if the host has no function `name`
+    pub fn get_host_fn_ty(
+        &self,
+        call_site: Span,
+        name: &str,
+        sig: &[Type],
+    ) -> Result<Option<Type>> {
+        let f = match self.host.fns.get(name) {
+            Some(f) => f,
+            None => return Ok(None),
+        };
+        if f.arg_ty.len() != sig.len() {
+            return Err(Error::new_argc_mismatch(
+                call_site,
+                f.arg_ty.len(),
+                sig.len(),
+            ));
+        }
+        for (expect, got) in f.arg_ty.iter().zip(sig) {
+            if expect != got && *got != Type::Never {
+                return Err(Error::new_type_mismatch(call_site, expect, got));
+            }
+        }
+        for ty in f.arg_ty.iter().chain(std::iter::once(&f.out_ty)) {
+            self.check_host_ty(call_site.clone(), ty)?;
+        }
+        Ok(Some(f.out_ty.clone()))
+    }
+
+    /// host globals are visible in every function, unless a variable shadows them
+    pub fn get_host_var(&self, name: &str) -> Option<&GlobalSig> {
+        match self.find_var(name) {
+            Some(_) => None,
+            None => self.host.globals.get(name),
+        }
+    }
+
+    pub fn push_static(&mut self, name: &str, ty: Type, mutable: bool) {
+        self.statics.insert(name.into(), VisibleVar { ty, mutable });
+    }
+
+    /// the name in the script of the static `name` refers to in the current file,
+    /// `None` if it is no static or a variable shadows it
+    pub fn get_static_name(&self, name: &str) -> Option<String> {
+        if self.find_var(name).is_some() {
+            return None;
+        }
+        let name = self.resolve_name(name);
+        self.statics.contains_key(&name).then_some(name)
+    }
+
+    /// checks the type of `name`, if it is a host global, see [`Self::check_host_ty`]
+    pub fn check_host_var(&self, span: Span, name: &str) -> Result<()> {
+        match self.get_host_var(name) {
+            Some(global) => self.check_host_ty(span, &global.ty),
+            None => Ok(()),
+        }
+    }
+
+    /// checks that the structs `ty` is made of are declared like the host declares them
+    fn check_host_ty(&self, span: Span, ty: &Type) -> Result<()> {
+        match ty {
+            Type::Named(name) => {
+                let s = self.get_struct(span.clone(), name)?;
+                let fields = s
+                    .fields
+                    .iter()
+                    .map(|field| (field.name.value.clone(), field.ty.clone()));
+                match self.host.structs.get(name) {
+                    Some(expect) if expect.iter().cloned().eq(fields) => expect
+                        .iter()
+                        .try_for_each(|(_, ty)| self.check_host_ty(span.clone(), ty)),
+                    _ => Err(Error::new_host_ty_mismatch(span, name)),
+                }
+            }
+            Type::Tuple(tys) => tys
+                .iter()
+                .try_for_each(|ty| self.check_host_ty(span.clone(), ty)),
+            Type::Array(ty, _) => self.check_host_ty(span, ty),
+            _ => Ok(()),
+        }
+    }
+
+    /// checks that the bounds of the type parameters of `f` exist
+    pub fn check_generics(&self, f: &FunctionGen<'i>) -> Result<()> {
+        for (i, generic) in f.internal.generics.iter().enumerate() {
+            let name = generic.ident.value.as_str();
+            if f.internal.generics[..i]
+                .iter()
+                .any(|other| other.ident.value == name)
+            {
+                return Err(Error::new_generic_redefined(generic.span(), name));
+            }
+            for bound in generic.bounds.iter() {
+                self.has_bound(bound, &Type::Unit)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// whether `ty` satisfies the bound `bound` of a type parameter,
+    /// traits declared in the script shadow the builtin bounds
+    pub fn has_bound(&self, bound: &Ident, ty: &Type) -> Result<bool> {
+        if self.traits.contains_key(&bound.value) {
+            return Ok(self.impls.contains(&(bound.value.clone(), ty.clone())));
+        }
+        ty.has_builtin_bound(&bound.value)
+            .ok_or_else(|| Error::new_trait_not_found(bound.span(), &bound.value))
+    }
+
+    pub fn push_trait(&mut self, t: Trait<'i>) {
+        self.traits.insert(t.name.value.clone(), t);
+    }
+
+    pub fn get_trait(&self, span: Span, name: &str) -> Result<&Trait<'i>> {
+        if let Some(t) = self.traits.get(name) {
+            Ok(t)
+        } else {
+            Err(Error::new_trait_not_found(span, name))
+        }
+    }
+
+    pub fn push_impl(&mut self, span: Span, name: &str, ty: &Type) -> Result<()> {
+        if self.impls.insert((name.into(), ty.clone())) {
+            Ok(())
+        } else {
+            Err(Error::new_impl_redefined(span, name, ty))
+        }
+    }
+
+    /// makes the type parameters of a function instance refer to the types in `bindings`,
+    /// until the function body is left with [`Self::pop_fn_body`]
+    pub fn bind_generics(&mut self, bindings: HashMap<String, Type>) {
+        self.generics = bindings;
+    }
+
+    /// replaces the type parameters in a type annotation
+    /// with the types they stand for in the current function instance
+    /// and the names of structs and enums with their names in the script
+    pub fn resolve_ty(&self, ty: &Type) -> Type {
+        ty.map_named(&|name| match self.generics.get(name) {
+            Some(ty) => Some(ty.clone()),
+            None => Some(Type::Named(self.resolve_name(name))),
+        })
+    }
+
+    pub fn set_files(&mut self, files: Vec<HashMap<String, String>>) {
+        self.files = files;
+    }
+
+    /// makes names refer to the items of the file `file`,
+    /// until the function body is left with [`Self::pop_fn_body`]
+    pub fn enter_file(&mut self, file: usize) {
+        self.file = file;
+    }
+
+    /// the name in the script of the item called `name` in the current file
+    pub fn resolve_name(&self, name: &str) -> String {
+        self.files
+            .get(self.file)
+            .and_then(|names| names.get(name))
+            .map_or_else(|| name.into(), Clone::clone)
+    }
+
+    pub fn push_struct(&mut self, s: Struct<'i>) {
+        self.structs.insert(s.name.value.clone(), s);
+    }
+
+    pub fn get_struct(&self, span: Span, name: &str) -> Result<&Struct<'i>> {
+        if let Some(s) = self.structs.get(name) {
+            Ok(s)
         } else {
-            self.fn_ty_cache.insert(name.into(), Type::Unresolved);
-            Ok(self.get_fn(call_site, name, sig)?.type_of())
+            Err(Error::new_ty_not_found(span, name))
+        }
+    }
+
+    pub fn push_enum(&mut self, e: Enum<'i>) {
+        self.enums.insert(e.name.value.clone(), e);
+    }
+
+    pub fn get_enum(&self, span: Span, name: &str) -> Result<&Enum<'i>> {
+        if let Some(e) = self.enums.get(name) {
+            Ok(e)
+        } else {
+            Err(Error::new_ty_not_found(span, name))
+        }
+    }
+
+    /// checks that every struct or enum `ty` refers to is declared
+    pub fn check_ty(&self, span: Span, ty: &Type) -> Result<()> {
+        match ty {
+            Type::Named(name) if self.structs.contains_key(name) => Ok(()),
+            Type::Named(name) => self.get_enum(span, name).map(|_| ()),
+            Type::Tuple(tys) => tys
+                .iter()
+                .try_for_each(|ty| self.check_ty(span.clone(), ty)),
+            Type::Array(ty, _) => self.check_ty(span, ty),
+            Type::Fn(params, ret) => params
+                .iter()
+                .chain(std::iter::once(ret.as_ref()))
+                .try_for_each(|ty| self.check_ty(span.clone(), ty)),
+            _ => Ok(()),
+        }
+    }
+
+    /// values are stored inline, so a struct or enum
+    /// that contains itself would have an infinite size
+    pub fn check_recursion(&self, span: Span, name: &str) -> Result<()> {
+        let mut visited: Vec<&String> = vec![];
+        let mut contained = self.contained_tys(name);
+        while let Some(ty) = contained.pop() {
+            let inner = match ty {
+                Type::Named(inner) => inner,
+                Type::Tuple(tys) => {
+                    contained.extend(tys);
+                    continue;
+                }
+                Type::Array(ty, _) => {
+                    contained.push(ty);
+                    continue;
+                }
+                _ => continue,
+            };
+            if inner == name {
+                return Err(Error::new_recursive_ty(span, name));
+            }
+            if !visited.contains(&inner) {
+                visited.push(inner);
+                contained.extend(self.contained_tys(inner));
+            }
+        }
+        Ok(())
+    }
+
+    /// types stored inline in values of the struct or enum `name`
+    fn contained_tys(&self, name: &str) -> Vec<&Type> {
+        if let Some(s) = self.structs.get(name) {
+            s.fields.iter().map(|field| &field.ty).collect()
+        } else if let Some(e) = self.enums.get(name) {
+            e.variants
+                .iter()
+                .flat_map(|variant| variant.fields.iter())
+                .collect()
+        } else {
+            vec![]
         }
     }
 
@@ -153,22 +661,61 @@ impl<'i> VisibleVars<'i> {
             .last_mut()
             .as_mut()
             .unwrap()
-            .insert(name.into(), ty);
+            .insert(name.into(), VisibleVar { ty, mutable: false });
     }
 
-    pub fn get_var(&self, name: &str) -> Option<Type> {
-        let ty = self
-            .vars
-            .iter()
-            .rev()
-            .find_map(|map| map.get(name))
-            .cloned()?;
+    pub fn push_mut_var(&mut self, name: &str, ty: Type) {
+        log::debug!("push mut {name} = {ty}");
+        self.vars
+            .last_mut()
+            .as_mut()
+            .unwrap()
+            .insert(name.into(), VisibleVar { ty, mutable: true });
+    }
+
+    /// closures around the current scope capture
+    /// the variable if it is declared outside of them
+    pub fn get_var(&mut self, name: &str) -> Option<Type> {
+        let (depth, var) = match self.find_var(name) {
+            Some(found) => found,
+            None => match self.get_static_name(name) {
+                Some(name) => return Some(self.statics[&name].ty.clone()),
+                None => return self.get_host_var(name).map(|global| global.ty.clone()),
+            },
+        };
+        let ty = var.ty.clone();
+
+        for closure in self.closures.iter_mut().filter(|c| depth < c.depth) {
+            if closure.vars.iter().all(|(captured, _)| captured != name) {
+                closure.vars.push((name.into(), ty.clone()));
+            }
+        }
 
         log::debug!("get {name} = {ty}");
 
         Some(ty)
     }
 
+    /// closures capture copies of the values, so captured variables are never mutable
+    pub fn is_var_mutable(&self, name: &str) -> bool {
+        if let Some(name) = self.get_static_name(name) {
+            return self.statics[&name].mutable;
+        }
+        if let Some(global) = self.get_host_var(name) {
+            return global.mutable;
+        }
+        let captured = |depth| matches!(self.closures.last(), Some(c) if depth < c.depth);
+        matches!(self.find_var(name), Some((depth, var)) if var.mutable && !captured(depth))
+    }
+
+    fn find_var(&self, name: &str) -> Option<(usize, &VisibleVar)> {
+        self.vars
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(depth, map)| Some((depth, map.get(name)?)))
+    }
+
     pub fn push(&mut self) {
         self.vars.push(Default::default())
     }
@@ -176,6 +723,111 @@ impl<'i> VisibleVars<'i> {
     pub fn pop(&mut self) {
         self.vars.pop();
     }
+
+    /// `break_ty` is the type every `break` in this loop has to
+    /// produce, `None` lets the first `break` decide it
+    pub fn push_loop(&mut self, break_ty: Option<Type>) {
+        self.loops.push(break_ty)
+    }
+
+    /// returns the type produced by the `break`s of the loop,
+    /// `None` if the loop had none
+    pub fn pop_loop(&mut self) -> Option<Type> {
+        self.loops.pop().flatten()
+    }
+
+    pub fn break_loop(&mut self, span: Span, ty: Type) -> Result<()> {
+        let break_ty = match self.loops.last_mut() {
+            Some(break_ty) => break_ty,
+            None => return Err(Error::new_not_in_loop(span, "break")),
+        };
+
+        match break_ty {
+            None | Some(Type::Never | Type::Unresolved) => *break_ty = Some(ty),
+            Some(expect) if *expect == ty || matches!(ty, Type::Never | Type::Unresolved) => {}
+            Some(expect) => return Err(Error::new_type_mismatch(span, expect, &ty)),
+        }
+
+        Ok(())
+    }
+
+    /// enters a function body, `return_ty` is the type every `return` in it has
+    /// to produce, `None` lets the first `return` decide it
+    ///
+    /// returns the state of the outer function to give back to [`Self::pop_fn_body`],
+    /// variables and loops around the call site are not visible from inside the function
+    pub fn push_fn_body(&mut self, return_ty: Option<Type>) -> OuterFn {
+        OuterFn {
+            vars: std::mem::replace(&mut self.vars, vec![Default::default()]),
+            closures: std::mem::take(&mut self.closures),
+            loops: std::mem::take(&mut self.loops),
+            return_ty: std::mem::replace(&mut self.return_ty, return_ty),
+            generics: std::mem::take(&mut self.generics),
+            file: self.file,
+        }
+    }
+
+    /// returns the type produced by the `return`s of the function,
+    /// `None` if the function had none and no declared type
+    pub fn pop_fn_body(&mut self, outer: OuterFn) -> Option<Type> {
+        self.vars = outer.vars;
+        self.closures = outer.closures;
+        self.loops = outer.loops;
+        self.generics = outer.generics;
+        self.file = outer.file;
+        std::mem::replace(&mut self.return_ty, outer.return_ty)
+    }
+
+    /// enters a closure body, like [`Self::push_fn_body`] but
+    /// the variables around the closure stay visible and get captured
+    pub fn push_closure(&mut self, return_ty: Option<Type>) -> OuterFn {
+        self.closures.push(Captures {
+            depth: self.vars.len(),
+            vars: vec![],
+        });
+        self.push();
+        OuterFn {
+            vars: vec![],
+            closures: vec![],
+            loops: std::mem::take(&mut self.loops),
+            return_ty: std::mem::replace(&mut self.return_ty, return_ty),
+            generics: HashMap::new(),
+            file: self.file,
+        }
+    }
+
+    /// returns the type produced by the `return`s of the closure
+    /// and the variables it captured, in the order they are first used
+    pub fn pop_closure(&mut self, outer: OuterFn) -> (Option<Type>, Vec<(String, Type)>) {
+        self.pop();
+        let captures = self.closures.pop().expect("no closure to pop").vars;
+        self.loops = outer.loops;
+        let returned = std::mem::replace(&mut self.return_ty, outer.return_ty);
+        (returned, captures)
+    }
+
+    /// the type `return` has to produce, if it is known already
+    pub fn return_ty(&self) -> Option<Type> {
+        self.return_ty.clone()
+    }
+
+    pub fn return_fn(&mut self, span: Span, ty: Type) -> Result<()> {
+        match &self.return_ty {
+            None | Some(Type::Never | Type::Unresolved) => self.return_ty = Some(ty),
+            Some(expect) if *expect == ty || matches!(ty, Type::Never | Type::Unresolved) => {}
+            Some(expect) => return Err(Error::new_type_mismatch(span, expect, &ty)),
+        }
+
+        Ok(())
+    }
+
+    pub fn continue_loop(&self, span: Span) -> Result<()> {
+        if self.loops.is_empty() {
+            Err(Error::new_not_in_loop(span, "continue"))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 // ----------
@@ -183,7 +835,12 @@ impl<'i> VisibleVars<'i> {
 // ----------
 
 pub struct Error {
-    error: PestError<Rule>,
+    /// boxed, results are returned all through type checking and the pest error is large
+    error: Box<PestError<Rule>>,
+    /// the file the error is in, scripts parsed from a string have none
+    file: Option<Box<PathBuf>>,
+    /// the index of the source the error is in, see [`Self::in_source`]
+    source: Option<usize>,
 }
 
 pub type Result<T> = ::std::result::Result<T, Error>;
@@ -191,31 +848,72 @@ pub type Result<T> = ::std::result::Result<T, Error>;
 impl Error {
     pub fn new_spanned<S: Into<String>>(span: Span, message: S) -> Self {
         Self {
-            error: PestError::new_from_span(
+            error: Box::new(PestError::new_from_span(
                 ErrorVariant::CustomError {
                     message: message.into(),
                 },
                 span,
-            ),
+            )),
+            file: None,
+            source: None,
         }
     }
 
     pub fn new_pest(error: PestError<Rule>) -> Self {
-        Self { error }
+        Self {
+            error: Box::new(error),
+            file: None,
+            source: None,
+        }
+    }
+
+    /// the file the error is in, if the script was loaded from files
+    pub fn file(&self) -> Option<&Path> {
+        self.file.as_deref().map(PathBuf::as_path)
+    }
+
+    /// names `path` as the file the error is in, if it has none yet
+    pub fn with_file(mut self, path: &Path) -> Self {
+        if self.file.is_none() {
+            self.error = Box::new(self.error.with_path(&path.display().to_string()));
+            self.file = Some(Box::new(path.into()));
+        }
+        self
+    }
+
+    /// marks the error as being in the source at index `source` of the script,
+    /// if it has none yet, the innermost item an error is raised in names its source
+    pub fn in_source(mut self, source: usize) -> Self {
+        if self.source.is_none() {
+            self.source = Some(source);
+        }
+        self
+    }
+
+    /// names the file of the source the error is in, see [`Self::in_source`]
+    fn with_source_file(self, sources: &[Source]) -> Self {
+        let path = self
+            .source
+            .and_then(|source| sources.get(source))
+            .and_then(|source| source.path);
+        match path {
+            Some(path) => self.with_file(path),
+            None => self,
+        }
     }
 
     pub fn new_leftover_tokens(span: Span, token: Pair<Rule>) -> Self {
         Self::new_spanned(span, format!("unexpected token: '{}'", token.as_str()))
     }
 
-    pub fn new_invalid_unary_op(span: Span, op: UnaryOp, ty: Type) -> Self {
+    pub fn new_invalid_unary_op(span: Span, op: UnaryOp, ty: &Type) -> Self {
         Self::new_spanned(
             span,
             format!("unary operator: '{op}' cannot be applied to type: '{ty}'"),
         )
     }
 
-    pub fn new_invalid_binary_op(span: Span, lhs: Type, op: BinaryOp, rhs: Type) -> Self {
+    pub fn new_invalid_binary_op(span: Span, lhs: &Type, op: BinaryOp, rhs: &Type) -> Self {
         Self::new_spanned(
             span,
             format!("binary operator: '{op}' cannot be applied to lhs: '{lhs}' and rhs: '{rhs}'"),
@@ -258,6 +956,261 @@ impl Error {
     pub fn new_not_callable(span: Span, expect: &str) -> Self {
         Self::new_spanned(span, format!("variable '{expect}' is not callable"))
     }
+
+    pub fn new_assign_immutable(span: Span, name: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("cannot assign twice to immutable variable '{name}'"),
+        )
+    }
+
+    pub fn new_assign_host_var(span: Span, name: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("cannot assign to host global '{name}', the host registered it as immutable"),
+        )
+    }
+
+    pub fn new_static_ty(span: Span, name: &str, ty: &Type) -> Self {
+        Self::new_spanned(
+            span,
+            format!(
+                "static '{name}' cannot hold '{ty}', strings and functions don't outlive calls"
+            ),
+        )
+    }
+
+    pub fn new_not_in_loop(span: Span, keyword: &str) -> Self {
+        Self::new_spanned(span, format!("'{keyword}' outside of a loop"))
+    }
+
+    pub fn new_ty_not_found(span: Span, expect: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("type '{expect}' not found within accessible scopes"),
+        )
+    }
+
+    pub fn new_ty_redefined(span: Span, name: &str) -> Self {
+        Self::new_spanned(span, format!("type '{name}' is defined multiple times"))
+    }
+
+    pub fn new_recursive_ty(span: Span, name: &str) -> Self {
+        Self::new_spanned(span, format!("recursive type '{name}' has infinite size"))
+    }
+
+    pub fn new_field_not_found(span: Span, ty: &Type, field: &str) -> Self {
+        Self::new_spanned(span, format!("no field '{field}' on type '{ty}'"))
+    }
+
+    pub fn new_duplicate_field(span: Span, field: &str) -> Self {
+        Self::new_spanned(span, format!("field '{field}' specified more than once"))
+    }
+
+    pub fn new_missing_field(span: Span, ty: &Type, field: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("missing field '{field}' in initializer of '{ty}'"),
+        )
+    }
+
+    pub fn new_duplicate_variant(span: Span, variant: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("variant '{variant}' is defined multiple times"),
+        )
+    }
+
+    pub fn new_variant_not_found(span: Span, ty: &Type, variant: &str) -> Self {
+        Self::new_spanned(span, format!("no variant '{variant}' on type '{ty}'"))
+    }
+
+    pub fn new_pattern_mismatch(span: Span, expect: &Type, pattern: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("pattern '{pattern}' cannot match values of type '{expect}'"),
+        )
+    }
+
+    pub fn new_not_indexable(span: Span, ty: &Type) -> Self {
+        Self::new_spanned(span, format!("cannot index into a value of type '{ty}'"))
+    }
+
+    pub fn new_not_iterable(span: Span, ty: &Type) -> Self {
+        Self::new_spanned(span, format!("a value of type '{ty}' is not iterable"))
+    }
+
+    pub fn new_refutable_pattern(span: Span, witness: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("refutable pattern in local binding: '{witness}' not covered"),
+        )
+    }
+
+    pub fn new_non_exhaustive(span: Span, witness: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("non-exhaustive patterns: '{witness}' not covered"),
+        )
+    }
+
+    pub fn new_invalid_cast(span: Span, from: &Type, to: &Type) -> Self {
+        Self::new_spanned(span, format!("cannot cast '{from}' as '{to}'"))
+    }
+
+    pub fn new_lit_out_of_range(span: Span, ty: &str) -> Self {
+        Self::new_spanned(span, format!("literal out of range for '{ty}'"))
+    }
+
+    pub fn new_invalid_param_ty(span: Span, name: &str, ty: &Type) -> Self {
+        Self::new_spanned(span, format!("parameter '{name}' cannot have type '{ty}'"))
+    }
+
+    pub fn new_untyped_param(span: Span, name: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("type annotations needed for parameter '{name}'"),
+        )
+    }
+
+    pub fn new_untyped_return(span: Span, name: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("type annotations needed for the return type of function '{name}'"),
+        )
+    }
+
+    pub fn new_generic_fn_value(span: Span, name: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("generic function '{name}' cannot be used as a value"),
+        )
+    }
+
+    pub fn new_generic_redefined(span: Span, name: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("type parameter '{name}' is defined multiple times"),
+        )
+    }
+
+    pub fn new_trait_not_found(span: Span, name: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("trait '{name}' not found within accessible scopes"),
+        )
+    }
+
+    pub fn new_impl_redefined(span: Span, name: &str, ty: &Type) -> Self {
+        Self::new_spanned(
+            span,
+            format!("conflicting implementations of trait '{name}' for type '{ty}'"),
+        )
+    }
+
+    pub fn new_missing_self(span: Span, name: &str) -> Self {
+        Self::new_spanned(span, format!("method '{name}' has no 'self' parameter"))
+    }
+
+    pub fn new_method_redefined(span: Span, name: &str, owner: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("method '{name}' is defined multiple times for '{owner}'"),
+        )
+    }
+
+    pub fn new_not_trait_member(span: Span, name: &str, trait_name: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("method '{name}' is not a member of trait '{trait_name}'"),
+        )
+    }
+
+    pub fn new_missing_method(span: Span, name: &str, trait_name: &str, ty: &Type) -> Self {
+        Self::new_spanned(
+            span,
+            format!("method '{name}' of trait '{trait_name}' is not implemented for '{ty}'"),
+        )
+    }
+
+    pub fn new_method_not_found(span: Span, ty: &Type, name: &str) -> Self {
+        Self::new_spanned(span, format!("no method '{name}' on type '{ty}'"))
+    }
+
+    pub fn new_host_ty_mismatch(span: Span, name: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("struct '{name}' is declared differently by the host"),
+        )
+    }
+
+    pub fn new_module_not_found(span: Span, module: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("module '{module}' not found, modules can only be used by scripts loaded from a file"),
+        )
+    }
+
+    pub fn new_module_not_loaded(span: Span, module: &str, path: &Path, err: &io::Error) -> Self {
+        Self::new_spanned(
+            span,
+            format!(
+                "module '{module}' could not be loaded from '{}': {err}",
+                path.display()
+            ),
+        )
+    }
+
+    pub fn new_module_not_declared(span: Span, module: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("module '{module}' not found, declare it with 'mod {module};'"),
+        )
+    }
+
+    pub fn new_cyclic_modules(span: Span, cycle: &str) -> Self {
+        Self::new_spanned(span, format!("modules declare each other: {cycle}"))
+    }
+
+    pub fn new_use_without_module(span: Span, name: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("'{name}' has no module, use 'use module::{name};'"),
+        )
+    }
+
+    pub fn new_item_not_found(span: Span, name: &str, module: &str) -> Self {
+        Self::new_spanned(span, format!("'{name}' not found in module '{module}'"))
+    }
+
+    pub fn new_name_redefined(span: Span, name: &str) -> Self {
+        Self::new_spanned(span, format!("name '{name}' is defined multiple times"))
+    }
+
+    pub fn new_statement_in_module(span: Span, module: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("module '{module}' has a statement outside of a function, only the root file can have them"),
+        )
+    }
+
+    pub fn new_unbound_generic(span: Span, name: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("type annotations needed, cannot infer type parameter '{name}'"),
+        )
+    }
+
+    pub fn new_unsatisfied_bound(span: Span, ty: &Type, name: &str, bound: &str) -> Self {
+        Self::new_spanned(
+            span,
+            format!("type '{ty}' does not satisfy the bound '{bound}' of type parameter '{name}'"),
+        )
+    }
+
+    pub fn new_invalid_escape(span: Span, escape: &str) -> Self {
+        Self::new_spanned(span, format!("invalid character escape '{escape}'"))
+    }
 }
 
 impl Debug for Error {
@@ -292,11 +1245,6 @@ where
 
     fn parse(token: Pair<'i, Rule>) -> Result<Self>;
 
-    fn parse_spanned(token: Pair<'i, Rule>) -> Result<(Self, Span<'i>)> {
-        let span = token.as_span();
-        Ok((Self::parse(token)?, span))
-    }
-
     fn parse_single(mut tokens: Pairs<'i, Rule>) -> Result<Self> {
         let result = Self::parse(tokens.next().unwrap());
         if let Some(token) = tokens.next() {
@@ -305,10 +1253,6 @@ where
             result
         }
     }
-
-    fn parse_multiple(tokens: Pairs<'i, Rule>) -> Result<Vec<Self>> {
-        tokens.into_iter().map(|token| Self::parse(token)).collect()
-    }
 }
 
 //
@@ -322,7 +1266,7 @@ pub trait TypeOf<'i> {
 
     fn type_check(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
         #[allow(deprecated)]
-        let _ = self.type_check_impl(vars)?;
+        self.type_check_impl(vars)?;
 
         let _ = self.type_of();
 
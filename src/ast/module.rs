@@ -1,6 +1,8 @@
-use super::{Ast, Function, FunctionGen, Result, Rule, Statement, Type, TypeOf, VisibleVars};
+use super::{
+    files::module_name, Ast, Enum, Error, Function, FunctionGen, Ident, Impl, Result, Rule,
+    Statement, Static, Struct, Trait, Type, TypeOf, VisibleVars,
+};
 use crate::ast::match_rule;
-use itertools::{Either, Itertools};
 use pest::{iterators::Pair, Span};
 use std::{collections::HashMap, fmt::Display};
 
@@ -10,11 +12,29 @@ use std::{collections::HashMap, fmt::Display};
 pub struct Module<'i> {
     pub function_gens: HashMap<String, FunctionGen<'i>>,
     pub functions: HashMap<String, Function<'i>>,
+    pub structs: HashMap<String, Struct<'i>>,
+    pub enums: HashMap<String, Enum<'i>>,
+    pub traits: HashMap<String, Trait<'i>>,
+    /// in the order they are declared, which is the order they are initialized in
+    pub statics: Vec<Static<'i>>,
+    /// `mod a::b;`, the modules this file uses
+    pub mods: Vec<Ident<'i>>,
+    pub uses: Vec<Use<'i>>,
+    impls: Vec<Impl<'i>>,
     global: Vec<Statement<'i>>,
 
     span: Span<'i>,
 }
 
+/// `use a::b::item;` or `use a::b::item as name;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Use<'i> {
+    pub module: Ident<'i>,
+    pub item: Ident<'i>,
+    /// the name of the item in the file with the `use`
+    pub alias: Ident<'i>,
+}
+
 /* #[derive(Debug, Clone)]
 enum Global<'i> {
     Function(Function<'i>),
@@ -33,34 +53,84 @@ impl<'i> Ast<'i> for Module<'i> {
         match_rule(&span, token.as_rule(), Rule::module)?;
         let tokens = token.into_inner();
 
-        let (global, functions): (Vec<Result<_>>, Vec<Result<_>>) =
-            tokens.partition_map(|token| match token.as_rule() {
-                Rule::statement => Either::Left(Statement::parse(token)),
-                Rule::function => Either::Right(FunctionGen::parse(token)),
+        let mut global = vec![];
+        let mut functions = vec![];
+        let mut structs = HashMap::new();
+        let mut enums = HashMap::new();
+        let mut traits = HashMap::new();
+        let mut impls = vec![];
+        let mut statics: Vec<Static> = vec![];
+        let mut mods = vec![];
+        let mut uses = vec![];
+        for token in tokens {
+            match token.as_rule() {
+                Rule::statement => global.push(Statement::parse(token)),
+                Rule::function => functions.push(FunctionGen::parse(token)),
+                Rule::struct_decl => {
+                    let s = Struct::parse(token)?;
+                    let name = s.name.value.clone();
+                    if structs.contains_key(&name) || enums.contains_key(&name) {
+                        return Err(Error::new_ty_redefined(s.span(), &name));
+                    }
+                    structs.insert(name, s);
+                }
+                Rule::enum_decl => {
+                    let e = Enum::parse(token)?;
+                    let name = e.name.value.clone();
+                    if structs.contains_key(&name) || enums.contains_key(&name) {
+                        return Err(Error::new_ty_redefined(e.span(), &name));
+                    }
+                    enums.insert(name, e);
+                }
+                Rule::trait_decl => {
+                    let t = Trait::parse(token)?;
+                    let name = t.name.value.clone();
+                    if traits.contains_key(&name) {
+                        return Err(Error::new_ty_redefined(t.span(), &name));
+                    }
+                    traits.insert(name, t);
+                }
+                Rule::impl_block => impls.push(Impl::parse(token)?),
+                Rule::static_decl => statics.push(Static::parse(token)?),
+                Rule::mod_decl => {
+                    let token = token.into_inner().next().unwrap();
+                    mods.push(Ident::from(module_name(token.clone()), token.as_span()));
+                }
+                Rule::use_decl => uses.push(Use::parse(token)?),
                 _ => unreachable!(),
-            });
+            }
+        }
 
         let global = global.into_iter().collect::<Result<Vec<_>>>()?;
         let functions = functions.into_iter().collect::<Result<Vec<_>>>()?;
-        let (functions, function_gens): (Vec<Function>, Vec<FunctionGen>) = functions
-            .into_iter()
-            .partition_map(|f| match Function::new_non_generic(f) {
-                Ok(f) => Either::Left(f),
-                Err(f) => Either::Right(f),
-            });
-
+        for (i, s) in statics.iter().enumerate() {
+            let name = &s.name.value;
+            if statics[..i].iter().any(|other| other.name.value == *name)
+                || functions.iter().any(|f| f.internal.name.value == *name)
+            {
+                return Err(Error::new_name_redefined(s.name.span(), name));
+            }
+        }
         let functions = functions
             .into_iter()
-            .map(|f| (f.internal.name.value.clone(), f))
-            .collect();
-        let function_gens = function_gens
+            .chain(statics.iter().map(Static::init));
+        // non generic functions are split off in the type check,
+        // after the names of all files are known
+        let function_gens = functions
             .into_iter()
             .map(|f| (f.internal.name.value.clone(), f))
             .collect();
 
         Ok(Self {
             function_gens,
-            functions,
+            functions: HashMap::new(),
+            structs,
+            enums,
+            traits,
+            statics,
+            mods,
+            uses,
+            impls,
             global,
 
             span,
@@ -68,35 +138,323 @@ impl<'i> Ast<'i> for Module<'i> {
     }
 }
 
+impl<'i> Ast<'i> for Use<'i> {
+    fn span(&self) -> Span<'i> {
+        self.item.span()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::use_decl)?;
+        let mut tokens = token.into_inner();
+
+        let mut path = tokens
+            .next()
+            .unwrap()
+            .into_inner()
+            .map(Ident::parse)
+            .collect::<Result<Vec<_>>>()?;
+        let item = path.pop().unwrap();
+        let module = match (path.first(), path.last()) {
+            (Some(first), Some(last)) => {
+                let names = path.iter().map(|name| name.value.as_str());
+                let span = first.span().start_pos().span(&last.span().end_pos());
+                Ident::from(names.collect::<Vec<_>>().join("::"), span)
+            }
+            _ => return Err(Error::new_use_without_module(item.span(), &item.value)),
+        };
+        let alias = match tokens.next() {
+            Some(token) => Ident::parse(token)?,
+            None => item.clone(),
+        };
+
+        Ok(Self {
+            module,
+            item,
+            alias,
+        })
+    }
+}
+
+impl<'i> Module<'i> {
+    /// type checks the instance of the generic function `name` for params `sig`
+    /// after the module was type checked, as if the script called it
+    ///
+    /// errors point at the declaration of the function
+    pub fn instantiate(
+        &mut self,
+        vars: &mut VisibleVars<'i>,
+        name: &str,
+        sig: &[Type],
+    ) -> Result<()> {
+        let call_site = match self.function_gens.get(name) {
+            Some(f) => f.internal.name.span(),
+            None => return Err(Error::new_fn_not_found(self.span(), name)),
+        };
+
+        // the functions go back to where the type checker left them
+        vars.function_gens = std::mem::take(&mut self.function_gens);
+        vars.functions = std::mem::take(&mut self.functions);
+        let instance = if vars.has_fn(name, sig) {
+            Ok(())
+        } else {
+            Function::new(vars, call_site, name, sig).map(|f| vars.push_fn(name, sig, f))
+        };
+        self.collect_functions(vars);
+
+        instance
+    }
+
+    /// moves the functions the type checker instantiated into the module,
+    /// named by their mangled names
+    fn collect_functions(&mut self, vars: &mut VisibleVars<'i>) {
+        self.function_gens.extend(vars.function_gens.drain());
+        self.functions.extend(vars.functions.drain());
+
+        for (name, f) in self.functions.iter_mut() {
+            f.internal.name.value = name.clone();
+        }
+    }
+
+    /// the items declared in this file, by their name in it and their name in the script
+    pub fn local_names(&self, module: &str) -> HashMap<String, String> {
+        let names = self
+            .function_gens
+            .keys()
+            .chain(self.structs.keys())
+            .chain(self.enums.keys())
+            .chain(self.traits.keys())
+            .chain(self.statics.iter().map(|s| &s.name.value));
+        names
+            .map(|name| match module {
+                "" => (name.clone(), name.clone()),
+                module => (name.clone(), format!("{module}::{name}")),
+            })
+            .collect()
+    }
+
+    /// gives every item of this file its name in the script and makes
+    /// the types in their signatures refer to items by those names too
+    ///
+    /// `names` is [`Self::local_names`] with the items imported by `use`,
+    /// `file` is the index of the file, function bodies look up names with it
+    pub fn namespace(&mut self, file: usize, names: &HashMap<String, String>) {
+        let rename = |name: &mut String| {
+            if let Some(renamed) = names.get(name) {
+                *name = renamed.clone();
+            }
+        };
+        let resolve = |ty: &Type, generics: &[&str]| {
+            ty.map_named(&|name| {
+                if generics.contains(&name) {
+                    return None;
+                }
+                names.get(name).map(|name| Type::Named(name.clone()))
+            })
+        };
+        let namespace_fn = |f: &mut FunctionGen| {
+            let generics: Vec<String> = f.generic_names().into_iter().map(String::from).collect();
+            let generics: Vec<&str> = generics.iter().map(String::as_str).collect();
+            let f = f.internal.as_mut();
+            for param in f.params.iter_mut() {
+                param.ty = resolve(&param.ty, &generics);
+            }
+            f.fn_ty.ty = resolve(&f.fn_ty.ty, &generics);
+            for bound in f.generics.iter_mut().flat_map(|g| g.bounds.iter_mut()) {
+                rename(&mut bound.value);
+            }
+            f.file = file;
+        };
+
+        self.function_gens = std::mem::take(&mut self.function_gens)
+            .into_values()
+            .map(|mut f| {
+                namespace_fn(&mut f);
+                rename(&mut f.internal.name.value);
+                (f.internal.name.value.clone(), f)
+            })
+            .collect();
+        self.structs = std::mem::take(&mut self.structs)
+            .into_values()
+            .map(|mut s| {
+                for field in s.fields.iter_mut() {
+                    field.ty = resolve(&field.ty, &[]);
+                }
+                rename(&mut s.name.value);
+                s.file = file;
+                (s.name.value.clone(), s)
+            })
+            .collect();
+        self.enums = std::mem::take(&mut self.enums)
+            .into_values()
+            .map(|mut e| {
+                for ty in e.variants.iter_mut().flat_map(|v| v.fields.iter_mut()) {
+                    *ty = resolve(ty, &[]);
+                }
+                rename(&mut e.name.value);
+                e.file = file;
+                (e.name.value.clone(), e)
+            })
+            .collect();
+        self.traits = std::mem::take(&mut self.traits)
+            .into_values()
+            .map(|mut t| {
+                for method in t.methods.iter_mut() {
+                    for param in method.params.iter_mut() {
+                        param.ty = resolve(&param.ty, &[]);
+                    }
+                    method.fn_ty.ty = resolve(&method.fn_ty.ty, &[]);
+                }
+                rename(&mut t.name.value);
+                t.file = file;
+                (t.name.value.clone(), t)
+            })
+            .collect();
+        for s in self.statics.iter_mut() {
+            s.type_decl = s.type_decl.as_ref().map(|ty| resolve(ty, &[]));
+            rename(&mut s.name.value);
+            s.file = file;
+        }
+        for i in self.impls.iter_mut() {
+            if let Some(trait_name) = i.trait_name.as_mut() {
+                rename(&mut trait_name.value);
+            }
+            i.ty = resolve(&i.ty, &[]);
+            i.functions.iter_mut().for_each(namespace_fn);
+            i.file = file;
+        }
+    }
+
+    /// adds the items of another file
+    pub fn merge(&mut self, other: Module<'i>) {
+        self.function_gens.extend(other.function_gens);
+        self.structs.extend(other.structs);
+        self.enums.extend(other.enums);
+        self.traits.extend(other.traits);
+        self.statics.extend(other.statics);
+        self.impls.extend(other.impls);
+    }
+
+    /// top level statements, only the root file can have them
+    pub fn statements(&self) -> &[Statement<'i>] {
+        &self.global
+    }
+}
+
 impl<'i> TypeOf<'i> for Module<'i> {
     fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
         let mut statements = vec![];
         std::mem::swap(&mut statements, &mut self.global);
 
+        // types first, everything else can refer to them
+        for s in self.structs.values() {
+            vars.push_struct(s.clone());
+        }
+        for e in self.enums.values() {
+            vars.push_enum(e.clone());
+        }
+        for s in self.structs.values_mut() {
+            s.type_check(vars).map_err(|err| err.in_source(s.file))?;
+        }
+        for e in self.enums.values_mut() {
+            e.type_check(vars).map_err(|err| err.in_source(e.file))?;
+        }
+
+        // then traits and impls, the methods are functions like any other
+        for t in self.traits.values() {
+            vars.push_trait(t.clone());
+        }
+        for t in self.traits.values_mut() {
+            t.type_check(vars).map_err(|err| err.in_source(t.file))?;
+        }
+        let mut methods: Vec<FunctionGen> = vec![];
+        for mut i in self.impls.drain(..) {
+            i.type_check(vars).map_err(|err| err.in_source(i.file))?;
+            for f in i.functions {
+                let name = &f.internal.name;
+                if methods
+                    .iter()
+                    .any(|other| other.internal.name.value == name.value)
+                {
+                    let (ty, method) = name.value.rsplit_once("::").unwrap();
+                    return Err(Error::new_method_redefined(name.span(), method, ty)
+                        .in_source(f.internal.file));
+                }
+                methods.push(f);
+            }
+        }
+        let functions = std::mem::take(&mut self.function_gens).into_values();
+        for f in methods.into_iter().chain(functions) {
+            // host functions can't be shadowed, calls would be ambiguous
+            let name = &f.internal.name;
+            if vars.get_host_fn(&name.value).is_some() {
+                return Err(
+                    Error::new_name_redefined(name.span(), &name.value).in_source(f.internal.file)
+                );
+            }
+            match Function::new_non_generic(f) {
+                Ok(f) => {
+                    self.functions.insert(f.internal.name.value.clone(), f);
+                }
+                Err(f) => {
+                    self.function_gens.insert(f.internal.name.value.clone(), f);
+                }
+            }
+        }
+
         for (_, f) in self.function_gens.drain() {
+            vars.check_generics(&f)
+                .map_err(|err| err.in_source(f.internal.file))?;
             vars.push_fn_gen(&f.internal.name.value.clone(), f);
         }
 
+        let mut non_generic = vec![];
         for (_, f) in self.functions.drain() {
-            let sig: Box<[Type]> = f.internal.params.iter().map(|param| param.ty).collect();
-            vars.push_fn(&f.internal.name.value.clone(), &sig, f);
+            let sig: Box<[Type]> = f
+                .internal
+                .params
+                .iter()
+                .map(|param| param.ty.clone())
+                .collect();
+            let name = f.internal.name.value.clone();
+            let span = f.internal.name.span();
+            vars.push_fn(&name, &sig, f);
+            non_generic.push((name, span, sig));
         }
 
-        let global = Function::global(vars, statements, self.span())?;
+        // statics are initialized in order, the ones with a declared
+        // type can be used before that, by functions called earlier
+        for s in self.statics.iter() {
+            let name = &s.name;
+            if vars.get_host_var(&name.value).is_some() || vars.get_host_fn(&name.value).is_some() {
+                return Err(Error::new_name_redefined(name.span(), &name.value).in_source(s.file));
+            }
+            if let Some(ty) = s.type_decl.as_ref() {
+                vars.check_ty(name.span(), ty)
+                    .map_err(|err| err.in_source(s.file))?;
+                vars.push_static(&name.value, ty.clone(), s.mutable);
+            }
+        }
+        for s in self.statics.iter_mut() {
+            s.type_check(vars).map_err(|err| err.in_source(s.file))?;
+        }
+
+        // non generic functions are checked even if nothing calls them
+        for (name, span, sig) in non_generic {
+            vars.get_fn(span, &name, &sig)?;
+        }
+
+        let global =
+            Function::global(vars, statements, self.span()).map_err(|err| err.in_source(0))?;
         let sig: Box<[Type]> = global
             .internal
             .params
             .iter()
-            .map(|param| param.ty)
+            .map(|param| param.ty.clone())
             .collect();
         vars.push_fn(&global.internal.name.value.clone(), &sig, global);
 
-        self.function_gens.extend(vars.function_gens.drain());
-        self.functions.extend(vars.functions.drain());
-
-        for (name, f) in self.functions.iter_mut() {
-            f.internal.name.value = name.clone();
-        }
+        self.collect_functions(vars);
 
         Ok(())
     }
@@ -108,6 +466,18 @@ impl<'i> TypeOf<'i> for Module<'i> {
 
 impl<'i> Display for Module<'i> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (_, s) in self.structs.iter() {
+            s.fmt(f)?;
+        }
+        for (_, e) in self.enums.iter() {
+            e.fmt(f)?;
+        }
+        for (_, t) in self.traits.iter() {
+            t.fmt(f)?;
+        }
+        for s in self.statics.iter() {
+            s.fmt(f)?;
+        }
         for (_, function) in self.function_gens.iter() {
             function.fmt(f)?;
         }
@@ -0,0 +1,369 @@
+use super::{match_rule, Ast, Error, Ident, Lit, Result, Rule, Type, TypeOf, VisibleVars};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatternKind<'i> {
+    /// `_`
+    Wildcard,
+
+    /// `name` / `mut name`, binds the matched value to a new variable
+    Binding { name: Ident<'i>, mutable: bool },
+
+    /// `5` / `true`
+    Lit(Lit),
+
+    /// `(patterns..)`
+    Tuple(Vec<Pattern<'i>>),
+
+    /// `Name::Variant(patterns..)`
+    Variant {
+        name: Ident<'i>,
+        variant: Ident<'i>,
+        fields: Vec<Pattern<'i>>,
+
+        /// index of the variant in the enum declaration, which is also its tag
+        index: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern<'i> {
+    pub kind: PatternKind<'i>,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> Pattern<'i> {
+    /// checks that this pattern can match values of `ty`
+    /// and declares all of its bindings in the current scope
+    pub fn check(&mut self, vars: &mut VisibleVars<'i>, ty: &Type) -> Result<()> {
+        let span = self.span();
+        let shown = self.to_string();
+        let mismatch = || Error::new_pattern_mismatch(span.clone(), ty, &shown);
+
+        match (&mut self.kind, ty) {
+            (PatternKind::Wildcard, _) => {}
+            (PatternKind::Binding { name, mutable }, ty) => {
+                if *mutable {
+                    vars.push_mut_var(&name.value, ty.clone())
+                } else {
+                    vars.push_var(&name.value, ty.clone())
+                }
+            }
+            // `0` matching a `u8` is `0u8`
+            (PatternKind::Lit(lit @ Lit::I64(_)), ty)
+                if ty.is_int() && Lit::is_unsuffixed_int(span.as_str()) =>
+            {
+                *lit = lit
+                    .to_int_type(ty)
+                    .ok_or_else(|| Error::new_lit_out_of_range(span.clone(), &ty.to_string()))?;
+            }
+            (PatternKind::Lit(lit), ty) if lit.type_of() == *ty || *ty == Type::Unresolved => {}
+            (PatternKind::Tuple(fields), Type::Tuple(tys)) => {
+                if tys.len() != fields.len() {
+                    return Err(mismatch());
+                }
+                for (field, ty) in fields.iter_mut().zip(tys.iter()) {
+                    field.check(vars, ty)?;
+                }
+            }
+            (PatternKind::Tuple(fields), Type::Never | Type::Unresolved) => {
+                for field in fields.iter_mut() {
+                    field.check(vars, ty)?;
+                }
+            }
+            (
+                PatternKind::Variant {
+                    name,
+                    variant,
+                    fields,
+                    index,
+                },
+                Type::Named(_) | Type::Unresolved,
+            ) => {
+                name.value = vars.resolve_name(&name.value);
+                let named = Type::Named(name.value.clone());
+                if *ty != Type::Unresolved && *ty != named {
+                    return Err(mismatch());
+                }
+
+                let decl = vars.get_enum(name.span(), &name.value)?;
+                let (i, decl) = decl.variant(&variant.value).ok_or_else(|| {
+                    Error::new_variant_not_found(variant.span(), &named, &variant.value)
+                })?;
+                if decl.fields.len() != fields.len() {
+                    return Err(Error::new_argc_mismatch(
+                        span,
+                        decl.fields.len(),
+                        fields.len(),
+                    ));
+                }
+                *index = i;
+
+                let field_tys = decl.fields.clone();
+                for (field, ty) in fields.iter_mut().zip(field_tys.iter()) {
+                    field.check(vars, ty)?;
+                }
+            }
+            _ => return Err(mismatch()),
+        }
+
+        self.ty = Some(ty.clone());
+
+        Ok(())
+    }
+
+    /// type of the values this pattern matches
+    pub fn ty(&self) -> &Type {
+        self.ty.as_ref().expect("Pattern was not type checked")
+    }
+
+    /// returns a value not matched by any of `patterns`
+    /// or `None` if the patterns are exhaustive
+    pub fn missing(vars: &VisibleVars<'i>, patterns: &[&Pattern<'i>], ty: &Type) -> Option<String> {
+        let rows: Vec<Row> = patterns
+            .iter()
+            .map(|&pattern| vec![Some(pattern)])
+            .collect();
+        missing(vars, &rows, std::slice::from_ref(ty)).map(|mut witness| witness.remove(0))
+    }
+}
+
+impl<'i> Ast<'i> for Pattern<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::pattern)?;
+        let token = token.into_inner().next().unwrap();
+
+        let kind = match token.as_rule() {
+            Rule::wildcard => PatternKind::Wildcard,
+            Rule::binding => {
+                let mut tokens = token.into_inner();
+                let mutable = tokens.peek().map(|token| token.as_rule()) == Some(Rule::mutable);
+                if mutable {
+                    tokens.next();
+                }
+                PatternKind::Binding {
+                    name: Ident::parse(tokens.next().unwrap())?,
+                    mutable,
+                }
+            }
+            Rule::tuple_pat => PatternKind::Tuple(
+                token
+                    .into_inner()
+                    .map(Pattern::parse)
+                    .collect::<Result<_>>()?,
+            ),
+            Rule::int => PatternKind::Lit(Lit::parse_int(token)?),
+            Rule::bool => PatternKind::Lit(Lit::Bool(token.as_str().parse().unwrap())),
+            Rule::variant_pat => {
+                let mut tokens = token.into_inner();
+                let name = Ident::parse(tokens.next().unwrap())?;
+                let variant = Ident::parse(tokens.next().unwrap())?;
+                let fields = tokens.map(Pattern::parse).collect::<Result<_>>()?;
+                PatternKind::Variant {
+                    name,
+                    variant,
+                    fields,
+                    index: 0,
+                }
+            }
+            other => unreachable!("{:?}", other),
+        };
+
+        Ok(Self {
+            kind,
+            span,
+            ty: None,
+        })
+    }
+}
+
+impl<'i> Display for Pattern<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            PatternKind::Wildcard => write!(f, "_"),
+            PatternKind::Binding {
+                name,
+                mutable: true,
+            } => write!(f, "mut {name}"),
+            PatternKind::Binding { name, .. } => write!(f, "{name}"),
+            PatternKind::Lit(lit) => write!(f, "{lit}"),
+            PatternKind::Tuple(fields) if fields.len() == 1 => write!(f, "({},)", fields[0]),
+            PatternKind::Tuple(fields) => {
+                write!(f, "(")?;
+                for (i, field) in fields.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{field}")?;
+                }
+                write!(f, ")")
+            }
+            PatternKind::Variant {
+                name,
+                variant,
+                fields,
+                ..
+            } => {
+                write!(f, "{name}::{variant}")?;
+                if !fields.is_empty() {
+                    write!(f, "(")?;
+                    for (i, field) in fields.iter().enumerate() {
+                        if i != 0 {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{field}")?;
+                    }
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// -----------------
+// Exhaustiveness
+// -----------------
+
+/// a constructor of values, patterns either match one constructor or all of them
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Ctor {
+    Variant(usize),
+    Tuple,
+    Bool(bool),
+    Int(i128),
+}
+
+/// one pattern per column, `None` matches everything
+type Row<'a, 'i> = Vec<Option<&'a Pattern<'i>>>;
+
+fn ctor(pattern: Option<&Pattern>) -> Option<Ctor> {
+    match &pattern?.kind {
+        PatternKind::Wildcard | PatternKind::Binding { .. } => None,
+        PatternKind::Lit(Lit::Bool(b)) => Some(Ctor::Bool(*b)),
+        PatternKind::Lit(lit) => Some(Ctor::Int(lit.as_int().unwrap())),
+        PatternKind::Tuple(_) => Some(Ctor::Tuple),
+        PatternKind::Variant { index, .. } => Some(Ctor::Variant(*index)),
+    }
+}
+
+/// every constructor of `ty` with the types of its fields,
+/// `None` if there are too many to list
+fn all_ctors(vars: &VisibleVars, ty: &Type) -> Option<Vec<(Ctor, Vec<Type>)>> {
+    match ty {
+        // nothing to match, so no pattern can miss anything
+        Type::Never => Some(vec![]),
+        Type::Tuple(tys) => Some(vec![(Ctor::Tuple, tys.clone())]),
+        Type::Bool => Some(vec![
+            (Ctor::Bool(false), vec![]),
+            (Ctor::Bool(true), vec![]),
+        ]),
+        Type::Named(name) => {
+            let decl = vars.enums.get(name)?;
+            Some(
+                decl.variants
+                    .iter()
+                    .enumerate()
+                    .map(|(i, variant)| (Ctor::Variant(i), variant.fields.clone()))
+                    .collect(),
+            )
+        }
+        _ => None,
+    }
+}
+
+fn ctor_name(vars: &VisibleVars, ty: &Type, ctor: Ctor, fields: Vec<String>) -> String {
+    match (ctor, ty) {
+        (Ctor::Bool(b), _) => b.to_string(),
+        (Ctor::Int(i), _) => i.to_string(),
+        (Ctor::Tuple, _) if fields.len() == 1 => format!("({},)", fields[0]),
+        (Ctor::Tuple, _) => format!("({})", fields.join(", ")),
+        (Ctor::Variant(i), Type::Named(name)) => {
+            let variant = &vars.enums[name].variants[i].name;
+            if fields.is_empty() {
+                format!("{name}::{variant}")
+            } else {
+                format!("{name}::{variant}({})", fields.join(", "))
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// rows that can match `ctor`, with the first column replaced by the fields of `ctor`
+fn specialize<'a, 'i>(rows: &[Row<'a, 'i>], ctor: Ctor, arity: usize) -> Vec<Row<'a, 'i>> {
+    rows.iter()
+        .filter_map(|row| {
+            let mut specialized: Row = match (self::ctor(row[0]), row[0].map(|p| &p.kind)) {
+                (None, _) => vec![None; arity],
+                (Some(c), _) if c != ctor => return None,
+                (_, Some(PatternKind::Variant { fields, .. } | PatternKind::Tuple(fields))) => {
+                    fields.iter().map(Some).collect()
+                }
+                _ => vec![],
+            };
+            specialized.extend(row[1..].iter().copied());
+            Some(specialized)
+        })
+        .collect()
+}
+
+/// usefulness check from 'Warnings for pattern matching' by Luc Maranget
+///
+/// returns values, one per column, that none of the `rows` match
+fn missing(vars: &VisibleVars, rows: &[Row], tys: &[Type]) -> Option<Vec<String>> {
+    let (ty, rest) = match tys.split_first() {
+        Some(split) => split,
+        None if rows.is_empty() => return Some(vec![]),
+        None => return None,
+    };
+
+    let used: Vec<Ctor> = rows.iter().filter_map(|row| ctor(row[0])).collect();
+
+    match all_ctors(vars, ty) {
+        Some(all) if all.iter().all(|(c, _)| used.contains(c)) => {
+            // every constructor is used, but one of them might miss some fields
+            all.into_iter().find_map(|(c, fields)| {
+                let arity = fields.len();
+                let tys: Vec<Type> = fields.into_iter().chain(rest.iter().cloned()).collect();
+                let mut witness = missing(vars, &specialize(rows, c, arity), &tys)?;
+                let rest = witness.split_off(arity);
+                Some(
+                    std::iter::once(ctor_name(vars, ty, c, witness))
+                        .chain(rest)
+                        .collect(),
+                )
+            })
+        }
+        all => {
+            // some constructor is not used, only the rows matching everything match it
+            let default: Vec<Row> = rows
+                .iter()
+                .filter(|row| ctor(row[0]).is_none())
+                .map(|row| row[1..].to_vec())
+                .collect();
+            let witness = missing(vars, &default, rest)?;
+
+            let head = match all {
+                Some(all) if !used.is_empty() => {
+                    let (c, fields) = all.into_iter().find(|(c, _)| !used.contains(c)).unwrap();
+                    ctor_name(vars, ty, c, vec!["_".into(); fields.len()])
+                }
+                _ => "_".into(),
+            };
+            Some(std::iter::once(head).chain(witness).collect())
+        }
+    }
+}
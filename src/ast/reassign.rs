@@ -0,0 +1,98 @@
+use super::{
+    match_rule, Access, Ast, BinaryExpr, BinaryOp, Error, Expr, Ident, Result, Rule, Term, Type,
+    TypeOf, VisibleVars,
+};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reassign<'i> {
+    pub name: Ident<'i>,
+    pub expr: Expr<'i>,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> Ast<'i> for Reassign<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::reassign)?;
+        let mut tokens = token.into_inner();
+
+        let name = Ident::parse(tokens.next().unwrap())?;
+        let mut token = tokens.next().unwrap();
+
+        // `a += b` is `a = a + b`
+        let compound = if token.as_rule() == Rule::compound {
+            let op = token.into_inner().next().unwrap().as_rule();
+            token = tokens.next().unwrap();
+            Some(BinaryOp::from_rule(op))
+        } else {
+            None
+        };
+        let mut expr = Expr::parse(token)?;
+        if let Some(op) = compound {
+            let lhs = Expr::from(Term::from(Access::new(name.clone())));
+            expr = Expr::from(BinaryExpr::from_op(span.clone(), lhs, op, expr));
+        }
+
+        Ok(Self {
+            name,
+            expr,
+
+            span,
+            ty: None,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for Reassign<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        if let Some(name) = vars.get_static_name(&self.name.value) {
+            self.name.value = name;
+        }
+        let name = self.name.value.as_str();
+        let expect = match vars.get_var(name) {
+            Some(ty) => ty,
+            None => return Err(Error::new_var_not_found(self.name.span(), name)),
+        };
+        vars.check_host_var(self.name.span(), name)?;
+        if !vars.is_var_mutable(name) {
+            if vars.get_host_var(name).is_some() {
+                return Err(Error::new_assign_host_var(self.span(), name));
+            }
+            return Err(Error::new_assign_immutable(self.span(), name));
+        }
+
+        self.expr.infer_params(&expect);
+        self.expr.type_check(vars)?;
+        self.expr.infer_lit(&expect)?;
+        let got = self.expr.type_of();
+        if expect != got && got != Type::Never {
+            return Err(Error::new_type_mismatch(self.expr.span(), &expect, &got));
+        }
+
+        self.ty = Some(Type::Unit);
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Display for Reassign<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} = {}", self.name, self.expr)
+    }
+}
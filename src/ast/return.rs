@@ -0,0 +1,72 @@
+use super::{match_rule, Ast, Expr, Result, Rule, Type, TypeOf, VisibleVars};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Return<'i> {
+    pub expr: Option<Expr<'i>>,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> Ast<'i> for Return<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::return_expr)?;
+        let mut tokens = token.into_inner();
+
+        let expr = tokens.next().map(Expr::parse).transpose()?;
+
+        Ok(Self {
+            expr,
+
+            span,
+            ty: None,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for Return<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        let ty = match self.expr.as_mut() {
+            Some(expr) => {
+                if let Some(expect) = vars.return_ty() {
+                    expr.infer_params(&expect);
+                }
+                expr.type_check(vars)?;
+                if let Some(expect) = vars.return_ty() {
+                    expr.infer_lit(&expect)?;
+                }
+                expr.type_of()
+            }
+            None => Type::Unit,
+        };
+        vars.return_fn(self.span(), ty)?;
+
+        self.ty = Some(Type::Never);
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Display for Return<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.expr.as_ref() {
+            Some(expr) => write!(f, "return {expr}"),
+            None => write!(f, "return"),
+        }
+    }
+}
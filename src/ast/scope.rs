@@ -1,4 +1,4 @@
-use super::{Ast, Result, Rule, Statement, Type, TypeOf, VisibleVars};
+use super::{Ast, Result, Rule, Statement, StatementInternal, Type, TypeOf, VisibleVars};
 use crate::ast::match_rule;
 use pest::{iterators::Pair, Span};
 use std::fmt::Display;
@@ -8,6 +8,8 @@ use std::fmt::Display;
 #[derive(Debug, Clone, PartialEq)]
 pub struct Scope<'i> {
     pub statements: Vec<Statement<'i>>,
+    /// `{ a; b; }` evaluates to `()`, not to `b`
+    pub trailing_semi: bool,
 
     /* alloc: HashMap<String, Type>, */
     span: Span<'i>,
@@ -26,8 +28,14 @@ impl<'i> Ast<'i> for Scope<'i> {
         match_rule(&span, token.as_rule(), Rule::scope)?;
 
         // all statements within this scope
-        let statements: Vec<Statement> =
-            token.into_inner().map(Ast::parse).collect::<Result<_>>()?;
+        let mut statements = vec![];
+        let mut trailing_semi = false;
+        for token in token.into_inner() {
+            match token.as_rule() {
+                Rule::trailing_semi => trailing_semi = true,
+                _ => statements.push(Statement::parse(token)?),
+            }
+        }
 
         /* // assign statements are the only statements to allocate
         let alloc = statements
@@ -42,6 +50,7 @@ impl<'i> Ast<'i> for Scope<'i> {
 
         Ok(Scope {
             statements,
+            trailing_semi,
             /* alloc, */
             span,
             ty: None,
@@ -57,18 +66,20 @@ impl<'i> TypeOf<'i> for Scope<'i> {
             .try_for_each(|stmt| stmt.type_check(vars))?;
         vars.pop();
 
-        let ty = self
-            .statements
-            .last()
-            .as_ref()
-            .map_or(Type::Unit, |stmt| stmt.type_of());
+        let ty = match self.statements.last().map(|stmt| stmt.type_of()) {
+            // code after the last statement is unreachable
+            Some(Type::Never) => Type::Never,
+            Some(_) if self.trailing_semi => Type::Unit,
+            Some(ty) => ty,
+            None => Type::Unit,
+        };
         self.ty = Some(ty);
 
         Ok(())
     }
 
     fn type_of_impl(&self) -> Option<Type> {
-        self.ty
+        self.ty.clone()
     }
 }
 
@@ -83,10 +94,35 @@ impl<'i> Display for Scope<'i> {
 }
 
 impl<'i> Scope<'i> {
+    /// gives a lambda the scope evaluates to the param types of `ty`, see [`Expr::infer_params`]
+    pub fn infer_params(&mut self, ty: &Type) {
+        if let Some(StatementInternal::Expr(expr)) = self
+            .statements
+            .last_mut()
+            .map(|stmt| stmt.internal.as_mut())
+        {
+            expr.infer_params(ty);
+        }
+    }
+
+    /// gives the number literals the scope evaluates to the number type `ty`,
+    /// see [`Expr::infer_lit`]
+    pub fn infer_lit(&mut self, ty: &Type) -> Result<()> {
+        if self.trailing_semi {
+            return Ok(());
+        }
+        if let Some(stmt) = self.statements.last_mut() {
+            stmt.infer_lit(ty)?;
+            self.ty = Some(stmt.type_of());
+        }
+        Ok(())
+    }
+
     pub fn global(statements: Vec<Statement<'i>>, span: Span<'i>) -> Self {
         Self {
             span,
             statements,
+            trailing_semi: false,
             ty: None,
         }
     }
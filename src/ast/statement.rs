@@ -1,4 +1,4 @@
-use super::{match_rule, Assign, Ast, Expr, Result, Rule, Type, TypeOf, VisibleVars};
+use super::{match_rule, Assign, Ast, Expr, Reassign, Result, Rule, Type, TypeOf, VisibleVars};
 use pest::{iterators::Pair, Span};
 use std::fmt::Display;
 
@@ -8,6 +8,7 @@ use std::fmt::Display;
 pub enum StatementInternal<'i> {
     Expr(Expr<'i>),
     Assign(Assign<'i>),
+    Reassign(Reassign<'i>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -34,6 +35,7 @@ impl<'i> Ast<'i> for Statement<'i> {
         let internal = Box::new(match token.as_rule() {
             Rule::expr => StatementInternal::Expr(Ast::parse(token)?),
             Rule::assign => StatementInternal::Assign(Ast::parse(token)?),
+            Rule::reassign => StatementInternal::Reassign(Ast::parse(token)?),
             _ => unreachable!("{:?}", token),
         });
         assert_eq!(tokens.next(), None);
@@ -46,11 +48,34 @@ impl<'i> Ast<'i> for Statement<'i> {
     }
 }
 
+impl<'i> From<Expr<'i>> for Statement<'i> {
+    fn from(expr: Expr<'i>) -> Self {
+        Statement {
+            span: expr.span(),
+            internal: Box::new(StatementInternal::Expr(expr)),
+            ty: None,
+        }
+    }
+}
+
+impl<'i> Statement<'i> {
+    /// gives the number literals an expression statement evaluates to
+    /// the number type `ty`, see [`Expr::infer_lit`]
+    pub fn infer_lit(&mut self, ty: &Type) -> Result<()> {
+        if let StatementInternal::Expr(expr) = self.internal.as_mut() {
+            expr.infer_lit(ty)?;
+            self.ty = Some(expr.type_of());
+        }
+        Ok(())
+    }
+}
+
 impl<'i> TypeOf<'i> for Statement<'i> {
     fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
         let internal = match self.internal.as_mut() {
             StatementInternal::Expr(expr) => expr as &mut dyn TypeOf,
             StatementInternal::Assign(assign) => assign as _,
+            StatementInternal::Reassign(reassign) => reassign as _,
         };
 
         internal.type_check(vars)?;
@@ -60,7 +85,7 @@ impl<'i> TypeOf<'i> for Statement<'i> {
     }
 
     fn type_of_impl(&self) -> Option<Type> {
-        self.ty
+        self.ty.clone()
     }
 }
 
@@ -69,6 +94,7 @@ impl<'i> Display for Statement<'i> {
         match self.internal.as_ref() {
             StatementInternal::Expr(v) => v as &dyn Display,
             StatementInternal::Assign(v) => v as _,
+            StatementInternal::Reassign(v) => v as _,
         }
         .fmt(f)
     }
@@ -0,0 +1,157 @@
+use super::{
+    match_rule, Ast, Error, Expr, FnTyGen, Function, FunctionGen, FunctionGenInternal, Ident,
+    Result, Rule, Scope, Statement, Type, TypeOf, VisibleVars,
+};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+/// `static name: T = expr;` or `static mut name = expr;`,
+/// a variable every function of the script sees
+///
+/// the initializer is the function [`Static::init_name`],
+/// it runs once when the module is compiled
+#[derive(Debug, Clone, PartialEq)]
+pub struct Static<'i> {
+    pub name: Ident<'i>,
+    pub mutable: bool,
+    pub type_decl: Option<Type>,
+    pub expr: Expr<'i>,
+    /// the index of the file the static is declared in, see [`super::FunctionGenInternal::file`]
+    pub file: usize,
+
+    span: Span<'i>,
+    type_span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> Static<'i> {
+    /// the function that computes the initial value of the static `name`
+    pub fn init_name(name: &str) -> String {
+        format!("{name}::__init")
+    }
+
+    /// the initializer as a function without params, returning the declared type
+    pub fn init(&self) -> FunctionGen<'i> {
+        let ty = self.type_decl.clone().unwrap_or(Type::Unresolved);
+        let statements = vec![Statement::from(self.expr.clone())];
+        FunctionGen::new(
+            FunctionGenInternal {
+                name: Ident::from(Self::init_name(&self.name.value), self.name.span()),
+                generics: vec![],
+                params: vec![],
+                fn_ty: FnTyGen {
+                    span: self.type_span.clone(),
+                    ty,
+                },
+                scope: Scope::global(statements, self.expr.span()),
+                self_ty: None,
+                file: self.file,
+            },
+            self.span(),
+        )
+    }
+}
+
+/// strings and closure environments only live as long as the call that made them,
+/// so statics can't hold them
+fn outlives_calls(vars: &VisibleVars, ty: &Type) -> bool {
+    let fields: Vec<&Type> = match ty {
+        Type::Str | Type::Fn(..) => return false,
+        Type::Named(name) => match (vars.structs.get(name), vars.enums.get(name)) {
+            (Some(s), _) => s.fields.iter().map(|field| &field.ty).collect(),
+            (_, Some(e)) => e.variants.iter().flat_map(|v| v.fields.iter()).collect(),
+            _ => vec![],
+        },
+        Type::Tuple(tys) => tys.iter().collect(),
+        Type::Array(ty, _) => vec![ty.as_ref()],
+        _ => vec![],
+    };
+    fields.into_iter().all(|ty| outlives_calls(vars, ty))
+}
+
+impl<'i> Ast<'i> for Static<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::static_decl)?;
+        let mut tokens = token.into_inner();
+
+        let mutable = match tokens.peek().map(|token| token.as_rule()) {
+            Some(Rule::mutable) => {
+                tokens.next();
+                true
+            }
+            _ => false,
+        };
+        let name = Ident::parse(tokens.next().unwrap())?;
+        let (type_decl, type_span) = match tokens.peek().map(|token| token.as_rule()) {
+            Some(Rule::ty) => {
+                let token = tokens.next().unwrap();
+                let type_span = token.as_span();
+                (Some(Type::parse(token)?), type_span)
+            }
+            // `Type::Unresolved` leaves the type to the initializer, see [`FnTyGen::declared`]
+            _ => (None, name.span()),
+        };
+        let expr = Expr::parse(tokens.next().unwrap())?;
+
+        Ok(Self {
+            name,
+            mutable,
+            type_decl,
+            expr,
+            file: 0,
+
+            span,
+            type_span,
+            ty: None,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for Static<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        // the initializer is instantiated like a call to it
+        let init = Self::init_name(&self.name.value);
+        let ty = if vars.has_fn(&init, &[]) {
+            vars.get_fn_ty(self.name.span(), &init, &[])?
+        } else {
+            let f = Function::new(vars, self.name.span(), &init, &[])?;
+            let ty = f.type_of();
+            vars.push_fn(&init, &[], f);
+            ty
+        };
+        if !outlives_calls(vars, &ty) {
+            return Err(Error::new_static_ty(self.span(), &self.name.value, &ty));
+        }
+        vars.push_static(&self.name.value, ty.clone(), self.mutable);
+        self.ty = Some(ty);
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Display for Static<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "static ")?;
+        if self.mutable {
+            write!(f, "mut ")?;
+        }
+        write!(f, "{}", self.name)?;
+        if let Some(ty) = &self.type_decl {
+            write!(f, ": {ty}")?;
+        }
+        write!(f, " = {};", self.expr)
+    }
+}
@@ -0,0 +1,96 @@
+use super::{match_rule, Ast, Error, Ident, Result, Rule, Type, TypeOf, VisibleVars};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Struct<'i> {
+    pub name: Ident<'i>,
+    pub fields: Vec<StructField<'i>>,
+    /// the index of the file the struct is declared in, see [`super::FunctionGenInternal::file`]
+    pub file: usize,
+
+    span: Span<'i>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructField<'i> {
+    pub name: Ident<'i>,
+    pub ty: Type,
+}
+
+//
+
+impl<'i> Struct<'i> {
+    /// index and type of the field called `name`
+    pub fn field(&self, name: &str) -> Option<(usize, &Type)> {
+        self.fields
+            .iter()
+            .enumerate()
+            .find(|(_, field)| field.name.value == name)
+            .map(|(i, field)| (i, &field.ty))
+    }
+}
+
+impl<'i> Ast<'i> for Struct<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::struct_decl)?;
+        let mut tokens = token.into_inner();
+
+        let name = Ident::parse(tokens.next().unwrap())?;
+        let fields = tokens
+            .map(|token| {
+                let mut tokens = token.into_inner();
+                let name = Ident::parse(tokens.next().unwrap())?;
+                let ty = Type::parse(tokens.next().unwrap())?;
+                Ok(StructField { name, ty })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            name,
+            fields,
+            file: 0,
+            span,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for Struct<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        for (i, field) in self.fields.iter().enumerate() {
+            let name = field.name.value.as_str();
+            if self.fields[..i]
+                .iter()
+                .any(|other| other.name.value == name)
+            {
+                return Err(Error::new_duplicate_field(field.name.span(), name));
+            }
+
+            vars.check_ty(field.name.span(), &field.ty)?;
+        }
+        vars.check_recursion(self.span(), &self.name.value)?;
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        Some(Type::Named(self.name.value.clone()))
+    }
+}
+
+impl<'i> Display for Struct<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "struct {} {{ ", self.name)?;
+        for field in self.fields.iter() {
+            write!(f, "{}: {}, ", field.name, field.ty)?;
+        }
+        write!(f, "}}")
+    }
+}
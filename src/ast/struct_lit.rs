@@ -0,0 +1,120 @@
+use super::{match_rule, Ast, Error, Expr, Ident, Result, Rule, Type, TypeOf, VisibleVars};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructLit<'i> {
+    pub name: Ident<'i>,
+    pub fields: Vec<FieldInit<'i>>,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldInit<'i> {
+    pub name: Ident<'i>,
+    pub expr: Expr<'i>,
+
+    /// index of the field in the struct declaration,
+    /// fields are initialized in the order they are written
+    pub index: usize,
+}
+
+//
+
+impl<'i> Ast<'i> for StructLit<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::struct_lit)?;
+        let mut tokens = token.into_inner();
+
+        let name = Ident::parse(tokens.next().unwrap())?;
+        let fields = tokens
+            .map(|token| {
+                let mut tokens = token.into_inner();
+                let name = Ident::parse(tokens.next().unwrap())?;
+                let expr = Expr::parse(tokens.next().unwrap())?;
+                Ok(FieldInit {
+                    name,
+                    expr,
+                    index: 0,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            name,
+            fields,
+
+            span,
+            ty: None,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for StructLit<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        self.name.value = vars.resolve_name(&self.name.value);
+        let ty = Type::Named(self.name.value.clone());
+        let decl = vars.get_struct(self.name.span(), &self.name.value)?.clone();
+
+        for i in 0..self.fields.len() {
+            let (before, after) = self.fields.split_at_mut(i);
+            let field = &mut after[0];
+            let name = field.name.value.as_str();
+            if before.iter().any(|other| other.name.value == name) {
+                return Err(Error::new_duplicate_field(field.name.span(), name));
+            }
+
+            let (index, expect) = match decl.field(name) {
+                Some(field) => field,
+                None => return Err(Error::new_field_not_found(field.name.span(), &ty, name)),
+            };
+            field.index = index;
+
+            field.expr.type_check(vars)?;
+            field.expr.infer_lit(expect)?;
+            let got = field.expr.type_of();
+            if *expect != got && got != Type::Never {
+                return Err(Error::new_type_mismatch(field.expr.span(), expect, &got));
+            }
+        }
+
+        if let Some(missing) = decl.fields.iter().find(|field| {
+            self.fields
+                .iter()
+                .all(|init| init.name.value != field.name.value)
+        }) {
+            return Err(Error::new_missing_field(
+                self.span(),
+                &ty,
+                &missing.name.value,
+            ));
+        }
+
+        self.ty = Some(ty);
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Display for StructLit<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {{ ", self.name)?;
+        for field in self.fields.iter() {
+            write!(f, "{}: {}, ", field.name, field.expr)?;
+        }
+        write!(f, "}}")
+    }
+}
@@ -1,5 +1,9 @@
-use super::{Access, Ast, Branch, Call, Expr, Result, Rule, Type, TypeOf, VisibleVars};
-use crate::ast::{match_rule, Lit};
+use super::{
+    Access, ArrayLit, Ast, Branch, Break, Call, Continue, EnumLit, Expr, FieldAccess, For, Ident,
+    Index, Lambda, Loop, Match, Result, Return, Rule, StructLit, TupleLit, Type, TypeOf,
+    VisibleVars, While,
+};
+use crate::ast::{match_rule, Error, Lit};
 use pest::{iterators::Pair, Span};
 use std::fmt::{Debug, Display};
 
@@ -10,8 +14,22 @@ pub enum TermInternal<'i> {
     Lit(Lit),
     Expr(Expr<'i>),
     Branch(Branch<'i>),
+    Match(Match<'i>),
     Access(Access<'i>),
     Call(Call<'i>),
+    While(While<'i>),
+    Loop(Loop<'i>),
+    For(For<'i>),
+    Break(Break<'i>),
+    Continue(Continue<'i>),
+    Return(Return<'i>),
+    Lambda(Lambda<'i>),
+    StructLit(StructLit<'i>),
+    EnumLit(EnumLit<'i>),
+    TupleLit(TupleLit<'i>),
+    ArrayLit(ArrayLit<'i>),
+    FieldAccess(FieldAccess<'i>),
+    Index(Index<'i>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -35,22 +53,150 @@ impl<'i> Ast<'i> for Term<'i> {
         let mut tokens = token.into_inner();
 
         let token = tokens.next().unwrap();
+        let token_span = span.start_pos().span(&token.as_span().end_pos());
         let internal = Box::new(match token.as_rule() {
-            Rule::int => TermInternal::Lit(Lit::I64(token.as_str().parse().unwrap())),
-            Rule::float => TermInternal::Lit(Lit::F64(token.as_str().parse().unwrap())),
+            Rule::int => TermInternal::Lit(Lit::parse_int(token)?),
+            Rule::float => TermInternal::Lit(Lit::parse_float(token)?),
             Rule::bool => TermInternal::Lit(Lit::Bool(token.as_str().parse().unwrap())),
+            Rule::string => TermInternal::Lit(Lit::parse_str(token)?),
+            Rule::unit => TermInternal::Lit(Lit::Unit(())),
             Rule::expr => TermInternal::Expr(Ast::parse(token)?),
             Rule::branch => TermInternal::Branch(Ast::parse(token)?),
+            Rule::match_expr => TermInternal::Match(Ast::parse(token)?),
             Rule::access => TermInternal::Access(Ast::parse(token)?),
             Rule::call => TermInternal::Call(Ast::parse(token)?),
+            Rule::while_loop => TermInternal::While(Ast::parse(token)?),
+            Rule::inf_loop => TermInternal::Loop(Ast::parse(token)?),
+            Rule::for_loop => TermInternal::For(Ast::parse(token)?),
+            Rule::break_expr => TermInternal::Break(Ast::parse(token)?),
+            Rule::continue_expr => TermInternal::Continue(Ast::parse(token)?),
+            Rule::return_expr => TermInternal::Return(Ast::parse(token)?),
+            Rule::lambda => TermInternal::Lambda(Ast::parse(token)?),
+            Rule::struct_lit => TermInternal::StructLit(Ast::parse(token)?),
+            Rule::enum_lit => TermInternal::EnumLit(Ast::parse(token)?),
+            Rule::tuple_lit => TermInternal::TupleLit(Ast::parse(token)?),
+            Rule::array_lit => TermInternal::ArrayLit(Ast::parse(token)?),
             other => unreachable!("{:?}", other),
         });
-
-        Ok(Term {
+        let mut term = Term {
             internal,
-            span,
+            span: token_span,
+            ty: None,
+        };
+
+        // `a.b[c]` is `(a.b)[c]`
+        for token in tokens {
+            let span = span.start_pos().span(&token.as_span().end_pos());
+            let internal = match token.as_rule() {
+                Rule::method => {
+                    let mut tokens = token.into_inner();
+                    let name = Ident::parse(tokens.next().unwrap())?;
+                    let args = tokens
+                        .map(|token| Ast::parse_single(token.into_inner()))
+                        .collect::<Result<_>>()?;
+                    TermInternal::Call(Call::new_method(span.clone(), term, name, args))
+                }
+                Rule::index => {
+                    let index = Expr::parse_single(token.into_inner())?;
+                    TermInternal::Index(Index::new(span.clone(), term, index))
+                }
+                _ => {
+                    // `.0` indexes tuples, but is otherwise accessed like a field
+                    let token = token.into_inner().next().unwrap();
+                    let field = Ident::from(token.as_str().into(), token.as_span());
+                    TermInternal::FieldAccess(FieldAccess::new(span.clone(), term, field))
+                }
+            };
+            term = Term {
+                internal: Box::new(internal),
+                span,
+                ty: None,
+            };
+        }
+
+        Ok(term)
+    }
+}
+
+impl<'i> Term<'i> {
+    /// gives an integer literal without a suffix the integer type `ty`,
+    /// like the `1` in `x + 1` where `x: u8`, and a float literal
+    /// without a suffix the float type, like the `2.0` in `x * 2.0` where `x: f32`
+    ///
+    /// goes into the values of branches, matches and arrays, like the `1` in
+    /// `if c { x } else { 1 }`, does nothing for other terms,
+    /// the following type checks report mismatches
+    pub fn infer_lit(&mut self, ty: &Type) -> Result<()> {
+        let span = self.span.clone();
+        let inferred = match self.internal.as_mut() {
+            TermInternal::Lit(lit @ Lit::I64(_))
+                if ty.is_int() && Lit::is_unsuffixed_int(span.as_str()) =>
+            {
+                *lit = lit
+                    .to_int_type(ty)
+                    .ok_or_else(|| Error::new_lit_out_of_range(span, &ty.to_string()))?;
+                lit.type_of()
+            }
+            TermInternal::Lit(lit @ Lit::F64(_))
+                if ty.is_float() && Lit::is_unsuffixed_float(span.as_str()) =>
+            {
+                *lit = lit.to_float_type(ty).unwrap();
+                lit.type_of()
+            }
+            TermInternal::Expr(expr) => {
+                expr.infer_lit(ty)?;
+                expr.type_of()
+            }
+            TermInternal::Branch(branch) => {
+                branch.infer_lit(ty)?;
+                branch.type_of()
+            }
+            TermInternal::Match(m) => {
+                m.infer_lit(ty)?;
+                m.type_of()
+            }
+            TermInternal::ArrayLit(array) => {
+                array.infer_lit(ty)?;
+                array.type_of()
+            }
+            _ => return Ok(()),
+        };
+        self.ty = Some(inferred);
+        Ok(())
+    }
+}
+
+impl<'i> Term<'i> {
+    /// gives a lambda without param type annotations the param types
+    /// of the fn type `ty`, see [`Lambda::infer_params`]
+    ///
+    /// unlike [`Self::infer_lit`], this has to happen before the type check
+    pub fn infer_params(&mut self, ty: &Type) {
+        match self.internal.as_mut() {
+            TermInternal::Lambda(lambda) => lambda.infer_params(ty),
+            TermInternal::Expr(expr) => expr.infer_params(ty),
+            _ => {}
+        }
+    }
+}
+
+impl<'i> From<Access<'i>> for Term<'i> {
+    fn from(access: Access<'i>) -> Self {
+        Term {
+            span: access.span(),
+            internal: Box::new(TermInternal::Access(access)),
             ty: None,
-        })
+        }
+    }
+}
+
+impl<'i> From<Branch<'i>> for Term<'i> {
+    fn from(branch: Branch<'i>) -> Self {
+        Term {
+            span: branch.span(),
+            internal: Box::new(TermInternal::Branch(branch)),
+            ty: None,
+        }
     }
 }
 
@@ -60,8 +206,22 @@ impl<'i> TypeOf<'i> for Term<'i> {
             TermInternal::Lit(v) => v as &mut dyn TypeOf<'i>,
             TermInternal::Expr(v) => v as _,
             TermInternal::Branch(v) => v as _,
+            TermInternal::Match(v) => v as _,
             TermInternal::Access(v) => v as _,
             TermInternal::Call(v) => v as _,
+            TermInternal::While(v) => v as _,
+            TermInternal::Loop(v) => v as _,
+            TermInternal::For(v) => v as _,
+            TermInternal::Break(v) => v as _,
+            TermInternal::Continue(v) => v as _,
+            TermInternal::Return(v) => v as _,
+            TermInternal::Lambda(v) => v as _,
+            TermInternal::StructLit(v) => v as _,
+            TermInternal::EnumLit(v) => v as _,
+            TermInternal::TupleLit(v) => v as _,
+            TermInternal::ArrayLit(v) => v as _,
+            TermInternal::FieldAccess(v) => v as _,
+            TermInternal::Index(v) => v as _,
         };
 
         internal.type_check(vars)?;
@@ -71,7 +231,7 @@ impl<'i> TypeOf<'i> for Term<'i> {
     }
 
     fn type_of_impl(&self) -> Option<Type> {
-        self.ty
+        self.ty.clone()
     }
 }
 
@@ -81,8 +241,22 @@ impl<'i> Display for Term<'i> {
             TermInternal::Lit(v) => v as &dyn Display,
             TermInternal::Expr(v) => v as _,
             TermInternal::Branch(v) => v as _,
+            TermInternal::Match(v) => v as _,
             TermInternal::Access(v) => v as _,
             TermInternal::Call(v) => v as _,
+            TermInternal::While(v) => v as _,
+            TermInternal::Loop(v) => v as _,
+            TermInternal::For(v) => v as _,
+            TermInternal::Break(v) => v as _,
+            TermInternal::Continue(v) => v as _,
+            TermInternal::Return(v) => v as _,
+            TermInternal::Lambda(v) => v as _,
+            TermInternal::StructLit(v) => v as _,
+            TermInternal::EnumLit(v) => v as _,
+            TermInternal::TupleLit(v) => v as _,
+            TermInternal::ArrayLit(v) => v as _,
+            TermInternal::FieldAccess(v) => v as _,
+            TermInternal::Index(v) => v as _,
         }
         .fmt(f)
     }
@@ -0,0 +1,139 @@
+use super::{
+    match_rule, Ast, Error, FnTyGen, Ident, ParamGen, Result, Rule, Scope, Type, TypeOf,
+    VisibleVars,
+};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trait<'i> {
+    pub name: Ident<'i>,
+    pub methods: Vec<TraitFn<'i>>,
+    /// the index of the file the trait is declared in, see [`super::FunctionGenInternal::file`]
+    pub file: usize,
+
+    span: Span<'i>,
+}
+
+/// a method every type that implements the trait has,
+/// methods with a default body don't have to be implemented
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraitFn<'i> {
+    pub name: Ident<'i>,
+    pub params: Vec<ParamGen<'i>>,
+    pub fn_ty: FnTyGen<'i>,
+    pub default: Option<Scope<'i>>,
+
+    span: Span<'i>,
+}
+
+//
+
+impl<'i> Trait<'i> {
+    pub fn method(&self, name: &str) -> Option<&TraitFn<'i>> {
+        self.methods.iter().find(|method| method.name.value == name)
+    }
+}
+
+impl<'i> TraitFn<'i> {
+    pub fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+}
+
+/// methods are called on a value, so the first parameter has to be `self`
+pub(super) fn check_self_param(name: &Ident, params: &[ParamGen]) -> Result<()> {
+    match params.first() {
+        Some(param) if param.ident.value == "self" => Ok(()),
+        _ => Err(Error::new_missing_self(name.span(), &name.value)),
+    }
+}
+
+impl<'i> Ast<'i> for Trait<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::trait_decl)?;
+        let mut tokens = token.into_inner();
+
+        let name = Ident::parse(tokens.next().unwrap())?;
+        let methods = tokens
+            .map(|token| {
+                let span = token.as_span();
+                let mut tokens = token.into_inner();
+
+                let name = Ident::parse(tokens.next().unwrap())?;
+                let mut params = vec![];
+                while let Some(Rule::param) = tokens.peek().map(|token| token.as_rule()) {
+                    params.push(ParamGen::parse(tokens.next().unwrap())?);
+                }
+                let fn_ty = FnTyGen::parse(tokens.next().unwrap())?;
+                let default = tokens.next().map(Scope::parse).transpose()?;
+
+                Ok(TraitFn {
+                    name,
+                    params,
+                    fn_ty,
+                    default,
+
+                    span,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            name,
+            methods,
+            file: 0,
+            span,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for Trait<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        for (i, method) in self.methods.iter().enumerate() {
+            let name = method.name.value.as_str();
+            if self.methods[..i]
+                .iter()
+                .any(|other| other.name.value == name)
+            {
+                return Err(Error::new_method_redefined(
+                    method.name.span(),
+                    name,
+                    &self.name.value,
+                ));
+            }
+            check_self_param(&method.name, &method.params)?;
+
+            // types that refer to `Self` are checked in every impl
+            let tys = method.params.iter().map(|param| &param.ty);
+            for ty in tys.chain(std::iter::once(&method.fn_ty.ty)) {
+                if !ty.is_generic(&["Self"]) {
+                    vars.check_ty(method.span(), ty)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        Some(Type::Unresolved)
+    }
+}
+
+impl<'i> Display for Trait<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "trait {} {{ ", self.name)?;
+        for method in self.methods.iter() {
+            write!(f, "fn {}(); ", method.name)?;
+        }
+        write!(f, "}}")
+    }
+}
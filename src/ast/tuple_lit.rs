@@ -0,0 +1,62 @@
+use super::{match_rule, Ast, Expr, Result, Rule, Type, TypeOf, VisibleVars};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TupleLit<'i> {
+    pub fields: Vec<Expr<'i>>,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> Ast<'i> for TupleLit<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::tuple_lit)?;
+
+        let fields = token.into_inner().map(Expr::parse).collect::<Result<_>>()?;
+
+        Ok(Self {
+            fields,
+
+            span,
+            ty: None,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for TupleLit<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        for field in self.fields.iter_mut() {
+            field.type_check(vars)?;
+        }
+        self.ty = Some(Type::Tuple(
+            self.fields.iter().map(|field| field.type_of()).collect(),
+        ));
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Display for TupleLit<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(")?;
+        for field in self.fields.iter() {
+            write!(f, "{field}, ")?;
+        }
+        write!(f, ")")
+    }
+}
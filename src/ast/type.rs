@@ -1,8 +1,9 @@
-use super::{Ast, Result, Rule, TypeOf, VisibleVars};
+use super::{Ast, Error, Result, Rule, TypeOf, VisibleVars};
 use crate::ast::match_rule;
 use pest::{iterators::Pair, Span};
 use std::{
-    any::TypeId,
+    collections::HashMap,
+    convert::TryInto,
     fmt::{Debug, Display, Formatter},
     hash::Hash,
 };
@@ -11,12 +12,165 @@ use std::{
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Lit {
+    F32(f32),
     F64(f64),
+    I8(i8),
+    I16(i16),
+    I32(i32),
     I64(i64),
+    Isize(isize),
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    U64(u64),
+    Usize(usize),
     Bool(bool),
+    Str(String),
     Unit(()),
 }
 
+impl Lit {
+    /// parses an integer literal, `i64` unless it has a suffix like `5u8`,
+    /// see [`super::Term::infer_lit`] for literals without one
+    pub fn parse_int(token: Pair<Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::int)?;
+        let mut tokens = token.into_inner();
+
+        let digits = tokens.next().unwrap().as_str();
+        let suffix = tokens.next().map(|token| token.as_str());
+        let suffix = suffix.unwrap_or("i64");
+        let out_of_range = || Error::new_lit_out_of_range(span.clone(), suffix);
+
+        Ok(match suffix {
+            "i8" => Lit::I8(digits.parse().map_err(|_| out_of_range())?),
+            "i16" => Lit::I16(digits.parse().map_err(|_| out_of_range())?),
+            "i32" => Lit::I32(digits.parse().map_err(|_| out_of_range())?),
+            "i64" => Lit::I64(digits.parse().map_err(|_| out_of_range())?),
+            "isize" => Lit::Isize(digits.parse().map_err(|_| out_of_range())?),
+            "u8" => Lit::U8(digits.parse().map_err(|_| out_of_range())?),
+            "u16" => Lit::U16(digits.parse().map_err(|_| out_of_range())?),
+            "u32" => Lit::U32(digits.parse().map_err(|_| out_of_range())?),
+            "u64" => Lit::U64(digits.parse().map_err(|_| out_of_range())?),
+            "usize" => Lit::Usize(digits.parse().map_err(|_| out_of_range())?),
+            other => unreachable!("{:?}", other),
+        })
+    }
+
+    /// parses a float literal, `f64` unless it has a suffix like `1.5f32`
+    pub fn parse_float(token: Pair<Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::float)?;
+        let mut tokens = token.into_inner();
+
+        // floats never fail to parse, too large values become infinite
+        let digits = tokens.next().unwrap().as_str();
+        Ok(match tokens.next().map(|token| token.as_str()) {
+            Some("f32") => Lit::F32(digits.parse().unwrap()),
+            _ => Lit::F64(digits.parse().unwrap()),
+        })
+    }
+
+    /// the value of an integer literal, `None` for other literals
+    pub fn as_int(&self) -> Option<i128> {
+        Some(match *self {
+            Lit::I8(v) => v as _,
+            Lit::I16(v) => v as _,
+            Lit::I32(v) => v as _,
+            Lit::I64(v) => v as _,
+            Lit::Isize(v) => v as _,
+            Lit::U8(v) => v as _,
+            Lit::U16(v) => v as _,
+            Lit::U32(v) => v as _,
+            Lit::U64(v) => v as _,
+            Lit::Usize(v) => v as _,
+            _ => return None,
+        })
+    }
+
+    /// whether `src` is an integer literal without a suffix,
+    /// those are `i64` unless the context needs another integer type
+    pub fn is_unsuffixed_int(src: &str) -> bool {
+        let digits = src.trim_start_matches(['+', '-']);
+        !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+    }
+
+    /// whether `src` is a float literal without a suffix
+    pub fn is_unsuffixed_float(src: &str) -> bool {
+        let digits = src.trim_start_matches(['+', '-']);
+        digits.contains('.') && digits.bytes().all(|b| b.is_ascii_digit() || b == b'.')
+    }
+
+    /// the integer literal as a literal of the integer type `ty`, `None` if it doesn't fit
+    pub fn to_int_type(&self, ty: &Type) -> Option<Self> {
+        let v = self.as_int()?;
+        Some(match ty {
+            Type::I8 => Lit::I8(v.try_into().ok()?),
+            Type::I16 => Lit::I16(v.try_into().ok()?),
+            Type::I32 => Lit::I32(v.try_into().ok()?),
+            Type::I64 => Lit::I64(v.try_into().ok()?),
+            Type::Isize => Lit::Isize(v.try_into().ok()?),
+            Type::U8 => Lit::U8(v.try_into().ok()?),
+            Type::U16 => Lit::U16(v.try_into().ok()?),
+            Type::U32 => Lit::U32(v.try_into().ok()?),
+            Type::U64 => Lit::U64(v.try_into().ok()?),
+            Type::Usize => Lit::Usize(v.try_into().ok()?),
+            _ => return None,
+        })
+    }
+
+    /// the float literal as a literal of the float type `ty`,
+    /// too large values become infinite like when parsing them
+    pub fn to_float_type(&self, ty: &Type) -> Option<Self> {
+        let v = match *self {
+            Lit::F32(v) => v as f64,
+            Lit::F64(v) => v,
+            _ => return None,
+        };
+        Some(match ty {
+            Type::F32 => Lit::F32(v as f32),
+            Type::F64 => Lit::F64(v),
+            _ => return None,
+        })
+    }
+
+    /// parses a string literal, replacing its escape sequences
+    pub fn parse_str(token: Pair<Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::string)?;
+
+        let mut value = String::new();
+        for token in token.into_inner() {
+            let escape = match token.as_rule() {
+                Rule::escape => token.as_str(),
+                _ => {
+                    value.push_str(token.as_str());
+                    continue;
+                }
+            };
+
+            let c = match &escape[1..] {
+                "n" => '\n',
+                "r" => '\r',
+                "t" => '\t',
+                "0" => '\0',
+                unicode if unicode.starts_with('u') => {
+                    let hex = &unicode[2..unicode.len() - 1];
+                    u32::from_str_radix(hex, 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| Error::new_invalid_escape(token.as_span(), escape))?
+                }
+                // `\\`, `\"` and `\'`
+                other => other.chars().next().unwrap(),
+            };
+            value.push(c);
+        }
+
+        Ok(Lit::Str(value))
+    }
+}
+
 impl<'i> TypeOf<'i> for Lit {
     fn type_check_impl(&mut self, _: &mut VisibleVars) -> Result<()> {
         Ok(())
@@ -24,9 +178,20 @@ impl<'i> TypeOf<'i> for Lit {
 
     fn type_of(&self) -> Type {
         match self {
-            Lit::I64(_) => Type::I64,
+            Lit::F32(_) => Type::F32,
             Lit::F64(_) => Type::F64,
+            Lit::I8(_) => Type::I8,
+            Lit::I16(_) => Type::I16,
+            Lit::I32(_) => Type::I32,
+            Lit::I64(_) => Type::I64,
+            Lit::Isize(_) => Type::Isize,
+            Lit::U8(_) => Type::U8,
+            Lit::U16(_) => Type::U16,
+            Lit::U32(_) => Type::U32,
+            Lit::U64(_) => Type::U64,
+            Lit::Usize(_) => Type::Usize,
             Lit::Bool(_) => Type::Bool,
+            Lit::Str(_) => Type::Str,
             Lit::Unit(_) => Type::Unit,
         }
     }
@@ -36,23 +201,75 @@ impl<'i> TypeOf<'i> for Lit {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Type {
+    /// `f32`
+    F32,
+
     /// `f64`
     F64,
 
+    /// `i8`
+    I8,
+
+    /// `i16`
+    I16,
+
+    /// `i32`
+    I32,
+
     /// `i64`
     I64,
 
+    /// `isize`
+    /// as wide as a pointer of the host
+    Isize,
+
+    /// `u8`
+    U8,
+
+    /// `u16`
+    U16,
+
+    /// `u32`
+    U32,
+
     /// `u64`
     U64,
 
+    /// `usize`
+    /// as wide as a pointer of the host
+    Usize,
+
     /// `bool`
     Bool,
 
+    /// `str`
+    /// an immutable utf-8 string, passed around as a pointer and a length
+    Str,
+
     /// `()`
     Unit,
 
+    /// `!`
+    /// the type of expressions that never produce a value, like `break`
+    Never,
+
+    /// `Name`
+    /// a user defined struct or enum, the declaration is looked up by name
+    Named(String),
+
+    /// `[T; N]`
+    Array(Box<Type>, usize),
+
+    /// `(T, U)`
+    /// values are stored inline, like an anonymous struct
+    Tuple(Vec<Type>),
+
+    /// `fn(T, U) -> R`
+    /// a function or closure value, a function pointer and the captured environment
+    Fn(Vec<Type>, Box<Type>),
+
     /// `unresolved type`
     Unresolved,
 }
@@ -60,14 +277,137 @@ pub enum Type {
 //
 
 impl Type {
-    pub fn matches<T: 'static>(self) -> bool {
+    pub fn is_float(&self) -> bool {
+        matches!(self, Type::F32 | Type::F64)
+    }
+
+    pub fn is_int(&self) -> bool {
+        self.is_signed() || self.is_unsigned()
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self,
+            Type::I8 | Type::I16 | Type::I32 | Type::I64 | Type::Isize
+        )
+    }
+
+    pub fn is_unsigned(&self) -> bool {
+        matches!(
+            self,
+            Type::U8 | Type::U16 | Type::U32 | Type::U64 | Type::Usize
+        )
+    }
+
+    pub fn is_number(&self) -> bool {
+        self.is_int() || self.is_float()
+    }
+
+    /// whether number literals without a suffix have the type, unless another one is expected
+    pub fn is_lit_default(&self) -> bool {
+        matches!(self, Type::I64 | Type::F64)
+    }
+
+    /// whether the type still contains `?` or one of the type parameters `generics`
+    pub fn is_generic(&self, generics: &[&str]) -> bool {
+        match self {
+            Type::Unresolved => true,
+            Type::Named(name) => generics.contains(&name.as_str()),
+            Type::Array(ty, _) => ty.is_generic(generics),
+            Type::Tuple(tys) => tys.iter().any(|ty| ty.is_generic(generics)),
+            Type::Fn(params, ret) => {
+                params.iter().any(|ty| ty.is_generic(generics)) || ret.is_generic(generics)
+            }
+            _ => false,
+        }
+    }
+
+    /// matches the declared type `self` against the type `got` of an argument,
+    /// the type parameters `generics` in it are bound to the types they stand for
+    ///
+    /// false if `got` doesn't fit or a type parameter was already bound to another type
+    pub fn bind_generics(
+        &self,
+        got: &Type,
+        generics: &[&str],
+        bindings: &mut HashMap<String, Type>,
+    ) -> bool {
+        match (self, got) {
+            (Type::Unresolved, _) => true,
+            (Type::Named(name), got) if generics.contains(&name.as_str()) => {
+                match bindings.get(name) {
+                    Some(bound) => bound == got,
+                    None => {
+                        bindings.insert(name.clone(), got.clone());
+                        true
+                    }
+                }
+            }
+            (Type::Array(ty, len), Type::Array(got, got_len)) => {
+                len == got_len && ty.bind_generics(got, generics, bindings)
+            }
+            (Type::Tuple(tys), Type::Tuple(got)) => {
+                tys.len() == got.len()
+                    && tys
+                        .iter()
+                        .zip(got)
+                        .all(|(ty, got)| ty.bind_generics(got, generics, bindings))
+            }
+            (Type::Fn(params, ret), Type::Fn(got_params, got_ret)) => {
+                params.len() == got_params.len()
+                    && params
+                        .iter()
+                        .zip(got_params)
+                        .all(|(ty, got)| ty.bind_generics(got, generics, bindings))
+                    && ret.bind_generics(got_ret, generics, bindings)
+            }
+            (ty, got) => ty == got,
+        }
+    }
+
+    /// replaces the bound type parameters in the type
+    pub fn resolve_generics(&self, bindings: &HashMap<String, Type>) -> Type {
+        self.map_named(&|name| bindings.get(name).cloned())
+    }
+
+    /// replaces every named type `f` gives a type for, the others are kept
+    pub fn map_named(&self, f: &impl Fn(&str) -> Option<Type>) -> Type {
+        match self {
+            Type::Named(name) => f(name).unwrap_or_else(|| self.clone()),
+            Type::Array(ty, len) => Type::Array(Box::new(ty.map_named(f)), *len),
+            Type::Tuple(tys) => Type::Tuple(tys.iter().map(|ty| ty.map_named(f)).collect()),
+            Type::Fn(params, ret) => Type::Fn(
+                params.iter().map(|ty| ty.map_named(f)).collect(),
+                Box::new(ret.map_named(f)),
+            ),
+            ty => ty.clone(),
+        }
+    }
+
+    /// whether the type has the builtin bound `bound`, `None` if there is no such bound
+    ///
+    /// the builtin bounds are `Num`, `Int`, `Float`, `Signed`, `Eq` and `Ord`
+    pub fn has_builtin_bound(&self, bound: &str) -> Option<bool> {
+        Some(match bound {
+            "Num" => self.is_number(),
+            "Int" => self.is_int(),
+            "Float" => self.is_float(),
+            "Signed" => self.is_signed() || self.is_float(),
+            "Eq" => self.is_number() || matches!(self, Type::Bool | Type::Str),
+            "Ord" => self.is_number() || matches!(self, Type::Bool | Type::Str),
+            _ => return None,
+        })
+    }
+
+    /// width of a number in bits, `None` for other types
+    pub fn bits(&self) -> Option<u32> {
         match self {
-            Type::F64 => TypeId::of::<T>() == TypeId::of::<f64>(),
-            Type::I64 => TypeId::of::<T>() == TypeId::of::<i64>(),
-            Type::U64 => TypeId::of::<T>() == TypeId::of::<u64>(),
-            Type::Bool => TypeId::of::<T>() == TypeId::of::<bool>(),
-            Type::Unit => TypeId::of::<T>() == TypeId::of::<()>(),
-            Type::Unresolved => false,
+            Type::I8 | Type::U8 => Some(8),
+            Type::I16 | Type::U16 => Some(16),
+            Type::I32 | Type::U32 | Type::F32 => Some(32),
+            Type::I64 | Type::U64 | Type::F64 => Some(64),
+            Type::Isize | Type::Usize => Some(usize::BITS),
+            _ => None,
         }
     }
 }
@@ -86,10 +426,48 @@ impl<'i> Ast<'i> for Type {
         Ok(match token.as_rule() {
             Rule::unit_ty => Self::Unit,
             Rule::bool_ty => Self::Bool,
-            Rule::u_ty => Self::U64,
-            Rule::i_ty => Self::I64,
-            Rule::f_ty => Self::F64,
-            // Rule::gen => Self::Unresolved,
+            Rule::str_ty => Self::Str,
+            Rule::u_ty => match token.as_str() {
+                "u8" => Self::U8,
+                "u16" => Self::U16,
+                "u32" => Self::U32,
+                "u64" => Self::U64,
+                _ => Self::Usize,
+            },
+            Rule::i_ty => match token.as_str() {
+                "i8" => Self::I8,
+                "i16" => Self::I16,
+                "i32" => Self::I32,
+                "i64" => Self::I64,
+                _ => Self::Isize,
+            },
+            Rule::f_ty => match token.as_str() {
+                "f32" => Self::F32,
+                _ => Self::F64,
+            },
+            Rule::named_ty => Self::Named(token.as_str().into()),
+            Rule::array_ty => {
+                let mut tokens = token.into_inner();
+                let ty = Type::parse(tokens.next().unwrap())?;
+                let len = tokens.next().unwrap().as_str().parse().unwrap();
+                Self::Array(Box::new(ty), len)
+            }
+            Rule::tuple_ty => {
+                Self::Tuple(token.into_inner().map(Type::parse).collect::<Result<_>>()?)
+            }
+            Rule::func_ty => {
+                let mut params = vec![];
+                let mut ret = Type::Unit;
+                for token in token.into_inner() {
+                    match token.as_rule() {
+                        Rule::ty => params.push(Type::parse(token)?),
+                        _ => ret = Type::parse(token.into_inner().next().unwrap())?,
+                    }
+                }
+                Self::Fn(params, Box::new(ret))
+            }
+            // `?` is a type parameter without a name
+            Rule::gen_ty => Self::Unresolved,
             _ => unreachable!(),
         })
     }
@@ -98,9 +476,20 @@ impl<'i> Ast<'i> for Type {
 impl Display for Lit {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::F32(v) => return write!(f, "{v}f32"),
             Self::F64(v) => v as &dyn Display,
+            Self::I8(v) => return write!(f, "{v}i8"),
+            Self::I16(v) => return write!(f, "{v}i16"),
+            Self::I32(v) => return write!(f, "{v}i32"),
             Self::I64(v) => v as _,
+            Self::Isize(v) => return write!(f, "{v}isize"),
+            Self::U8(v) => return write!(f, "{v}u8"),
+            Self::U16(v) => return write!(f, "{v}u16"),
+            Self::U32(v) => return write!(f, "{v}u32"),
+            Self::U64(v) => return write!(f, "{v}u64"),
+            Self::Usize(v) => return write!(f, "{v}usize"),
             Self::Bool(v) => v as _,
+            Self::Str(v) => return write!(f, "{v:?}"),
             Self::Unit(_) => &"()",
         }
         .fmt(f)
@@ -110,11 +499,48 @@ impl Display for Lit {
 impl Display for Type {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::F32 => write!(f, "f32"),
             Self::F64 => write!(f, "f64"),
-            Self::U64 => write!(f, "u64"),
+            Self::I8 => write!(f, "i8"),
+            Self::I16 => write!(f, "i16"),
+            Self::I32 => write!(f, "i32"),
             Self::I64 => write!(f, "i64"),
+            Self::Isize => write!(f, "isize"),
+            Self::U8 => write!(f, "u8"),
+            Self::U16 => write!(f, "u16"),
+            Self::U32 => write!(f, "u32"),
+            Self::U64 => write!(f, "u64"),
+            Self::Usize => write!(f, "usize"),
             Self::Bool => write!(f, "bool"),
+            Self::Str => write!(f, "str"),
             Self::Unit => write!(f, "()"),
+            Self::Never => write!(f, "!"),
+            Self::Named(name) => write!(f, "{name}"),
+            Self::Array(ty, len) => write!(f, "[{ty}; {len}]"),
+            Self::Tuple(tys) if tys.len() == 1 => write!(f, "({},)", tys[0]),
+            Self::Tuple(tys) => {
+                write!(f, "(")?;
+                for (i, ty) in tys.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{ty}")?;
+                }
+                write!(f, ")")
+            }
+            Self::Fn(params, ret) => {
+                write!(f, "fn(")?;
+                for (i, ty) in params.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{ty}")?;
+                }
+                match ret.as_ref() {
+                    Type::Unit => write!(f, ")"),
+                    ret => write!(f, ") -> {ret}"),
+                }
+            }
             Self::Unresolved => write!(f, "<?>"),
         }
     }
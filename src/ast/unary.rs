@@ -52,11 +52,12 @@ impl<'i> Ast<'i> for UnaryExpr<'i> {
         let operator = match operator.as_rule() {
             Rule::plus => UnaryOp::Plus,
             Rule::neg => UnaryOp::Neg,
+            Rule::not => UnaryOp::Not,
             _ => unreachable!("{:?}", operator),
         };
 
         let operand = tokens.next().unwrap();
-        let operand = Box::<Expr>::new(Ast::parse(operand)?);
+        let operand = Box::new(Expr::parse_operand(operand)?);
 
         Ok(Self {
             operator,
@@ -68,6 +69,22 @@ impl<'i> Ast<'i> for UnaryExpr<'i> {
     }
 }
 
+impl<'i> UnaryExpr<'i> {
+    /// `-(1)` where an `i8` is expected is `-(1i8)`, see [`Expr::infer_lit`]
+    pub fn infer_lit(&mut self, ty: &Type) -> Result<()> {
+        let applies = match self.operator {
+            UnaryOp::Plus => true,
+            UnaryOp::Neg => ty.is_signed() || ty.is_float(),
+            UnaryOp::Not => false,
+        };
+        if applies && self.ty.as_ref().is_some_and(Type::is_lit_default) {
+            self.operand.infer_lit(ty)?;
+            self.ty = Some(self.operand.type_of());
+        }
+        Ok(())
+    }
+}
+
 impl<'i> TypeOf<'i> for UnaryExpr<'i> {
     fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
         self.operand.type_check(vars)?;
@@ -75,21 +92,16 @@ impl<'i> TypeOf<'i> for UnaryExpr<'i> {
         let operator = self.operator;
         let operand = self.operand.type_of();
 
-        let ty = match (operator, operand) {
-            (UnaryOp::Plus, Type::F64) => Ok(Type::F64),
-            (UnaryOp::Plus, Type::I64) => Ok(Type::I64),
-            (UnaryOp::Neg, Type::F64) => Ok(Type::F64),
-            (UnaryOp::Neg, Type::I64) => Ok(Type::I64),
+        let ty = match (operator, &operand) {
+            (UnaryOp::Plus, ty) if ty.is_number() => Ok(ty.clone()),
+            (UnaryOp::Neg, ty) if ty.is_signed() || ty.is_float() => Ok(ty.clone()),
 
             (UnaryOp::Not, Type::Bool) => Ok(Type::Bool),
 
+            (_, Type::Never) => Ok(Type::Never),
             (_, Type::Unresolved) => Ok(Type::Unresolved),
 
-            (op, rhs) => Err(Error::new_invalid_unary_op(
-                Span::new("unreachable", 0, 0).unwrap(),
-                op,
-                rhs,
-            )),
+            (op, rhs) => Err(Error::new_invalid_unary_op(self.span(), op, rhs)),
         }?;
         log::debug!("{operator} {operand} = {ty}");
         self.ty = Some(ty);
@@ -98,7 +110,7 @@ impl<'i> TypeOf<'i> for UnaryExpr<'i> {
     }
 
     fn type_of_impl(&self) -> Option<Type> {
-        self.ty
+        self.ty.clone()
     }
 }
 
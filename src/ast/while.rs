@@ -0,0 +1,77 @@
+use super::{match_rule, Ast, Error, Expr, Result, Rule, Scope, Type, TypeOf, VisibleVars};
+use pest::{iterators::Pair, Span};
+use std::fmt::Display;
+
+//
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhileInternal<'i> {
+    pub test: Expr<'i>,
+    pub scope: Scope<'i>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct While<'i> {
+    pub internal: Box<WhileInternal<'i>>,
+
+    span: Span<'i>,
+    ty: Option<Type>,
+}
+
+//
+
+impl<'i> Ast<'i> for While<'i> {
+    fn span(&self) -> Span<'i> {
+        self.span.clone()
+    }
+
+    fn parse(token: Pair<'i, Rule>) -> Result<Self> {
+        let span = token.as_span();
+        match_rule(&span, token.as_rule(), Rule::while_loop)?;
+        let mut tokens = token.into_inner();
+
+        let test = Expr::parse(tokens.next().unwrap())?;
+        let scope = Scope::parse(tokens.next().unwrap())?;
+
+        Ok(Self {
+            internal: Box::new(WhileInternal { test, scope }),
+
+            span,
+            ty: None,
+        })
+    }
+}
+
+impl<'i> TypeOf<'i> for While<'i> {
+    fn type_check_impl(&mut self, vars: &mut VisibleVars<'i>) -> Result<()> {
+        self.internal.test.type_check(vars)?;
+
+        let ty_test = self.internal.test.type_of();
+        if !matches!(ty_test, Type::Bool | Type::Never) {
+            return Err(Error::new_type_mismatch(
+                self.internal.test.span(),
+                &Type::Bool,
+                &ty_test,
+            ));
+        }
+
+        // while loops can exit without a break, so breaks cannot carry values
+        vars.push_loop(Some(Type::Unit));
+        self.internal.scope.type_check(vars)?;
+        vars.pop_loop();
+
+        self.ty = Some(Type::Unit);
+
+        Ok(())
+    }
+
+    fn type_of_impl(&self) -> Option<Type> {
+        self.ty.clone()
+    }
+}
+
+impl<'i> Display for While<'i> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "while {} {}", self.internal.test, self.internal.scope)
+    }
+}
@@ -1,19 +1,66 @@
-use super::{CodeGen, CodeGenResult};
+use super::{
+    lambda::code_gen_fn_value, r#static::static_ptr, wrapper::load, CodeGen, CodeGenResult,
+};
 use crate::{
-    ast,
-    compiler::{err::CompileError, module::Module},
+    ast::{self, TypeOf},
+    compiler::{
+        err::{CompileError, CompileResult},
+        module::{Module, Var},
+    },
 };
+use inkwell::{values::PointerValue, AddressSpace};
 
 //
 
 impl<'i> CodeGen for ast::Access<'i> {
     fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
-        let function = module.function.borrow();
-        let function = function.as_ref().expect("Access outside of any function?");
-
-        match function.vars.get(self.name.value.as_str()) {
-            Some(&val) => Ok(val),
-            None => Err(CompileError::VarNotFound),
+        let name = self.name.value.as_str();
+        match (load_var(module, name), self.type_of()) {
+            (Err(CompileError::VarNotFound), _) if module.statics.contains_key(name) => {
+                Ok(static_ptr(module, name).map(|ptr| module.builder.build_load(ptr, name)))
+            }
+            (Err(CompileError::VarNotFound), ty) if module.host_globals.contains_key(name) => {
+                let ptr = host_global_ptr(module, name, &ty, false)?;
+                Ok(load(module, ptr, 0, &ty))
+            }
+            // functions used as values
+            (Err(CompileError::VarNotFound), ast::Type::Fn(params, _)) => {
+                code_gen_fn_value(module, name, &params)
+            }
+            (value, _) => value,
         }
     }
 }
+
+/// the current value of the variable `name`
+pub(super) fn load_var<'ctx>(module: &Module<'ctx>, name: &str) -> CodeGenResult<'ctx> {
+    let function = module.function.borrow();
+    let function = function.as_ref().expect("Access outside of any function?");
+
+    match function.vars.get(name) {
+        Some(&Var::Value(val)) => Ok(val),
+        Some(&Var::Slot(slot)) => Ok(Some(module.builder.build_load(slot, name))),
+        None => Err(CompileError::VarNotFound),
+    }
+}
+
+/// the memory of the host global `name`, which has to hold a `ty`
+pub(super) fn host_global_ptr<'ctx>(
+    module: &Module<'ctx>,
+    name: &str,
+    ty: &ast::Type,
+    assign: bool,
+) -> CompileResult<PointerValue<'ctx>> {
+    let global = &module.host_globals[name];
+    // the module might have been type checked without this host global
+    if global.ty != *ty || (assign && !global.mutable) {
+        return Err(CompileError::InvalidType);
+    }
+
+    let i8_ptr = module.context.i8_type().ptr_type(AddressSpace::Generic);
+    Ok(module
+        .context
+        .i64_type()
+        .const_int(global.as_ptr() as u64, false)
+        .const_to_pointer(i8_ptr))
+}
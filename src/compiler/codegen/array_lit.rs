@@ -0,0 +1,55 @@
+use super::{CodeGen, CodeGenResult};
+use crate::{
+    ast::{self, TypeOf},
+    compiler::module::Module,
+};
+
+//
+
+impl<'i> CodeGen for ast::ArrayLit<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let ty = module
+            .basic_type(&self.type_of())
+            .unwrap()
+            .into_array_type();
+        // elements without a value are stored as empty structs
+        let zero = || ty.get_element_type().into_struct_type().const_zero().into();
+
+        let mut value = ty.get_undef();
+        let mut insert = |module: &mut Module<'ctx>, element, i: usize| {
+            value = module
+                .builder
+                .build_insert_value(value, element, i as u32, "ArrayLit element")
+                .unwrap()
+                .into_array_value();
+        };
+
+        match self.repeat {
+            Some(len) => {
+                let element = self.elements[0].code_gen(module)?;
+                if module.is_terminated() {
+                    // the element jumped out with break or continue
+                    return Ok(None);
+                }
+
+                let element = element.unwrap_or_else(zero);
+                for i in 0..len {
+                    insert(module, element, i);
+                }
+            }
+            None => {
+                for (i, element) in self.elements.iter().enumerate() {
+                    let element = element.code_gen(module)?;
+                    if module.is_terminated() {
+                        // the element jumped out with break or continue
+                        return Ok(None);
+                    }
+
+                    insert(module, element.unwrap_or_else(zero), i);
+                }
+            }
+        }
+
+        Ok(Some(value.into()))
+    }
+}
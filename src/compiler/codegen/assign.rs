@@ -1,4 +1,4 @@
-use super::{CodeGen, CodeGenResult};
+use super::{pattern::code_gen_bindings, CodeGen, CodeGenResult};
 use crate::{ast, compiler::module::Module};
 
 //
@@ -6,14 +6,12 @@ use crate::{ast, compiler::module::Module};
 impl<'i> CodeGen for ast::Assign<'i> {
     fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
         let value = self.expr.code_gen(module)?;
+        if module.is_terminated() {
+            // the value jumped out with break or continue
+            return Ok(None);
+        }
 
-        module
-            .function
-            .borrow_mut()
-            .as_mut()
-            .expect("Assign outside of any function?")
-            .vars
-            .insert(self.name.value.clone(), value);
+        code_gen_bindings(module, &self.pattern, value);
 
         Ok(value)
     }
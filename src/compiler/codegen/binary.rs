@@ -1,8 +1,15 @@
 use super::{CodeGen, CodeGenResult};
-use crate::{ast, compiler::module::Module};
+use crate::{
+    ast::{self, Type, TypeOf},
+    compiler::{
+        err::CompileError,
+        module::Module,
+        runtime::{STR_CMP, STR_CONCAT},
+    },
+};
 use inkwell::{
     builder::Builder,
-    values::{BasicValueEnum, FloatValue, IntValue},
+    values::{BasicValueEnum, FloatValue, IntValue, StructValue},
     FloatPredicate, IntPredicate,
 };
 
@@ -10,36 +17,195 @@ use inkwell::{
 
 impl<'i> CodeGen for ast::BinaryExpr<'i> {
     fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
-        let lhs = self.operands.lhs.code_gen(module)?.unwrap();
-        let rhs = self.operands.rhs.code_gen(module)?.unwrap();
+        if matches!(self.operator, ast::BinaryOp::Or | ast::BinaryOp::And) {
+            return short_circuit(module, self);
+        }
+
+        let lhs = self.operands.lhs.code_gen(module)?;
+        if module.is_terminated() {
+            // the lhs jumped out with break, continue or return
+            return Ok(None);
+        }
+        let rhs = self.operands.rhs.code_gen(module)?;
+        if module.is_terminated() {
+            // the rhs jumped out with break, continue or return
+            return Ok(None);
+        }
+        let lhs = lhs.ok_or(CompileError::InvalidType)?;
+        let rhs = rhs.ok_or(CompileError::InvalidType)?;
 
-        let value = if lhs.is_float_value() || rhs.is_float_value() {
-            let lhs = into_float_value(module, lhs);
-            let rhs = into_float_value(module, rhs);
+        // both sides have the same type, the values alone can't tell signed and unsigned apart
+        let ty = self.operands.lhs.type_of();
+        let value = if ty == Type::Unresolved {
+            return Err(CompileError::InvalidType);
+        } else if ty == Type::Str {
+            binary_str_op(
+                module,
+                self.operator,
+                lhs.into_struct_value(),
+                rhs.into_struct_value(),
+            )
+        } else if ty.is_float() {
+            let lhs = lhs.into_float_value();
+            let rhs = rhs.into_float_value();
             let b = &module.builder;
 
             binary_float_op(b, self.operator, lhs, rhs)
         } else {
             let lhs = lhs.into_int_value();
-            let rhs = rhs.into_int_value();
+            let mut rhs = rhs.into_int_value();
             let b = &module.builder;
 
-            binary_int_op(b, self.operator, lhs, rhs)
+            if matches!(self.operator, ast::BinaryOp::Shl | ast::BinaryOp::Shr) {
+                rhs = shift_amount(b, lhs, rhs);
+            }
+
+            // bools are `i1`, `true` is 1 unsigned but -1 signed
+            binary_int_op(
+                b,
+                self.operator,
+                lhs,
+                rhs,
+                ty.is_unsigned() || ty == Type::Bool,
+            )
         };
 
         Ok(Some(value))
     }
 }
 
-fn into_float_value<'ctx>(module: &Module<'ctx>, value: BasicValueEnum<'ctx>) -> FloatValue<'ctx> {
-    if value.is_float_value() {
-        value.into_float_value()
+// `&&` and `||` only evaluate the rhs if the lhs doesn't decide the result
+//
+// +---+  lhs decides          +---+
+// | E |-----------------------| C |
+// | N |                       | O |
+// | T |  lhs doesn't  +-----+ | N |
+// | R |---------------| RHS |-| T |
+// | Y |               +-----+ +---+
+// +---+
+
+fn short_circuit<'ctx>(module: &mut Module<'ctx>, expr: &ast::BinaryExpr) -> CodeGenResult<'ctx> {
+    let lhs = expr.operands.lhs.code_gen(module)?;
+    if module.is_terminated() {
+        // the lhs jumped out with break or continue
+        return Ok(None);
+    }
+    let lhs = lhs.ok_or(CompileError::InvalidType)?.into_int_value();
+
+    let proto = module
+        .function
+        .borrow()
+        .as_ref()
+        .expect("BinaryExpr outside of any function?")
+        .proto;
+
+    let id = module.label_id;
+    module.label_id += 1;
+
+    let entry = module.builder.get_insert_block().unwrap();
+    let rhs_block = module
+        .context
+        .append_basic_block(proto, &format!("BinaryExpr rhs {id}"));
+    let r#continue = module
+        .context
+        .append_basic_block(proto, &format!("BinaryExpr continue {id}"));
+
+    // `false && _` is false and `true || _` is true
+    let decided = expr.operator == ast::BinaryOp::Or;
+    if decided {
+        module
+            .builder
+            .build_conditional_branch(lhs, r#continue, rhs_block);
     } else {
-        module.builder.build_signed_int_to_float(
-            value.into_int_value(),
-            module.context.f64_type(),
-            "BinaryExpr lhs f cast",
+        module
+            .builder
+            .build_conditional_branch(lhs, rhs_block, r#continue);
+    }
+
+    // rhs block
+    module.builder.position_at_end(rhs_block);
+    let rhs = expr.operands.rhs.code_gen(module)?;
+    let rhs_block = module.builder.get_insert_block().unwrap(); // the rhs codegen can make new blocks
+    let rhs_continues = !module.is_terminated();
+    if rhs_continues {
+        module.builder.build_unconditional_branch(r#continue);
+    }
+
+    // continue block
+    module.builder.position_at_end(r#continue);
+    let bool_ty = module.context.bool_type();
+    let phi = module
+        .builder
+        .build_phi(bool_ty, &format!("BinaryExpr phi {id}"));
+    phi.add_incoming(&[(&bool_ty.const_int(decided as u64, false), entry)]);
+    if rhs_continues {
+        let rhs = rhs.ok_or(CompileError::InvalidType)?;
+        phi.add_incoming(&[(&rhs, rhs_block)]);
+    }
+    Ok(Some(phi.as_basic_value()))
+}
+
+/// strings are concatenated and compared by the host, see `compiler::runtime`
+fn binary_str_op<'ctx>(
+    module: &Module<'ctx>,
+    op: ast::BinaryOp,
+    lhs: StructValue<'ctx>,
+    rhs: StructValue<'ctx>,
+) -> BasicValueEnum<'ctx> {
+    use ast::BinaryOp::*;
+    use inkwell::IntPredicate::*;
+
+    let b = &module.builder;
+    let field = |s: StructValue<'ctx>, i: u32| b.build_extract_value(s, i, "Str field").unwrap();
+    let (lhs_len, rhs_len) = (field(lhs, 1), field(rhs, 1));
+    let args = [field(lhs, 0), lhs_len, field(rhs, 0), rhs_len];
+
+    let runtime_fn = match op {
+        Add => STR_CONCAT,
+        _ => STR_CMP,
+    };
+    let result = b
+        .build_call(
+            module.module.get_function(runtime_fn).unwrap(),
+            &args,
+            "BinaryExpr s call",
         )
+        .try_as_basic_value()
+        .left()
+        .unwrap();
+
+    if op == Add {
+        let len = b.build_int_add(
+            lhs_len.into_int_value(),
+            rhs_len.into_int_value(),
+            "BinaryExpr s len",
+        );
+        let value = module.str_type().get_undef();
+        let value = b
+            .build_insert_value(value, result, 0, "BinaryExpr s ptr")
+            .unwrap();
+        let value = b
+            .build_insert_value(value, len, 1, "BinaryExpr s len")
+            .unwrap();
+        return value.into_struct_value().into();
+    }
+
+    // the ordering of the strings is compared to 0
+    let ordering = result.into_int_value();
+    let zero = module.context.i32_type().const_zero();
+    let s_cmp = |pred: IntPredicate, name: &str| -> BasicValueEnum {
+        b.build_int_compare(pred, ordering, zero, name).into()
+    };
+
+    match op {
+        Eq => s_cmp(EQ, "BinaryExpr s eq"),
+        Ne => s_cmp(NE, "BinaryExpr s ne"),
+        Gt => s_cmp(SGT, "BinaryExpr s gt"),
+        Ge => s_cmp(SGE, "BinaryExpr s ge"),
+        Lt => s_cmp(SLT, "BinaryExpr s lt"),
+        Le => s_cmp(SLE, "BinaryExpr s le"),
+
+        op => unreachable!("{}", op),
     }
 }
 
@@ -61,6 +227,7 @@ fn binary_float_op<'ctx>(
         Sub => b.build_float_sub(lhs, rhs, "BinaryExpr f sub").into(),
         Mul => b.build_float_mul(lhs, rhs, "BinaryExpr f mul").into(),
         Div => b.build_float_div(lhs, rhs, "BinaryExpr f div").into(),
+        Rem => b.build_float_rem(lhs, rhs, "BinaryExpr f rem").into(),
 
         Eq => f_cmp(OEQ, "BinaryExpr f eq"),
         Ne => f_cmp(ONE, "BinaryExpr f neq"),
@@ -69,15 +236,33 @@ fn binary_float_op<'ctx>(
         Lt => f_cmp(OLT, "BinaryExpr f lt"),
         Le => f_cmp(OLE, "BinaryExpr f le"),
 
-        op => todo!("{}", op),
+        op => unreachable!("{}", op),
     }
 }
 
+/// the shift amount with the width of the shifted value,
+/// wrapped to less than that width like rust does without overflow checks
+fn shift_amount<'ctx>(
+    b: &Builder<'ctx>,
+    lhs: IntValue<'ctx>,
+    rhs: IntValue<'ctx>,
+) -> IntValue<'ctx> {
+    let ty = lhs.get_type();
+    let rhs = if rhs.get_type().get_bit_width() > ty.get_bit_width() {
+        b.build_int_truncate(rhs, ty, "BinaryExpr shift trunc")
+    } else {
+        b.build_int_z_extend_or_bit_cast(rhs, ty, "BinaryExpr shift zext")
+    };
+    let mask = ty.const_int(ty.get_bit_width() as u64 - 1, false);
+    b.build_and(rhs, mask, "BinaryExpr shift mask")
+}
+
 fn binary_int_op<'ctx>(
     b: &Builder<'ctx>,
     op: ast::BinaryOp,
     lhs: IntValue<'ctx>,
     rhs: IntValue<'ctx>,
+    unsigned: bool,
 ) -> BasicValueEnum<'ctx> {
     use ast::BinaryOp::*;
     use inkwell::IntPredicate::*;
@@ -86,21 +271,42 @@ fn binary_int_op<'ctx>(
         b.build_int_compare(pred, lhs, rhs, name).into()
     };
 
-    match op {
-        Add => b.build_int_add(lhs, rhs, "BinaryExpr i add").into(),
-        Sub => b.build_int_sub(lhs, rhs, "BinaryExpr i sub").into(),
-        Mul => b.build_int_mul(lhs, rhs, "BinaryExpr i mul").into(),
-        Div => b.build_int_signed_div(lhs, rhs, "BinaryExpr i div").into(),
-
-        Eq => i_cmp(EQ, "BinaryExpr i eq"),
-        Ne => i_cmp(NE, "BinaryExpr i ne"),
-        Gt => i_cmp(SGT, "BinaryExpr i gt"),
-        Ge => i_cmp(SGE, "BinaryExpr i ge"),
-        Lt => i_cmp(SLT, "BinaryExpr i lt"),
-        Le => i_cmp(SLE, "BinaryExpr i le"),
-
-        Or => b.build_or(lhs, rhs, "BinaryExpr b or").into(),
-        And => b.build_and(lhs, rhs, "BinaryExpr b and").into(),
+    match (op, unsigned) {
+        (Add, _) => b.build_int_add(lhs, rhs, "BinaryExpr i add").into(),
+        (Sub, _) => b.build_int_sub(lhs, rhs, "BinaryExpr i sub").into(),
+        (Mul, _) => b.build_int_mul(lhs, rhs, "BinaryExpr i mul").into(),
+        (Div, false) => b.build_int_signed_div(lhs, rhs, "BinaryExpr i div").into(),
+        (Div, true) => b
+            .build_int_unsigned_div(lhs, rhs, "BinaryExpr u div")
+            .into(),
+        (Rem, false) => b.build_int_signed_rem(lhs, rhs, "BinaryExpr i rem").into(),
+        (Rem, true) => b
+            .build_int_unsigned_rem(lhs, rhs, "BinaryExpr u rem")
+            .into(),
+
+        (Eq, _) => i_cmp(EQ, "BinaryExpr i eq"),
+        (Ne, _) => i_cmp(NE, "BinaryExpr i ne"),
+        (Gt, false) => i_cmp(SGT, "BinaryExpr i gt"),
+        (Ge, false) => i_cmp(SGE, "BinaryExpr i ge"),
+        (Lt, false) => i_cmp(SLT, "BinaryExpr i lt"),
+        (Le, false) => i_cmp(SLE, "BinaryExpr i le"),
+        (Gt, true) => i_cmp(UGT, "BinaryExpr u gt"),
+        (Ge, true) => i_cmp(UGE, "BinaryExpr u ge"),
+        (Lt, true) => i_cmp(ULT, "BinaryExpr u lt"),
+        (Le, true) => i_cmp(ULE, "BinaryExpr u le"),
+
+        (Or | And, _) => unreachable!("short circuited"),
+
+        (BitAnd, _) => b.build_and(lhs, rhs, "BinaryExpr i and").into(),
+        (BitOr, _) => b.build_or(lhs, rhs, "BinaryExpr i or").into(),
+        (BitXor, _) => b.build_xor(lhs, rhs, "BinaryExpr i xor").into(),
+        (Shl, _) => b.build_left_shift(lhs, rhs, "BinaryExpr i shl").into(),
+        (Shr, false) => b
+            .build_right_shift(lhs, rhs, true, "BinaryExpr i shr")
+            .into(),
+        (Shr, true) => b
+            .build_right_shift(lhs, rhs, false, "BinaryExpr u shr")
+            .into(),
         // op => todo!("{}", op),
     }
 }
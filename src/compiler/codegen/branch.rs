@@ -6,7 +6,6 @@ use crate::{
         module::Module,
     },
 };
-use inkwell::types::BasicTypeEnum;
 
 // branches contain 3 blocks
 //
@@ -23,7 +22,12 @@ use inkwell::types::BasicTypeEnum;
 
 impl<'i> CodeGen for ast::Branch<'i> {
     fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
-        let cond = self.internal.test.code_gen(module)?.expect_bool()?;
+        let cond = self.internal.test.code_gen(module)?;
+        if module.is_terminated() {
+            // the condition jumped out with break, continue or return
+            return Ok(None);
+        }
+        let cond = cond.expect_bool()?;
         // let const_zero = module.context.f64_type().const_zero();
         let function = module.function.clone();
         let function_ref = function.borrow();
@@ -54,33 +58,43 @@ impl<'i> CodeGen for ast::Branch<'i> {
         module.builder.position_at_end(a);
         let result_a = self.internal.on_true.code_gen(module)?;
         let a = module.builder.get_insert_block().unwrap(); // because the result_a codegen can make new blocks, we need to get the 'last' one
-        module.builder.build_unconditional_branch(r#continue);
+        let a_continues = !module.is_terminated(); // the on_true block might have jumped out with break or continue
+        if a_continues {
+            module.builder.build_unconditional_branch(r#continue);
+        }
 
         // on_false block
         module.builder.position_at_end(b);
-        let result_b = self.internal.on_false.code_gen(module)?;
+        let result_b = match self.internal.on_false.as_ref() {
+            Some(on_false) => on_false.code_gen(module)?,
+            None => None,
+        };
         let b = module.builder.get_insert_block().unwrap(); // because the result_b codegen can make new blocks, we need to get the 'last' one
-        module.builder.build_unconditional_branch(r#continue);
+        let b_continues = !module.is_terminated(); // the on_false block might have jumped out with break or continue
+        if b_continues {
+            module.builder.build_unconditional_branch(r#continue);
+        }
+
+        if !a_continues && !b_continues {
+            // neither block reaches the continue block
+            module.delete_unreachable_block(r#continue);
+            return Ok(None);
+        }
 
         // continue block
         module.builder.position_at_end(r#continue);
-        match (result_a, result_b) {
-            (None, None) => Ok(None),
-            (None, Some(_)) => Err(CompileError::InvalidType),
-            (Some(_), None) => Err(CompileError::InvalidType),
-            (Some(result_a), Some(result_b)) => {
-                let ty: BasicTypeEnum = match self.type_of() {
-                    ast::Type::F64 => module.context.f64_type().into(),
-                    ast::Type::U64 => module.context.i64_type().into(),
-                    ast::Type::I64 => module.context.i64_type().into(),
-                    ast::Type::Bool => module.context.bool_type().into(),
-                    ast::Type::Unit | ast::Type::Unresolved => unreachable!(),
-                };
 
-                let phi = module.builder.build_phi(ty, &format!("Branch phi {id}"));
-                phi.add_incoming(&[(&result_a, a), (&result_b, b)]);
-                Ok(Some(phi.as_basic_value()))
+        let ty = match module.basic_type(&self.type_of()) {
+            Some(ty) => ty,
+            None => return Ok(None),
+        };
+
+        let phi = module.builder.build_phi(ty, &format!("Branch phi {id}"));
+        for (result, block, continues) in [(result_a, a, a_continues), (result_b, b, b_continues)] {
+            if continues {
+                phi.add_incoming(&[(&result.ok_or(CompileError::InvalidType)?, block)]);
             }
         }
+        Ok(Some(phi.as_basic_value()))
     }
 }
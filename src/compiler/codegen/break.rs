@@ -0,0 +1,32 @@
+use super::{CodeGen, CodeGenResult};
+use crate::{ast, compiler::module::Module};
+
+//
+
+impl<'i> CodeGen for ast::Break<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let value = match self.expr.as_ref() {
+            Some(expr) => expr.code_gen(module)?,
+            None => None,
+        };
+        if module.is_terminated() {
+            return Ok(None);
+        }
+        // because the value codegen can make new blocks, the 'last' one is the source
+        let block = module.builder.get_insert_block().unwrap();
+
+        let exit = {
+            let mut function = module.function.borrow_mut();
+            let function = function.as_mut().expect("Break outside of any function?");
+            let blocks = function
+                .loops
+                .last_mut()
+                .expect("Break outside of any loop?");
+            blocks.breaks.push((value, block));
+            blocks.exit
+        };
+
+        module.builder.build_unconditional_branch(exit);
+        Ok(None)
+    }
+}
@@ -1,8 +1,23 @@
-use super::{CodeGen, CodeGenResult};
+use super::{
+    access::load_var,
+    wrapper::{load, store},
+    CodeGen, CodeGenResult,
+};
 use crate::{
-    ast::{self, generic_mangle, TypeOf},
-    compiler::{err::CompileError, module::Module},
+    ast::{self, generic_mangle, Type, TypeOf},
+    compiler::{
+        err::{CompileError, CompileResult},
+        host::HostFn,
+        module::Module,
+        runtime::HOST_CALL,
+    },
+};
+use inkwell::{
+    types::BasicType,
+    values::{BasicValueEnum, PointerValue},
+    AddressSpace,
 };
+use std::alloc::Layout;
 
 //
 
@@ -18,24 +33,178 @@ impl<'i> CodeGen for ast::Call<'i> {
 
         log::debug!("Compiling call: {name} ({sig:?} {as_generic})",);
 
+        if self.indirect {
+            return code_gen_indirect(module, self);
+        }
+
+        let host_fns = module.host_fns;
+        let shadowed = !self.method
+            && (host_fns.contains_key(name) || module.functions.contains_key(&as_generic));
+        match (name, &sig[..]) {
+            _ if shadowed => {}
+            ("len", [Type::Array(_, len)]) => {
+                self.args[0].code_gen(module)?;
+                let len = module.context.i64_type().const_int(*len as u64, false);
+                return Ok(Some(len.into()));
+            }
+            ("len", [Type::Str]) => {
+                let s = self.args[0].code_gen(module)?.unwrap().into_struct_value();
+                let len = module.builder.build_extract_value(s, 1, "Str len");
+                return Ok(len);
+            }
+            _ => {}
+        }
+
+        if let Some(host) = host_fns.get(name) {
+            return code_gen_host(module, self, host);
+        }
+
         let func = match module.functions.get(&as_generic) {
             Some(&val) => val,
             None => return Err(CompileError::FuncNotFound),
         };
 
-        let args: Vec<_> = self
-            .args
-            .iter()
-            .map(|arg| Ok(arg.code_gen(module)?.unwrap()))
-            .collect::<Result<_, _>>()?;
+        let mut args = vec![];
+        if !code_gen_args(module, self, &mut args)? {
+            return Ok(None);
+        }
 
+        // unit functions return void
         let ret = module
             .builder
             .build_call(func, &args[..], "function call")
             .try_as_basic_value()
-            .left()
-            .unwrap();
+            .left();
+        after_call(module, self);
+
+        Ok(ret)
+    }
+}
+
+/// calls a function value, the function pointer gets the captured environment as the first argument
+fn code_gen_indirect<'ctx>(module: &mut Module<'ctx>, call: &ast::Call) -> CodeGenResult<'ctx> {
+    let value = load_var(module, call.name.value.as_str())?
+        .ok_or(CompileError::InvalidType)?
+        .into_struct_value();
+    let ptr = module
+        .builder
+        .build_extract_value(value, 0, "Call fn ptr")
+        .unwrap()
+        .into_pointer_value();
+    let env = module
+        .builder
+        .build_extract_value(value, 1, "Call env")
+        .unwrap();
+
+    let mut args = vec![env];
+    if !code_gen_args(module, call, &mut args)? {
+        return Ok(None);
+    }
+
+    let ret = module
+        .builder
+        .build_call(ptr, &args[..], "function value call")
+        .try_as_basic_value()
+        .left();
+    after_call(module, call);
+
+    Ok(ret)
+}
+
+/// calls a host function through [`crate::compiler::runtime::host_call`],
+/// the args and the return value are passed in buffers laid out like the wrappers do
+fn code_gen_host<'ctx>(
+    module: &mut Module<'ctx>,
+    call: &ast::Call,
+    host: &HostFn,
+) -> CodeGenResult<'ctx> {
+    let params = &host.sig.arg_ty;
+    let ret = &host.sig.out_ty;
+    // the module might have been type checked without this host function
+    if params.len() != call.args.len()
+        || call
+            .args
+            .iter()
+            .zip(params.iter())
+            .any(|(arg, ty)| arg.type_of() != *ty)
+    {
+        return Err(CompileError::FuncNotFound);
+    }
+
+    let mut args = vec![];
+    if !code_gen_args(module, call, &mut args)? {
+        return Ok(None);
+    }
+
+    let (layout, offsets) = module.tuple_layout(params);
+    let args_ptr = build_host_buffer(module, layout, "Host args");
+    for ((ty, offset), value) in params.iter().zip(offsets).zip(args) {
+        store(module, args_ptr, offset, ty, value);
+    }
+    let ret_ptr = build_host_buffer(module, module.layout(ret), "Host ret");
+
+    let i8_ptr = module.context.i8_type().ptr_type(AddressSpace::Generic);
+    let f = module
+        .context
+        .i64_type()
+        .const_int(host.as_ptr() as u64, false)
+        .const_to_pointer(i8_ptr);
+    let host_call = module.module.get_function(HOST_CALL).unwrap();
+    let code = module
+        .builder
+        .build_call(
+            host_call,
+            &[f.into(), args_ptr.into(), ret_ptr.into()],
+            "Host call",
+        )
+        .try_as_basic_value()
+        .left()
+        .unwrap();
+    module.builder.build_store(module.runtime_error(), code);
+    after_call(module, call);
+
+    Ok(load(module, ret_ptr, 0, ret))
+}
+
+/// a stack buffer for values passed to or from the host
+fn build_host_buffer<'ctx>(
+    module: &Module<'ctx>,
+    layout: Layout,
+    name: &str,
+) -> PointerValue<'ctx> {
+    // none of the script types need more alignment than u64
+    let words = module
+        .context
+        .i64_type()
+        .array_type(layout.size() as u32 / 8 + 1);
+    let ptr = module.build_entry_alloca(words.as_basic_type_enum(), name);
+    let i8_ptr = module.context.i8_type().ptr_type(AddressSpace::Generic);
+    module.builder.build_pointer_cast(ptr, i8_ptr, name)
+}
+
+/// appends the values of the arguments to `args`,
+/// `false` if one of them jumped out with break, continue or return
+fn code_gen_args<'ctx>(
+    module: &mut Module<'ctx>,
+    call: &ast::Call,
+    args: &mut Vec<BasicValueEnum<'ctx>>,
+) -> CompileResult<bool> {
+    for arg in call.args.iter() {
+        let value = arg.code_gen(module)?;
+        if module.is_terminated() {
+            return Ok(false);
+        }
+        args.push(value.ok_or(CompileError::InvalidType)?);
+    }
+    Ok(true)
+}
+
+fn after_call(module: &mut Module, call: &ast::Call) {
+    // the callee might have failed a runtime check
+    module.build_runtime_error_check();
 
-        Ok(Some(ret))
+    // functions that never return leave nothing after the call reachable
+    if call.type_of() == Type::Never {
+        module.builder.build_unreachable();
     }
 }
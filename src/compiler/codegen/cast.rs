@@ -0,0 +1,145 @@
+use super::{CodeGen, CodeGenResult};
+use crate::{
+    ast::{self, Type, TypeOf},
+    compiler::{err::CompileError, module::Module},
+};
+use inkwell::{
+    types::FloatType,
+    values::{BasicValueEnum, FloatValue, IntValue},
+    FloatPredicate,
+};
+
+//
+
+impl<'i> CodeGen for ast::Cast<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let value = match self.expr.code_gen(module)? {
+            Some(value) => value,
+            // the expression never produced a value
+            None => return Ok(None),
+        };
+
+        let from = self.expr.type_of();
+        let to = &self.target;
+        let b = &module.builder;
+
+        let value: BasicValueEnum = match (from.bits(), to.bits()) {
+            _ if from == *to => value,
+
+            // signed and unsigned integers only differ in how their bits are read
+            (Some(from_bits), Some(to_bits)) if from.is_int() && to.is_int() => {
+                let value = value.into_int_value();
+                let ty = module.context.custom_width_int_type(to_bits);
+                if from_bits > to_bits {
+                    b.build_int_truncate(value, ty, "Cast i trunc").into()
+                } else if from_bits == to_bits {
+                    value.into()
+                } else if from.is_signed() {
+                    b.build_int_s_extend(value, ty, "Cast i sext").into()
+                } else {
+                    b.build_int_z_extend(value, ty, "Cast i zext").into()
+                }
+            }
+
+            (Some(from_bits), Some(to_bits)) if from.is_float() && to.is_float() => {
+                let value = value.into_float_value();
+                let ty = float_type(module, to_bits);
+                if from_bits > to_bits {
+                    b.build_float_trunc(value, ty, "Cast f trunc").into()
+                } else {
+                    b.build_float_ext(value, ty, "Cast f ext").into()
+                }
+            }
+
+            (_, Some(to_bits)) if from.is_int() && to.is_float() => {
+                let value = value.into_int_value();
+                let ty = float_type(module, to_bits);
+                if from.is_signed() {
+                    b.build_signed_int_to_float(value, ty, "Cast i to f").into()
+                } else {
+                    b.build_unsigned_int_to_float(value, ty, "Cast u to f")
+                        .into()
+                }
+            }
+
+            (_, Some(to_bits)) if from.is_float() && to.is_int() => {
+                float_to_int(module, value.into_float_value(), to_bits, to.is_signed()).into()
+            }
+
+            (_, Some(to_bits)) if from == Type::Bool => {
+                let ty = module.context.custom_width_int_type(to_bits);
+                b.build_int_z_extend(value.into_int_value(), ty, "Cast b to i")
+                    .into()
+            }
+
+            _ => return Err(CompileError::InvalidType),
+        };
+
+        Ok(Some(value))
+    }
+}
+
+fn float_type<'ctx>(module: &Module<'ctx>, bits: u32) -> FloatType<'ctx> {
+    match bits {
+        32 => module.context.f32_type(),
+        _ => module.context.f64_type(),
+    }
+}
+
+/// converts a float to an integer like rust does:
+/// values out of range saturate to the closest integer and NaN becomes 0
+fn float_to_int<'ctx>(
+    module: &Module<'ctx>,
+    value: FloatValue<'ctx>,
+    bits: u32,
+    signed: bool,
+) -> IntValue<'ctx> {
+    let b = &module.builder;
+    let int_ty = module.context.custom_width_int_type(bits);
+    let float_ty = value.get_type();
+
+    // powers of two are exact in every float type
+    let (int, min, max, lowest, highest) = if signed {
+        let half = 1i128 << (bits - 1);
+        (
+            b.build_float_to_signed_int(value, int_ty, "Cast f to i"),
+            int_ty.const_int(-half as u64, true),
+            int_ty.const_int((half - 1) as u64, true),
+            -half as f64,
+            // the first float too large for the integer
+            half as f64,
+        )
+    } else {
+        (
+            b.build_float_to_unsigned_int(value, int_ty, "Cast f to u"),
+            int_ty.const_zero(),
+            int_ty.const_all_ones(),
+            0.0,
+            // the first float too large for the integer
+            (1u128 << bits) as f64,
+        )
+    };
+
+    let too_low = b.build_float_compare(
+        FloatPredicate::OLT,
+        value,
+        float_ty.const_float(lowest),
+        "Cast too low",
+    );
+    let too_high = b.build_float_compare(
+        FloatPredicate::OGE,
+        value,
+        float_ty.const_float(highest),
+        "Cast too high",
+    );
+    let nan = b.build_float_compare(FloatPredicate::UNO, value, value, "Cast nan");
+
+    let int = b
+        .build_select(too_low, min, int, "Cast min")
+        .into_int_value();
+    let int = b
+        .build_select(too_high, max, int, "Cast max")
+        .into_int_value();
+    b.build_select(nan, int_ty.const_zero(), int, "Cast nan")
+        .into_int_value()
+}
@@ -0,0 +1,23 @@
+use super::{CodeGen, CodeGenResult};
+use crate::{ast, compiler::module::Module};
+
+//
+
+impl<'i> CodeGen for ast::Continue<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let repeat = {
+            let function = module.function.borrow();
+            let function = function
+                .as_ref()
+                .expect("Continue outside of any function?");
+            let blocks = function
+                .loops
+                .last()
+                .expect("Continue outside of any loop?");
+            blocks.repeat
+        };
+
+        module.builder.build_unconditional_branch(repeat);
+        Ok(None)
+    }
+}
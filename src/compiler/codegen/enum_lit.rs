@@ -0,0 +1,75 @@
+use super::{CodeGen, CodeGenResult};
+use crate::{ast, compiler::module::Module};
+use inkwell::{values::PointerValue, AddressSpace};
+
+//
+
+impl<'i> CodeGen for ast::EnumLit<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let name = self.name.value.as_str();
+        let ty = module.structs[name];
+        let payload_ty = module.variants[name][self.index];
+
+        let mut payload = payload_ty.get_undef();
+        for (i, arg) in self.args.iter().enumerate() {
+            let arg_value = arg.code_gen(module)?;
+            if module.is_terminated() {
+                // the argument jumped out with break or continue
+                return Ok(None);
+            }
+
+            // fields without a value are stored as empty structs
+            let arg_value = arg_value.unwrap_or_else(|| {
+                payload_ty
+                    .get_field_type_at_index(i as u32)
+                    .unwrap()
+                    .into_struct_type()
+                    .const_zero()
+                    .into()
+            });
+
+            payload = module
+                .builder
+                .build_insert_value(payload, arg_value, i as u32, "EnumLit field")
+                .unwrap()
+                .into_struct_value();
+        }
+
+        // the storage past the payload is left zeroed
+        let slot = module.build_entry_alloca(ty.into(), "EnumLit");
+        module.builder.build_store(slot, ty.const_zero());
+        let payload_ptr = payload_ptr(module, name, slot, self.index);
+        module.builder.build_store(payload_ptr, payload);
+        let value = module
+            .builder
+            .build_load(slot, "EnumLit value")
+            .into_struct_value();
+
+        let tag = module
+            .context
+            .i32_type()
+            .const_int(self.index as u64, false);
+        let value = module
+            .builder
+            .build_insert_value(value, tag, 0, &format!("EnumLit {}", self.variant))
+            .unwrap();
+
+        Ok(Some(value.into_struct_value().into()))
+    }
+}
+
+/// the payload of variant `variant` in the storage of the enum `name` that `slot` points to
+pub(super) fn payload_ptr<'ctx>(
+    module: &Module<'ctx>,
+    name: &str,
+    slot: PointerValue<'ctx>,
+    variant: usize,
+) -> PointerValue<'ctx> {
+    let payload_ty = module.variants[name][variant];
+    let storage = unsafe { module.builder.build_struct_gep(slot, 1, "Enum storage") };
+    module.builder.build_pointer_cast(
+        storage,
+        payload_ty.ptr_type(AddressSpace::Generic),
+        "Enum payload",
+    )
+}
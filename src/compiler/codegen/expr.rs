@@ -14,6 +14,7 @@ impl<'i> CodeGen for ast::ExprInternal<'i> {
         match self {
             ast::ExprInternal::BinaryExpr(expr) => expr.code_gen(module),
             ast::ExprInternal::UnaryExpr(expr) => expr.code_gen(module),
+            ast::ExprInternal::Cast(cast) => cast.code_gen(module),
             ast::ExprInternal::Term(term) => term.code_gen(module),
         }
     }
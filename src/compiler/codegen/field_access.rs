@@ -0,0 +1,29 @@
+use super::{CodeGen, CodeGenResult};
+use crate::{
+    ast::{self, TypeOf},
+    compiler::{err::CompileError, module::Module},
+};
+use inkwell::values::BasicValueEnum;
+
+//
+
+impl<'i> CodeGen for ast::FieldAccess<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let value = match self.term.code_gen(module)? {
+            Some(BasicValueEnum::StructValue(value)) => value,
+            _ if module.is_terminated() => return Ok(None),
+            _ => return Err(CompileError::InvalidType),
+        };
+
+        if module.basic_type(&self.type_of()).is_none() {
+            // fields without a value are stored as empty structs
+            return Ok(None);
+        }
+
+        Ok(module.builder.build_extract_value(
+            value,
+            self.index as u32,
+            &format!("FieldAccess {}", self.field),
+        ))
+    }
+}
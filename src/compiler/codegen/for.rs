@@ -0,0 +1,134 @@
+use super::{pattern::code_gen_bindings, r#loop::loop_body, CodeGen, CodeGenResult};
+use crate::{
+    ast::{self, Type, TypeOf},
+    compiler::{err::CompileError, module::Module},
+};
+use inkwell::IntPredicate;
+
+// for loops contain 4 blocks and count through the array
+//
+//             +---+  on_false          +---+
+// +---+       | T |--------------------| E |
+// | E |-------| E |                    | X |
+// | N |       | S |  on_true           | I |
+// | T |       | T |-----+              | T |
+// | R |       +---+     |              +---+
+// | Y |         |     +---+              |
+// +---+         |     | B |--------------+
+//               |     | O |  break
+//             +---+   | D |
+//             | S |---| Y |
+//             | T |   +---+
+//             | E |  continue
+//             | P |
+//             +---+
+
+impl<'i> CodeGen for ast::For<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let array = self.internal.expr.code_gen(module)?;
+        if module.is_terminated() {
+            // the array jumped out with break or continue
+            return Ok(None);
+        }
+        let array = array.ok_or(CompileError::InvalidType)?.into_array_value();
+
+        let (ty, len) = match self.internal.expr.type_of() {
+            Type::Array(ty, len) => (*ty, len),
+            _ => return Err(CompileError::InvalidType),
+        };
+
+        let proto = module
+            .function
+            .borrow()
+            .as_ref()
+            .expect("For outside of any function?")
+            .proto;
+
+        let id = module.label_id;
+        module.label_id += 1;
+
+        // one block to test the counter in
+        let test = module
+            .context
+            .append_basic_block(proto, &format!("For test {id}"));
+        // one block to repeat for each element
+        let body = module
+            .context
+            .append_basic_block(proto, &format!("For body {id}"));
+        // one block to step to the next element in
+        let step = module
+            .context
+            .append_basic_block(proto, &format!("For step {id}"));
+        // and one block to enter after the last element or after breaking out
+        let exit = module
+            .context
+            .append_basic_block(proto, &format!("For exit {id}"));
+
+        let i64_ty = module.context.i64_type();
+        let slot = module.build_entry_alloca(array.get_type().into(), "For array");
+        module.builder.build_store(slot, array);
+        let counter = module.build_entry_alloca(i64_ty.into(), "For counter");
+        module.builder.build_store(counter, i64_ty.const_zero());
+        module.builder.build_unconditional_branch(test);
+
+        // test block
+        module.builder.position_at_end(test);
+        let i = module
+            .builder
+            .build_load(counter, "For index")
+            .into_int_value();
+        let len = i64_ty.const_int(len as u64, false);
+        let cond = module
+            .builder
+            .build_int_compare(IntPredicate::ULT, i, len, "For cond");
+        module.builder.build_conditional_branch(cond, body, exit);
+
+        // body block
+        module.builder.position_at_end(body);
+        let element = match module.basic_type(&ty) {
+            Some(_) => {
+                let ptr = unsafe {
+                    module.builder.build_in_bounds_gep(
+                        slot,
+                        &[i64_ty.const_zero(), i],
+                        "For element",
+                    )
+                };
+                Some(module.builder.build_load(ptr, "For load"))
+            }
+            None => None,
+        };
+
+        // bindings shadow the outer variables only until the end of the loop
+        let outer_vars = module
+            .function
+            .borrow()
+            .as_ref()
+            .expect("For outside of any function?")
+            .vars
+            .clone();
+        code_gen_bindings(module, &self.internal.pattern, element);
+        loop_body(module, &self.internal.scope, step, exit)?;
+        module
+            .function
+            .borrow_mut()
+            .as_mut()
+            .expect("For outside of any function?")
+            .vars = outer_vars;
+        if !module.is_terminated() {
+            module.builder.build_unconditional_branch(step);
+        }
+
+        // step block
+        module.builder.position_at_end(step);
+        let next = module
+            .builder
+            .build_int_add(i, i64_ty.const_int(1, false), "For next");
+        module.builder.build_store(counter, next);
+        module.builder.build_unconditional_branch(test);
+
+        // exit block
+        module.builder.position_at_end(exit);
+        Ok(None)
+    }
+}
@@ -1,7 +1,10 @@
 use super::{CodeGen, CodeGenResult};
 use crate::{
-    ast::{self, TypeOf},
-    compiler::module::{Module, ScopeVars},
+    ast::{self, Type, TypeOf},
+    compiler::{
+        err::{CompileError, CompileResult},
+        module::{Module, ScopeVars, Var},
+    },
 };
 use inkwell::values::BasicValueEnum;
 use std::collections::HashMap;
@@ -26,19 +29,42 @@ impl<'i> CodeGen for ast::Function<'i> {
         module.builder.position_at_end(entry);
 
         // setup scope vars
-        let mut vars: HashMap<String, Option<BasicValueEnum>> = HashMap::new();
+        let mut vars: HashMap<String, Var> = HashMap::new();
         for (param, param_name) in proto.get_param_iter().zip(self.internal.params.iter()) {
-            vars.insert(param_name.ident.value.clone(), Some(param));
+            vars.insert(param_name.ident.value.clone(), Var::Value(Some(param)));
         }
 
         // scope
-        *module.function.borrow_mut() = Some(ScopeVars { proto, vars });
-        let value = self.internal.scope.code_gen(module)?.unwrap();
+        *module.function.borrow_mut() = Some(ScopeVars {
+            proto,
+            vars,
+            loops: vec![],
+        });
+        let value = self.internal.scope.code_gen(module)?;
 
         // return
-        module.builder.build_return(Some(&value));
+        build_fn_return(module, &self.type_of(), value)?;
         *module.function.borrow_mut() = None;
 
         Ok(None)
     }
 }
+
+/// returns the value of the function body, if it didn't return or jump out already
+pub(super) fn build_fn_return<'ctx>(
+    module: &Module<'ctx>,
+    ty: &Type,
+    value: Option<BasicValueEnum<'ctx>>,
+) -> CompileResult<()> {
+    if module.is_terminated() {
+        return Ok(());
+    }
+
+    match (module.basic_type(ty), value) {
+        // unit functions return void
+        (None, _) => module.builder.build_return(None),
+        (Some(_), Some(value)) => module.builder.build_return(Some(&value)),
+        (Some(_), None) => return Err(CompileError::InvalidType),
+    };
+    Ok(())
+}
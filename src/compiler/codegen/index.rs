@@ -0,0 +1,96 @@
+use super::{CodeGen, CodeGenResult};
+use crate::{
+    ast::{self, Type, TypeOf},
+    compiler::{
+        err::{CompileError, RuntimeError},
+        module::Module,
+    },
+};
+use inkwell::IntPredicate;
+
+// indexing checks the bounds first
+//
+// +---+  in bounds   +---+
+// | E |--------------| O |
+// | N |              | K |
+// | T |              +---+
+// | R |  out of bounds  +-------+
+// | Y |-----------------| error |  returns to the host
+// +---+                 +-------+
+
+impl<'i> CodeGen for ast::Index<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let array = self.term.code_gen(module)?;
+        let index = self.index.code_gen(module)?;
+        if module.is_terminated() {
+            // the array or the index jumped out with break or continue
+            return Ok(None);
+        }
+        let array = array.ok_or(CompileError::InvalidType)?.into_array_value();
+        let index = index.ok_or(CompileError::InvalidType)?.into_int_value();
+
+        let len = match self.term.type_of() {
+            Type::Array(_, len) => len,
+            _ => return Err(CompileError::InvalidType),
+        };
+
+        let proto = module
+            .function
+            .borrow()
+            .as_ref()
+            .expect("Index outside of any function?")
+            .proto;
+
+        let id = module.label_id;
+        module.label_id += 1;
+
+        let ok = module
+            .context
+            .append_basic_block(proto, &format!("Index ok {id}"));
+        let error = module
+            .context
+            .append_basic_block(proto, &format!("Index out of bounds {id}"));
+
+        // negative indices wrap around to huge unsigned ones and fail the same check
+        let i64_ty = module.context.i64_type();
+        let index = if self.index.type_of().is_signed() {
+            module
+                .builder
+                .build_int_s_extend_or_bit_cast(index, i64_ty, "Index sext")
+        } else {
+            module
+                .builder
+                .build_int_z_extend_or_bit_cast(index, i64_ty, "Index zext")
+        };
+        let len = i64_ty.const_int(len as u64, false);
+        let in_bounds =
+            module
+                .builder
+                .build_int_compare(IntPredicate::ULT, index, len, "Index in bounds");
+        module
+            .builder
+            .build_conditional_branch(in_bounds, ok, error);
+
+        // error block
+        module.builder.position_at_end(error);
+        module.build_runtime_error(RuntimeError::IndexOutOfBounds);
+
+        // ok block
+        module.builder.position_at_end(ok);
+        if module.basic_type(&self.type_of()).is_none() {
+            return Ok(None);
+        }
+
+        // arrays are indexed through memory, constant indices are folded by LLVM
+        let slot = module.build_entry_alloca(array.get_type().into(), "Index array");
+        module.builder.build_store(slot, array);
+        let zero = module.context.i64_type().const_zero();
+        let ptr = unsafe {
+            module
+                .builder
+                .build_in_bounds_gep(slot, &[zero, index], "Index element")
+        };
+
+        Ok(Some(module.builder.build_load(ptr, "Index load")))
+    }
+}
@@ -0,0 +1,214 @@
+use super::{access::load_var, function::build_fn_return, CodeGen, CodeGenResult};
+use crate::{
+    ast::{self, generic_mangle, Type, TypeOf},
+    compiler::{
+        err::CompileError,
+        module::{Module, ScopeVars, Var},
+        runtime::ENV_ALLOC,
+    },
+};
+use inkwell::{
+    values::{BasicValueEnum, FunctionValue, PointerValue},
+    AddressSpace,
+};
+use std::collections::HashMap;
+
+// lambdas are compiled into functions that get the captured environment as the first argument
+//
+//                   +----------------------+
+// captures -------> | env (in the arena)   | <------+
+//                   +----------------------+        |
+//                   +----------------------+        |
+// value:  { fn*, env* }                             |
+//           |                                       |
+//           |       +----------------------+        |
+//           +-----> | fn(env, params..)    | -------+ load captures
+//                   +----------------------+
+//
+// named functions used as values get a function
+// that ignores the environment and calls them
+
+impl<'i> CodeGen for ast::Lambda<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let (params, ret) = match self.type_of() {
+            Type::Fn(params, ret) => (params, *ret),
+            _ => return Err(CompileError::InvalidType),
+        };
+
+        let id = module.label_id;
+        module.label_id += 1;
+
+        let name = format!("Lambda {id}");
+        let proto = module
+            .module
+            .add_function(&name, module.closure_fn_type(&params, &ret), None);
+        module.functions.insert(name, proto);
+
+        // copy the captured values into the environment
+        let env_ty = module.context.struct_type(
+            &self
+                .captures
+                .iter()
+                .map(|(_, ty)| module.field_type(ty))
+                .collect::<Vec<_>>()[..],
+            false,
+        );
+        let env = if self.captures.is_empty() {
+            // nothing to allocate
+            module
+                .context
+                .i8_type()
+                .ptr_type(AddressSpace::Generic)
+                .const_null()
+        } else {
+            let mut value = env_ty.get_undef();
+            for (i, (name, _)) in self.captures.iter().enumerate() {
+                if let Some(capture) = load_var(module, name)? {
+                    value = module
+                        .builder
+                        .build_insert_value(value, capture, i as u32, &format!("Lambda {name}"))
+                        .unwrap()
+                        .into_struct_value();
+                }
+            }
+
+            let size = env_ty.size_of().unwrap();
+            let env = module
+                .builder
+                .build_call(
+                    module.module.get_function(ENV_ALLOC).unwrap(),
+                    &[size.into()],
+                    "Lambda env",
+                )
+                .try_as_basic_value()
+                .left()
+                .unwrap()
+                .into_pointer_value();
+            let ptr = module.builder.build_pointer_cast(
+                env,
+                env_ty.ptr_type(AddressSpace::Generic),
+                "Lambda env ptr",
+            );
+            module.builder.build_store(ptr, value);
+            env
+        };
+        let value = closure_value(module, proto, &params, &ret, env);
+
+        // the body is generated in its own function, then the builder returns here
+        let block = module.builder.get_insert_block().unwrap();
+        let entry = module.context.append_basic_block(proto, "entry");
+        module.builder.position_at_end(entry);
+
+        let mut vars: HashMap<String, Var> = HashMap::new();
+        if !self.captures.is_empty() {
+            let env = proto.get_first_param().unwrap().into_pointer_value();
+            let ptr = module.builder.build_pointer_cast(
+                env,
+                env_ty.ptr_type(AddressSpace::Generic),
+                "Lambda env ptr",
+            );
+            let env = module
+                .builder
+                .build_load(ptr, "Lambda env")
+                .into_struct_value();
+            for (i, (name, ty)) in self.captures.iter().enumerate() {
+                let capture = module
+                    .basic_type(ty)
+                    .and_then(|_| module.builder.build_extract_value(env, i as u32, name));
+                vars.insert(name.clone(), Var::Value(capture));
+            }
+        }
+        for (param, param_name) in proto.get_param_iter().skip(1).zip(self.params.iter()) {
+            vars.insert(param_name.ident.value.clone(), Var::Value(Some(param)));
+        }
+
+        let outer = module.function.replace(Some(ScopeVars {
+            proto,
+            vars,
+            loops: vec![],
+        }));
+        let result = self
+            .scope
+            .code_gen(module)
+            .and_then(|value| build_fn_return(module, &ret, value));
+        *module.function.borrow_mut() = outer;
+        module.builder.position_at_end(block);
+        result?;
+
+        Ok(Some(value))
+    }
+}
+
+/// the function `name` with `params` as a value
+pub(super) fn code_gen_fn_value<'ctx>(
+    module: &mut Module<'ctx>,
+    name: &str,
+    params: &[Type],
+) -> CodeGenResult<'ctx> {
+    let mangled = generic_mangle(params, name);
+    let function = *module
+        .functions
+        .get(&mangled)
+        .ok_or(CompileError::FuncNotFound)?;
+    let ret = module.returns[&mangled].clone();
+
+    // one function per named function, that skips the environment
+    let name = format!("Fn value {mangled}");
+    let proto = match module.functions.get(&name) {
+        Some(&proto) => proto,
+        None => {
+            let proto =
+                module
+                    .module
+                    .add_function(&name, module.closure_fn_type(params, &ret), None);
+            module.functions.insert(name, proto);
+
+            let block = module.builder.get_insert_block().unwrap();
+            let entry = module.context.append_basic_block(proto, "entry");
+            module.builder.position_at_end(entry);
+
+            let args: Vec<BasicValueEnum> = proto.get_param_iter().skip(1).collect();
+            let value = module
+                .builder
+                .build_call(function, &args[..], "Fn value call")
+                .try_as_basic_value()
+                .left();
+            // runtime errors are checked by the caller of the value
+            match value {
+                Some(value) => module.builder.build_return(Some(&value)),
+                None => module.builder.build_return(None),
+            };
+
+            module.builder.position_at_end(block);
+            proto
+        }
+    };
+
+    let env = module
+        .context
+        .i8_type()
+        .ptr_type(AddressSpace::Generic)
+        .const_null();
+    Ok(Some(closure_value(module, proto, params, &ret, env)))
+}
+
+fn closure_value<'ctx>(
+    module: &Module<'ctx>,
+    proto: FunctionValue<'ctx>,
+    params: &[Type],
+    ret: &Type,
+    env: PointerValue<'ctx>,
+) -> BasicValueEnum<'ctx> {
+    let ptr = proto.as_global_value().as_pointer_value();
+    let value = module.closure_type(params, ret).get_undef();
+    let value = module
+        .builder
+        .build_insert_value(value, ptr, 0, "Fn value ptr")
+        .unwrap();
+    module
+        .builder
+        .build_insert_value(value, env, 1, "Fn value env")
+        .unwrap()
+        .into_struct_value()
+        .into()
+}
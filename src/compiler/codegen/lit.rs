@@ -1,15 +1,28 @@
 use super::{CodeGen, CodeGenResult};
-use crate::{ast, compiler::module::Module};
+use crate::{
+    ast::{self, TypeOf},
+    compiler::module::Module,
+};
+use inkwell::{module::Linkage, AddressSpace};
 
 //
 
 impl CodeGen for ast::Lit {
     fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
         match self {
+            ast::Lit::F32(v) => Ok(Some(
+                module.context.f32_type().const_float(*v as f64).into(),
+            )),
             ast::Lit::F64(v) => v.code_gen(module),
             ast::Lit::I64(v) => v.code_gen(module),
             ast::Lit::Bool(v) => v.code_gen(module),
+            ast::Lit::Str(v) => v.code_gen(module),
             ast::Lit::Unit(v) => v.code_gen(module),
+            lit => {
+                let ty = module.basic_type(&lit.type_of()).unwrap().into_int_type();
+                let v = lit.as_int().unwrap();
+                Ok(Some(ty.const_int(v as u64, v < 0).into()))
+            }
         }
     }
 }
@@ -44,6 +57,35 @@ impl CodeGen for bool {
     }
 }
 
+impl CodeGen for String {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        // the bytes are a constant that lives as long as the module
+        let bytes = module.context.const_string(self.as_bytes(), false);
+        let global = module.module.add_global(
+            module.context.i8_type().array_type(self.len() as u32),
+            None,
+            "Str lit",
+        );
+        global.set_initializer(&bytes);
+        global.set_constant(true);
+        global.set_linkage(Linkage::Private);
+
+        let ptr = global
+            .as_pointer_value()
+            .const_cast(module.context.i8_type().ptr_type(AddressSpace::Generic));
+        let len = module
+            .context
+            .i64_type()
+            .const_int(self.len() as u64, false);
+        Ok(Some(
+            module
+                .context
+                .const_struct(&[ptr.into(), len.into()], false)
+                .into(),
+        ))
+    }
+}
+
 impl CodeGen for () {
     fn code_gen<'ctx>(&self, _: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
         Ok(None)
@@ -0,0 +1,118 @@
+use super::{CodeGen, CodeGenResult};
+use crate::{
+    ast::{self, Type, TypeOf},
+    compiler::{
+        err::{CompileError, CompileResult},
+        module::{LoopBlocks, Module},
+    },
+};
+use inkwell::{basic_block::BasicBlock, values::BasicValueEnum};
+
+// loops contain 2 blocks
+//
+//             +---+  break   +---+
+// +---+       | B |----------| E |
+// | E |-------| O |          | X |
+// | N |       | D |          | I |
+// | T |    +--| Y |          | T |
+// | R |    |  +---+          +---+
+// | Y |    |    |
+// +---+    +----+
+//         continue
+
+impl<'i> CodeGen for ast::Loop<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let function = module.function.clone();
+        let function_ref = function.borrow();
+        let function = function_ref
+            .as_ref()
+            .expect("Loop outside of any function?");
+
+        let id = module.label_id;
+        module.label_id += 1;
+
+        // one block to repeat
+        let body = module
+            .context
+            .append_basic_block(function.proto, &format!("Loop body {id}"));
+        // and one block to enter after breaking out of it
+        let exit = module
+            .context
+            .append_basic_block(function.proto, &format!("Loop exit {id}"));
+
+        drop(function_ref);
+        module.builder.build_unconditional_branch(body);
+
+        // body block
+        module.builder.position_at_end(body);
+        let breaks = loop_body(module, &self.scope, body, exit)?;
+        if !module.is_terminated() {
+            module.builder.build_unconditional_branch(body);
+        }
+
+        // exit block
+        loop_exit(module, &self.type_of(), exit, breaks, id)
+    }
+}
+
+/// generates the loop body with `continue` jumping to `repeat` and `break` jumping to `exit`,
+/// returns all values the loop was broken out with
+pub(super) fn loop_body<'ctx>(
+    module: &mut Module<'ctx>,
+    scope: &ast::Scope,
+    repeat: BasicBlock<'ctx>,
+    exit: BasicBlock<'ctx>,
+) -> CompileResult<Vec<(Option<BasicValueEnum<'ctx>>, BasicBlock<'ctx>)>> {
+    module
+        .function
+        .borrow_mut()
+        .as_mut()
+        .expect("Loop outside of any function?")
+        .loops
+        .push(LoopBlocks {
+            repeat,
+            exit,
+            breaks: vec![],
+        });
+
+    scope.code_gen(module)?;
+
+    let blocks = module
+        .function
+        .borrow_mut()
+        .as_mut()
+        .expect("Loop outside of any function?")
+        .loops
+        .pop()
+        .unwrap();
+
+    Ok(blocks.breaks)
+}
+
+/// merges the values of all `break`s in the exit block
+pub(super) fn loop_exit<'ctx>(
+    module: &mut Module<'ctx>,
+    ty: &Type,
+    exit: BasicBlock<'ctx>,
+    breaks: Vec<(Option<BasicValueEnum<'ctx>>, BasicBlock<'ctx>)>,
+    id: u32,
+) -> CodeGenResult<'ctx> {
+    if breaks.is_empty() {
+        // nothing breaks out of the loop
+        module.delete_unreachable_block(exit);
+        return Ok(None);
+    }
+
+    module.builder.position_at_end(exit);
+
+    let ty = match module.basic_type(ty) {
+        Some(ty) => ty,
+        None => return Ok(None),
+    };
+
+    let phi = module.builder.build_phi(ty, &format!("Loop phi {id}"));
+    for (value, block) in breaks {
+        phi.add_incoming(&[(&value.ok_or(CompileError::InvalidType)?, block)]);
+    }
+    Ok(Some(phi.as_basic_value()))
+}
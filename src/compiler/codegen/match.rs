@@ -0,0 +1,194 @@
+use super::{
+    pattern::{code_gen_bindings, code_gen_test},
+    CodeGen, CodeGenResult,
+};
+use crate::{
+    ast::{self, PatternKind, Type, TypeOf},
+    compiler::{
+        err::{CompileError, CompileResult},
+        module::Module,
+    },
+};
+use inkwell::{
+    basic_block::BasicBlock,
+    values::{BasicValueEnum, IntValue},
+};
+
+// matches on enums switch on the tag first,
+// each variant then tests the arms that can match it in order
+//
+//            +---------+  test   +-------+        +---+
+// +---+  A   | variant |---------| arm 0 |--------| C |
+// | E |------| A       |--+      +-------+        | O |
+// | N |      +---------+  |                       | N |
+// | T |                   |      +-------+        | T |
+// | R |  B   +---------+  +------| arm 1 |--------| I |
+// | Y |------| variant |---------|       |        | N |
+// +---+      | B       |         +-------+        | U |
+//            +---------+                          | E |
+//                                                 +---+
+//
+// matches on anything else only test the arms in order
+
+impl<'i> CodeGen for ast::Match<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let value = self.internal.expr.code_gen(module)?;
+        if module.is_terminated() {
+            // the matched expression jumped out with break or continue
+            return Ok(None);
+        }
+
+        let proto = module
+            .function
+            .borrow()
+            .as_ref()
+            .expect("Match outside of any function?")
+            .proto;
+
+        let id = module.label_id;
+        module.label_id += 1;
+
+        // one block for each arm
+        let arms: Vec<BasicBlock> = (0..self.internal.arms.len())
+            .map(|i| {
+                module
+                    .context
+                    .append_basic_block(proto, &format!("Match arm {i} {id}"))
+            })
+            .collect();
+        // one block for values the exhaustive arms cannot miss
+        let unreachable = module
+            .context
+            .append_basic_block(proto, &format!("Match unreachable {id}"));
+        // and one last block to enter after exiting from any of the arms
+        let r#continue = module
+            .context
+            .append_basic_block(proto, &format!("Match continue {id}"));
+
+        match self.internal.expr.type_of() {
+            Type::Named(name) if !module.struct_decls.contains_key(&name) => {
+                let value = value.ok_or(CompileError::InvalidType)?.into_struct_value();
+                let tag = module
+                    .builder
+                    .build_extract_value(value, 0, "Match tag")
+                    .unwrap()
+                    .into_int_value();
+
+                // one block for each variant
+                let variants = module.variants[name.as_str()].len();
+                let cases: Vec<(IntValue, BasicBlock)> = (0..variants)
+                    .map(|i| {
+                        let tag = module.context.i32_type().const_int(i as u64, false);
+                        let block = module
+                            .context
+                            .append_basic_block(proto, &format!("Match variant {i} {id}"));
+                        (tag, block)
+                    })
+                    .collect();
+                module.builder.build_switch(tag, unreachable, &cases[..]);
+
+                for (i, (_, block)) in cases.into_iter().enumerate() {
+                    module.builder.position_at_end(block);
+                    let candidates =
+                        self.internal
+                            .arms
+                            .iter()
+                            .zip(arms.iter())
+                            .filter(|(arm, _)| match &arm.pattern.kind {
+                                PatternKind::Variant { index, .. } => *index == i,
+                                _ => true,
+                            });
+                    code_gen_tests(module, id, Some(value.into()), candidates, unreachable)?;
+                }
+            }
+            _ => {
+                let candidates = self.internal.arms.iter().zip(arms.iter());
+                code_gen_tests(module, id, value, candidates, unreachable)?;
+            }
+        }
+
+        module.builder.position_at_end(unreachable);
+        module.builder.build_unreachable();
+
+        // arm blocks
+        let mut results = vec![];
+        for (arm, &block) in self.internal.arms.iter().zip(arms.iter()) {
+            module.builder.position_at_end(block);
+
+            // bindings shadow the outer variables only until the end of the arm
+            let outer_vars = module
+                .function
+                .borrow()
+                .as_ref()
+                .expect("Match outside of any function?")
+                .vars
+                .clone();
+            code_gen_bindings(module, &arm.pattern, value);
+            let result = arm.scope.code_gen(module)?;
+            module
+                .function
+                .borrow_mut()
+                .as_mut()
+                .expect("Match outside of any function?")
+                .vars = outer_vars;
+
+            let block = module.builder.get_insert_block().unwrap(); // because the arm codegen can make new blocks, we need to get the 'last' one
+            if !module.is_terminated() {
+                // the arm might have jumped out with break or continue
+                module.builder.build_unconditional_branch(r#continue);
+                results.push((result, block));
+            }
+        }
+
+        if results.is_empty() {
+            // none of the arms reach the continue block
+            module.delete_unreachable_block(r#continue);
+            return Ok(None);
+        }
+
+        // continue block
+        module.builder.position_at_end(r#continue);
+
+        let ty = match module.basic_type(&self.type_of()) {
+            Some(ty) => ty,
+            None => return Ok(None),
+        };
+
+        let phi = module.builder.build_phi(ty, &format!("Match phi {id}"));
+        for (result, block) in results {
+            phi.add_incoming(&[(&result.ok_or(CompileError::InvalidType)?, block)]);
+        }
+        Ok(Some(phi.as_basic_value()))
+    }
+}
+
+/// tests `candidates` in order starting from the current block
+/// and jumps to the first arm that matches `value`
+fn code_gen_tests<'a, 'i: 'a, 'ctx: 'a>(
+    module: &mut Module<'ctx>,
+    id: u32,
+    value: Option<BasicValueEnum<'ctx>>,
+    candidates: impl Iterator<Item = (&'a ast::MatchArm<'i>, &'a BasicBlock<'ctx>)>,
+    unreachable: BasicBlock<'ctx>,
+) -> CompileResult<()> {
+    for (arm, &block) in candidates {
+        match code_gen_test(module, &arm.pattern, value)? {
+            Some(cond) => {
+                let next = module.context.insert_basic_block_after(
+                    module.builder.get_insert_block().unwrap(),
+                    &format!("Match test {id}"),
+                );
+                module.builder.build_conditional_branch(cond, block, next);
+                module.builder.position_at_end(next);
+            }
+            None => {
+                // the arm matches everything left
+                module.builder.build_unconditional_branch(block);
+                return Ok(());
+            }
+        }
+    }
+
+    module.builder.build_unconditional_branch(unreachable);
+    Ok(())
+}
@@ -3,33 +3,38 @@ use inkwell::values::BasicValueEnum;
 
 //
 
-pub use self::access::*;
-pub use self::assign::*;
-pub use self::binary::*;
-pub use self::branch::*;
-pub use self::call::*;
-pub use self::expr::*;
-pub use self::function::*;
-pub use self::lit::*;
-pub use self::module::*;
-pub use self::scope::*;
-pub use self::statement::*;
-pub use self::term::*;
-pub use self::unary::*;
-
 pub mod access;
+pub mod array_lit;
 pub mod assign;
 pub mod binary;
 pub mod branch;
+pub mod r#break;
 pub mod call;
+pub mod cast;
+pub mod r#continue;
+pub mod enum_lit;
 pub mod expr;
+pub mod field_access;
+pub mod r#for;
 pub mod function;
+pub mod index;
+pub mod lambda;
 pub mod lit;
+pub mod r#loop;
+pub mod r#match;
 pub mod module;
+pub mod pattern;
+pub mod reassign;
+pub mod r#return;
 pub mod scope;
 pub mod statement;
+pub mod r#static;
+pub mod struct_lit;
 pub mod term;
+pub mod tuple_lit;
 pub mod unary;
+pub mod r#while;
+pub mod wrapper;
 
 //
 
@@ -1,9 +1,16 @@
-use inkwell::types::BasicTypeEnum;
+use inkwell::types::{BasicType, BasicTypeEnum, StructType};
 
-use super::{CodeGen, CodeGenResult};
+use super::{
+    r#static::{code_gen_static_accessors, code_gen_static_init, declare_statics},
+    wrapper::code_gen_wrapper,
+    CodeGen, CodeGenResult,
+};
 use crate::{
-    ast::{self, Type, TypeOf},
-    compiler::module::Module,
+    ast::{self, TypeOf},
+    compiler::{
+        err::{CompileError, CompileResult},
+        module::{Module, RUNTIME_ERROR},
+    },
 };
 
 //
@@ -12,67 +19,175 @@ impl<'i> CodeGen for ast::Module<'i> {
     fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
         log::debug!("compiling module");
 
-        // first compile all function prototypes
+        // status of the last call from host code, set by failed runtime checks
+        let i32_ty = module.context.i32_type();
+        let runtime_error = module.module.add_global(i32_ty, None, RUNTIME_ERROR);
+        runtime_error.set_initializer(&i32_ty.const_zero());
+
+        // declare all structs and enums before any of them is used as a field type
+
+        for s in self.structs.values() {
+            let name = s.name.value.as_str();
+            let ty = module.context.opaque_struct_type(name);
+            module.structs.insert(name.into(), ty);
 
-        for function in self.functions.values() {
-            let name = function.internal.name.value.clone();
-            let ty = function.type_of();
+            let fields = s
+                .fields
+                .iter()
+                .map(|field| (field.name.value.clone(), field.ty.clone()))
+                .collect();
+            module.struct_decls.insert(name.into(), fields);
+        }
+        for e in self.enums.values() {
+            let name = e.name.value.as_str();
+            let ty = module.context.opaque_struct_type(name);
+            module.structs.insert(name.into(), ty);
+        }
+        for s in self.structs.values() {
+            let fields: Vec<BasicTypeEnum> = s
+                .fields
+                .iter()
+                .map(|field| module.field_type(&field.ty))
+                .collect();
+            module.structs[s.name.value.as_str()].set_body(&fields[..], false);
+        }
+        // enums are tagged unions, the tag followed by storage for the largest variant,
+        // so an enum is laid out after the enums its variants hold
+        let mut enums: Vec<&ast::Enum> = self.enums.values().collect();
+        while !enums.is_empty() {
+            let (sized, rest): (Vec<&ast::Enum>, _) = enums.into_iter().partition(|e| {
+                e.variants
+                    .iter()
+                    .flat_map(|variant| variant.fields.iter())
+                    .all(|field| module.field_type(field).is_sized())
+            });
+            assert!(!sized.is_empty(), "Recursive enum?");
+            for e in sized {
+                declare_enum(module, e);
+            }
+            enums = rest;
+        }
+
+        // statics before the functions that use them
+        declare_statics(module, &self.statics);
+
+        let functions: Vec<_> = self.functions.values().collect();
+        code_gen_functions(module, &functions)?;
+
+        code_gen_static_init(module, &self.statics)?;
+        code_gen_static_accessors(module, &self.statics);
+
+        Ok(None)
+    }
+}
 
-            let params: Vec<BasicTypeEnum> = function
-                .internal
-                .params
+/// sets the body of the enum `e` to `{ i32, [n x iA] }`, the tag and storage
+/// the size and alignment of its largest variant, see [`Module::variants`]
+fn declare_enum(module: &mut Module, e: &ast::Enum) {
+    let payloads: Vec<StructType> = e
+        .variants
+        .iter()
+        .map(|variant| {
+            let fields: Vec<BasicTypeEnum> = variant
+                .fields
                 .iter()
-                .map(|param| match param.ty {
-                    Type::F64 => module.context.f64_type().into(),
-                    Type::I64 => module.context.i64_type().into(),
-                    Type::U64 => module.context.i64_type().into(),
-                    Type::Bool => module.context.bool_type().into(),
-                    Type::Unit | Type::Unresolved => unreachable!(),
-                })
+                .map(|field| module.field_type(field))
                 .collect();
+            module.context.struct_type(&fields[..], false)
+        })
+        .collect();
 
-            let fn_ty = match ty {
-                Type::F64 => module.context.f64_type().fn_type(&params[..], false),
-                Type::U64 => module.context.i64_type().fn_type(&params[..], false),
-                Type::I64 => module.context.i64_type().fn_type(&params[..], false),
-                Type::Bool => module.context.bool_type().fn_type(&params[..], false),
-                Type::Unit => module.context.void_type().fn_type(&params[..], false),
-                Type::Unresolved => unreachable!(),
-            };
-
-            log::debug!("compiling proto: '{}' -> {:?}", name, ty);
-            let proto = module.module.add_function(&name, fn_ty, None);
-            for (param, param_name) in proto.get_param_iter().zip(function.internal.params.iter()) {
-                match param {
-                    inkwell::values::BasicValueEnum::ArrayValue(v) => {
-                        v.set_name(param_name.ident.value.as_str())
-                    }
-                    inkwell::values::BasicValueEnum::IntValue(v) => {
-                        v.set_name(param_name.ident.value.as_str())
-                    }
-                    inkwell::values::BasicValueEnum::FloatValue(v) => {
-                        v.set_name(param_name.ident.value.as_str())
-                    }
-                    inkwell::values::BasicValueEnum::PointerValue(v) => {
-                        v.set_name(param_name.ident.value.as_str())
-                    }
-                    inkwell::values::BasicValueEnum::StructValue(v) => {
-                        v.set_name(param_name.ident.value.as_str())
-                    }
-                    inkwell::values::BasicValueEnum::VectorValue(v) => {
-                        v.set_name(param_name.ident.value.as_str())
-                    }
+    let target_data = module.target_data();
+    let size = payloads
+        .iter()
+        .map(|ty| target_data.get_abi_size(ty))
+        .max()
+        .unwrap_or(0);
+    let align = payloads
+        .iter()
+        .map(|ty| target_data.get_abi_alignment(ty))
+        .max()
+        .unwrap_or(1);
+    let storage = module
+        .context
+        .custom_width_int_type(align * 8)
+        .array_type(size.div_ceil(align as u64) as u32);
+
+    let name = e.name.value.as_str();
+    let tag = module.context.i32_type();
+    module.structs[name].set_body(&[tag.into(), storage.into()], false);
+    module.variants.insert(name.into(), payloads);
+}
+
+/// compiles the prototypes of `functions` first, so they can call each other,
+/// then their bodies and the wrappers host code calls them with
+pub(in crate::compiler) fn code_gen_functions<'ctx>(
+    module: &mut Module<'ctx>,
+    functions: &[&ast::Function],
+) -> CompileResult<()> {
+    // first compile all function prototypes
+
+    for function in functions.iter() {
+        let name = function.internal.name.value.clone();
+        let ty = function.type_of();
+
+        let params: Vec<BasicTypeEnum> = function
+            .internal
+            .params
+            .iter()
+            .map(|param| {
+                // unit params are rejected by the type checker
+                module
+                    .basic_type(&param.ty)
+                    .ok_or(CompileError::InvalidType)
+            })
+            .collect::<Result<_, _>>()?;
+
+        let fn_ty = match module.basic_type(&ty) {
+            Some(ty) => ty.fn_type(&params[..], false),
+            None => module.context.void_type().fn_type(&params[..], false),
+        };
+
+        log::debug!("compiling proto: '{}' -> {:?}", name, ty);
+        let proto = module.module.add_function(&name, fn_ty, None);
+        for (param, param_name) in proto.get_param_iter().zip(function.internal.params.iter()) {
+            match param {
+                inkwell::values::BasicValueEnum::ArrayValue(v) => {
+                    v.set_name(param_name.ident.value.as_str())
+                }
+                inkwell::values::BasicValueEnum::IntValue(v) => {
+                    v.set_name(param_name.ident.value.as_str())
+                }
+                inkwell::values::BasicValueEnum::FloatValue(v) => {
+                    v.set_name(param_name.ident.value.as_str())
+                }
+                inkwell::values::BasicValueEnum::PointerValue(v) => {
+                    v.set_name(param_name.ident.value.as_str())
+                }
+                inkwell::values::BasicValueEnum::StructValue(v) => {
+                    v.set_name(param_name.ident.value.as_str())
+                }
+                inkwell::values::BasicValueEnum::VectorValue(v) => {
+                    v.set_name(param_name.ident.value.as_str())
                 }
             }
-            module.functions.insert(name, proto);
         }
+        module.returns.insert(name.clone(), ty);
+        module.functions.insert(name, proto);
+    }
 
-        // and then compile all function bodies
+    // and then compile all function bodies
 
-        for function in self.functions.values() {
-            function.code_gen(module)?;
-        }
+    for function in functions.iter() {
+        function.code_gen(module)?;
+    }
 
-        Ok(None)
+    // and the wrappers host code calls them with
+
+    for function in functions.iter() {
+        let proto = module.functions[function.internal.name.value.as_str()];
+        code_gen_wrapper(module, function, proto)?;
     }
+
+    Ok(())
 }
@@ -0,0 +1,169 @@
+use super::{enum_lit::payload_ptr, CodeGen};
+use crate::{
+    ast::{self, PatternKind, Type},
+    compiler::{
+        err::{CompileError, CompileResult},
+        module::{Module, Var},
+    },
+};
+use inkwell::{
+    values::{BasicValueEnum, IntValue, StructValue},
+    IntPredicate,
+};
+
+//
+
+/// the condition for `pattern` to match `value`, `None` if it always matches
+pub(super) fn code_gen_test<'ctx>(
+    module: &mut Module<'ctx>,
+    pattern: &ast::Pattern,
+    value: Option<BasicValueEnum<'ctx>>,
+) -> CompileResult<Option<IntValue<'ctx>>> {
+    let value = match (&pattern.kind, value) {
+        (PatternKind::Wildcard | PatternKind::Binding { .. }, _) => return Ok(None),
+        (_, Some(value)) => value,
+        (_, None) => return Err(CompileError::InvalidType),
+    };
+
+    let cond = match &pattern.kind {
+        PatternKind::Lit(lit) => {
+            let lit = lit.code_gen(module)?.unwrap().into_int_value();
+            module.builder.build_int_compare(
+                IntPredicate::EQ,
+                value.into_int_value(),
+                lit,
+                "Pattern lit",
+            )
+        }
+        PatternKind::Variant {
+            name,
+            fields,
+            index,
+            ..
+        } => {
+            let value = value.into_struct_value();
+            let tag = module
+                .builder
+                .build_extract_value(value, 0, "Pattern tag")
+                .unwrap()
+                .into_int_value();
+            let expect = module.context.i32_type().const_int(*index as u64, false);
+            let mut cond =
+                module
+                    .builder
+                    .build_int_compare(IntPredicate::EQ, tag, expect, "Pattern variant");
+
+            for (i, field) in fields.iter().enumerate() {
+                let field_value = variant_field(module, &name.value, value, *index, i, field.ty());
+                if let Some(field_cond) = code_gen_test(module, field, field_value)? {
+                    cond = module.builder.build_and(cond, field_cond, "Pattern and");
+                }
+            }
+            cond
+        }
+        PatternKind::Tuple(fields) => {
+            let value = value.into_struct_value();
+            let mut cond = module.context.bool_type().const_int(1, false);
+            for (i, field) in fields.iter().enumerate() {
+                let field_value = tuple_field(module, value, i, field.ty());
+                if let Some(field_cond) = code_gen_test(module, field, field_value)? {
+                    cond = module.builder.build_and(cond, field_cond, "Pattern and");
+                }
+            }
+            cond
+        }
+        PatternKind::Wildcard | PatternKind::Binding { .. } => unreachable!(),
+    };
+
+    Ok(Some(cond))
+}
+
+/// declares the variables `pattern` binds to parts of `value`
+pub(super) fn code_gen_bindings<'ctx>(
+    module: &mut Module<'ctx>,
+    pattern: &ast::Pattern,
+    value: Option<BasicValueEnum<'ctx>>,
+) {
+    match (&pattern.kind, value) {
+        (PatternKind::Binding { name, mutable }, value) => {
+            let var = match value {
+                Some(value) if *mutable => {
+                    let slot = module.build_entry_alloca(value.get_type(), name.value.as_str());
+                    module.builder.build_store(slot, value);
+                    Var::Slot(slot)
+                }
+                value => Var::Value(value),
+            };
+            module
+                .function
+                .borrow_mut()
+                .as_mut()
+                .expect("Pattern outside of any function?")
+                .vars
+                .insert(name.value.clone(), var);
+        }
+        (PatternKind::Tuple(fields), Some(value)) => {
+            for (i, field) in fields.iter().enumerate() {
+                let field_value = tuple_field(module, value.into_struct_value(), i, field.ty());
+                code_gen_bindings(module, field, field_value);
+            }
+        }
+        (
+            PatternKind::Variant {
+                name,
+                fields,
+                index,
+                ..
+            },
+            Some(value),
+        ) => {
+            for (i, field) in fields.iter().enumerate() {
+                let field_value = variant_field(
+                    module,
+                    &name.value,
+                    value.into_struct_value(),
+                    *index,
+                    i,
+                    field.ty(),
+                );
+                code_gen_bindings(module, field, field_value);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// field `field` of variant `variant` of the enum `name`, `None` for fields without a value
+fn variant_field<'ctx>(
+    module: &Module<'ctx>,
+    name: &str,
+    value: StructValue<'ctx>,
+    variant: usize,
+    field: usize,
+    ty: &Type,
+) -> Option<BasicValueEnum<'ctx>> {
+    module.basic_type(ty)?;
+    let slot = module.build_entry_alloca(value.get_type().into(), "Pattern enum");
+    module.builder.build_store(slot, value);
+    let payload_ptr = payload_ptr(module, name, slot, variant);
+    let payload = module
+        .builder
+        .build_load(payload_ptr, "Pattern payload")
+        .into_struct_value();
+    module
+        .builder
+        .build_extract_value(payload, field as u32, "Pattern field")
+}
+
+/// field `field` of a tuple, `None` for fields without a value
+fn tuple_field<'ctx>(
+    module: &Module<'ctx>,
+    value: StructValue<'ctx>,
+    field: usize,
+    ty: &Type,
+) -> Option<BasicValueEnum<'ctx>> {
+    module.basic_type(ty)?;
+    module
+        .builder
+        .build_extract_value(value, field as u32, "Pattern field")
+}
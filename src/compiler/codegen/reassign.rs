@@ -0,0 +1,56 @@
+use super::{
+    access::host_global_ptr, r#static::static_ptr, wrapper::store, CodeGen, CodeGenResult,
+};
+use crate::{
+    ast::{self, TypeOf},
+    compiler::{
+        err::CompileError,
+        module::{Module, Var},
+    },
+};
+
+//
+
+impl<'i> CodeGen for ast::Reassign<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let value = self.expr.code_gen(module)?;
+
+        let var = match module
+            .function
+            .borrow()
+            .as_ref()
+            .expect("Reassign outside of any function?")
+            .vars
+            .get(self.name.value.as_str())
+        {
+            Some(&var) => var,
+            None if module.statics.contains_key(self.name.value.as_str()) => {
+                if let (Some(ptr), Some(value)) = (static_ptr(module, &self.name.value), value) {
+                    module.builder.build_store(ptr, value);
+                }
+                return Ok(None);
+            }
+            None if module.host_globals.contains_key(self.name.value.as_str()) => {
+                // values that never arrive have nothing to store
+                if let Some(value) = value {
+                    let ty = self.expr.type_of();
+                    let ptr = host_global_ptr(module, &self.name.value, &ty, true)?;
+                    store(module, ptr, 0, &ty, value);
+                }
+                return Ok(None);
+            }
+            None => return Err(CompileError::VarNotFound),
+        };
+
+        match (var, value) {
+            (Var::Slot(slot), Some(value)) => {
+                module.builder.build_store(slot, value);
+            }
+            // variables without a value have nothing to store
+            (Var::Value(None), _) | (_, None) => {}
+            (Var::Value(Some(_)), Some(_)) => return Err(CompileError::InvalidType),
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,22 @@
+use super::{CodeGen, CodeGenResult};
+use crate::{ast, compiler::module::Module};
+use inkwell::values::BasicValue;
+
+//
+
+impl<'i> CodeGen for ast::Return<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let value = match self.expr.as_ref() {
+            Some(expr) => expr.code_gen(module)?,
+            None => None,
+        };
+        if module.is_terminated() {
+            return Ok(None);
+        }
+
+        module
+            .builder
+            .build_return(value.as_ref().map(|value| value as &dyn BasicValue));
+        Ok(None)
+    }
+}
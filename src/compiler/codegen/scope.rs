@@ -5,10 +5,39 @@ use crate::{ast, compiler::module::Module};
 
 impl<'i> CodeGen for ast::Scope<'i> {
     fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
-        self.statements
-            .iter()
-            .map(|stmt| stmt.code_gen(module))
-            .last()
-            .unwrap_or(Ok(None))
+        // variables declared in this scope shadow the outer
+        // ones only until the end of this scope
+        let outer_vars = module
+            .function
+            .borrow()
+            .as_ref()
+            .expect("Scope outside of any function?")
+            .vars
+            .clone();
+
+        let mut value = None;
+        for stmt in self.statements.iter() {
+            // everything after a break, continue or
+            // any other jump is unreachable
+            if module.is_terminated() {
+                value = None;
+                break;
+            }
+            value = stmt.code_gen(module)?;
+        }
+
+        module
+            .function
+            .borrow_mut()
+            .as_mut()
+            .expect("Scope outside of any function?")
+            .vars = outer_vars;
+
+        // `{ a; b; }` has no value
+        if self.trailing_semi {
+            value = None;
+        }
+
+        Ok(value)
     }
 }
@@ -8,6 +8,7 @@ impl<'i> CodeGen for ast::Statement<'i> {
         match self.internal.as_ref() {
             ast::StatementInternal::Expr(expr) => expr.code_gen(module),
             ast::StatementInternal::Assign(assign) => assign.code_gen(module),
+            ast::StatementInternal::Reassign(reassign) => reassign.code_gen(module),
         }
     }
 }
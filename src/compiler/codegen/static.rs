@@ -0,0 +1,153 @@
+use super::wrapper::{is_passable, load, store, wrapper_fn_type};
+use crate::{
+    ast::{self, generic_mangle, TypeOf},
+    compiler::{
+        err::CompileResult,
+        module::{
+            static_getter_name, static_global_name, static_setter_name, Module, INIT_STATICS,
+        },
+    },
+};
+use inkwell::{
+    types::BasicTypeEnum,
+    values::{BasicValueEnum, PointerValue},
+    IntPredicate,
+};
+
+// statics live in LLVM globals, zero until `__init_statics` runs the initializers
+//
+// host code reads and writes them through accessors called like wrappers:
+// `i32 __get_static{name}(i8*, i8* ret)` and `i32 __set_static{name}(i8* value, i8*)`
+
+/// declares the globals of `statics`, statics without a value get none
+pub(super) fn declare_statics(module: &mut Module, statics: &[ast::Static]) {
+    for s in statics.iter() {
+        let name = s.name.value.as_str();
+        let ty = s.type_of();
+        if let Some(basic_ty) = module.basic_type(&ty) {
+            let global = module
+                .module
+                .add_global(basic_ty, None, &static_global_name(name));
+            global.set_initializer(&const_zero(basic_ty));
+        }
+        module.statics.insert(name.into(), (ty, s.mutable));
+    }
+}
+
+/// the global the static `name` lives in, `None` if the static has no value
+pub(super) fn static_ptr<'ctx>(module: &Module<'ctx>, name: &str) -> Option<PointerValue<'ctx>> {
+    module
+        .module
+        .get_global(&static_global_name(name))
+        .map(|global| global.as_pointer_value())
+}
+
+/// `i32 __init_statics(i8*, i8*)` calls the initializers in the order the statics
+/// are declared, it stops at the first one that raises an error and returns its code
+pub(super) fn code_gen_static_init<'ctx>(
+    module: &mut Module<'ctx>,
+    statics: &[ast::Static],
+) -> CompileResult<()> {
+    let proto = module
+        .module
+        .add_function(INIT_STATICS, wrapper_fn_type(module), None);
+    let entry = module.context.append_basic_block(proto, "entry");
+    let raised = module
+        .context
+        .append_basic_block(proto, "Static init error");
+    module.builder.position_at_end(entry);
+
+    let runtime_error = module.runtime_error();
+    let zero = module.context.i32_type().const_zero();
+    module.builder.build_store(runtime_error, zero);
+
+    for s in statics.iter() {
+        let name = s.name.value.as_str();
+        let init = module.functions[&generic_mangle(&[], &ast::Static::init_name(name))];
+        let value = module
+            .builder
+            .build_call(init, &[], "Static init")
+            .try_as_basic_value()
+            .left();
+
+        let code = module
+            .builder
+            .build_load(runtime_error, "Static init code")
+            .into_int_value();
+        let cond =
+            module
+                .builder
+                .build_int_compare(IntPredicate::NE, code, zero, "Static init raised");
+        let ok = module.context.append_basic_block(proto, "Static init ok");
+        module.builder.build_conditional_branch(cond, raised, ok);
+        module.builder.position_at_end(ok);
+
+        if let (Some(ptr), Some(value)) = (static_ptr(module, name), value) {
+            module.builder.build_store(ptr, value);
+        }
+    }
+    module.builder.build_return(Some(&zero));
+
+    module.builder.position_at_end(raised);
+    let code = module.builder.build_load(runtime_error, "Static init code");
+    module.builder.build_return(Some(&code));
+
+    module.functions.insert(INIT_STATICS.into(), proto);
+
+    Ok(())
+}
+
+/// the accessors of `statics` the host can pass values of, immutable statics have no setter
+pub(super) fn code_gen_static_accessors(module: &mut Module, statics: &[ast::Static]) {
+    let zero = module.context.i32_type().const_zero();
+    for s in statics.iter() {
+        let name = s.name.value.as_str();
+        let ty = s.type_of();
+        if !is_passable(module, &ty) {
+            continue;
+        }
+
+        let getter_name = static_getter_name(name);
+        let getter = module
+            .module
+            .add_function(&getter_name, wrapper_fn_type(module), None);
+        let entry = module.context.append_basic_block(getter, "entry");
+        module.builder.position_at_end(entry);
+        if let Some(ptr) = static_ptr(module, name) {
+            let ret = getter.get_nth_param(1).unwrap().into_pointer_value();
+            let value = module.builder.build_load(ptr, "Static get");
+            store(module, ret, 0, &ty, value);
+        }
+        module.builder.build_return(Some(&zero));
+        module.functions.insert(getter_name, getter);
+
+        if !s.mutable {
+            continue;
+        }
+        let setter_name = static_setter_name(name);
+        let setter = module
+            .module
+            .add_function(&setter_name, wrapper_fn_type(module), None);
+        let entry = module.context.append_basic_block(setter, "entry");
+        module.builder.position_at_end(entry);
+        if let Some(ptr) = static_ptr(module, name) {
+            let args = setter.get_nth_param(0).unwrap().into_pointer_value();
+            if let Some(value) = load(module, args, 0, &ty) {
+                module.builder.build_store(ptr, value);
+            }
+        }
+        module.builder.build_return(Some(&zero));
+        module.functions.insert(setter_name, setter);
+    }
+}
+
+fn const_zero(ty: BasicTypeEnum) -> BasicValueEnum {
+    match ty {
+        BasicTypeEnum::ArrayType(ty) => ty.const_zero().into(),
+        BasicTypeEnum::FloatType(ty) => ty.const_zero().into(),
+        BasicTypeEnum::IntType(ty) => ty.const_zero().into(),
+        BasicTypeEnum::PointerType(ty) => ty.const_zero().into(),
+        BasicTypeEnum::StructType(ty) => ty.const_zero().into(),
+        BasicTypeEnum::VectorType(ty) => ty.const_zero().into(),
+    }
+}
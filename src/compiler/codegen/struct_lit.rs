@@ -0,0 +1,41 @@
+use super::{CodeGen, CodeGenResult};
+use crate::{ast, compiler::module::Module};
+
+//
+
+impl<'i> CodeGen for ast::StructLit<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let ty = module.structs[self.name.value.as_str()];
+
+        let mut value = ty.get_undef();
+        for field in self.fields.iter() {
+            let field_value = field.expr.code_gen(module)?;
+            if module.is_terminated() {
+                // the field initializer jumped out with break or continue
+                return Ok(None);
+            }
+
+            // fields without a value are stored as empty structs
+            let field_value = field_value.unwrap_or_else(|| {
+                ty.get_field_type_at_index(field.index as u32)
+                    .unwrap()
+                    .into_struct_type()
+                    .const_zero()
+                    .into()
+            });
+
+            value = module
+                .builder
+                .build_insert_value(
+                    value,
+                    field_value,
+                    field.index as u32,
+                    &format!("StructLit {}", field.name),
+                )
+                .unwrap()
+                .into_struct_value();
+        }
+
+        Ok(Some(value.into()))
+    }
+}
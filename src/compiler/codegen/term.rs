@@ -15,8 +15,22 @@ impl<'i> CodeGen for ast::TermInternal<'i> {
             ast::TermInternal::Lit(lit) => lit.code_gen(module),
             ast::TermInternal::Expr(expr) => expr.code_gen(module),
             ast::TermInternal::Branch(branch) => branch.code_gen(module),
+            ast::TermInternal::Match(r#match) => r#match.code_gen(module),
             ast::TermInternal::Access(access) => access.code_gen(module),
             ast::TermInternal::Call(call) => call.code_gen(module),
+            ast::TermInternal::While(r#while) => r#while.code_gen(module),
+            ast::TermInternal::Loop(r#loop) => r#loop.code_gen(module),
+            ast::TermInternal::Break(r#break) => r#break.code_gen(module),
+            ast::TermInternal::Continue(r#continue) => r#continue.code_gen(module),
+            ast::TermInternal::Return(r#return) => r#return.code_gen(module),
+            ast::TermInternal::Lambda(lambda) => lambda.code_gen(module),
+            ast::TermInternal::StructLit(lit) => lit.code_gen(module),
+            ast::TermInternal::EnumLit(lit) => lit.code_gen(module),
+            ast::TermInternal::TupleLit(lit) => lit.code_gen(module),
+            ast::TermInternal::FieldAccess(access) => access.code_gen(module),
+            ast::TermInternal::ArrayLit(lit) => lit.code_gen(module),
+            ast::TermInternal::Index(index) => index.code_gen(module),
+            ast::TermInternal::For(r#for) => r#for.code_gen(module),
         }
     }
 }
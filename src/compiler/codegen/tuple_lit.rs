@@ -0,0 +1,42 @@
+use super::{CodeGen, CodeGenResult};
+use crate::{
+    ast::{self, TypeOf},
+    compiler::module::Module,
+};
+
+//
+
+impl<'i> CodeGen for ast::TupleLit<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let ty = module
+            .basic_type(&self.type_of())
+            .unwrap()
+            .into_struct_type();
+
+        let mut value = ty.get_undef();
+        for (i, field) in self.fields.iter().enumerate() {
+            let field_value = field.code_gen(module)?;
+            if module.is_terminated() {
+                // the field jumped out with break or continue
+                return Ok(None);
+            }
+
+            // fields without a value are stored as empty structs
+            let field_value = field_value.unwrap_or_else(|| {
+                ty.get_field_type_at_index(i as u32)
+                    .unwrap()
+                    .into_struct_type()
+                    .const_zero()
+                    .into()
+            });
+
+            value = module
+                .builder
+                .build_insert_value(value, field_value, i as u32, "TupleLit field")
+                .unwrap()
+                .into_struct_value();
+        }
+
+        Ok(Some(value.into()))
+    }
+}
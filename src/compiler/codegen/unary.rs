@@ -8,7 +8,12 @@ use crate::{
 
 impl<'i> CodeGen for ast::UnaryExpr<'i> {
     fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
-        let operand = self.operand.code_gen(module)?.unwrap();
+        let operand = self.operand.code_gen(module)?;
+        if module.is_terminated() {
+            // the operand jumped out with break, continue or return
+            return Ok(None);
+        }
+        let operand = operand.ok_or(CompileError::InvalidType)?;
 
         let value = match self.operator {
             ast::UnaryOp::Plus => operand,
@@ -0,0 +1,72 @@
+use super::{r#loop::loop_body, CodeGen, CodeGenResult};
+use crate::{
+    ast,
+    compiler::{err::ExpectType, module::Module},
+};
+
+// while loops contain 3 blocks
+//
+//             +---+  on_false    +---+
+// +---+       | T |--------------| E |
+// | E |-------| E |              | X |
+// | N |       | S |  on_true     | I |
+// | T |       | T |-----+        | T |
+// | R |       +---+     |        +---+
+// | Y |         |     +---+        |
+// +---+         +-----| B |--------+
+//             continue| O |  break
+//                     | D |
+//                     | Y |
+//                     +---+
+
+impl<'i> CodeGen for ast::While<'i> {
+    fn code_gen<'ctx>(&self, module: &mut Module<'ctx>) -> CodeGenResult<'ctx> {
+        let function = module.function.clone();
+        let function_ref = function.borrow();
+        let function = function_ref
+            .as_ref()
+            .expect("While outside of any function?");
+
+        let id = module.label_id;
+        module.label_id += 1;
+
+        // one block to test the condition in
+        let test = module
+            .context
+            .append_basic_block(function.proto, &format!("While test {id}"));
+        // one block to repeat while the condition is true
+        let body = module
+            .context
+            .append_basic_block(function.proto, &format!("While body {id}"));
+        // and one block to enter after the condition is false or after breaking out
+        let exit = module
+            .context
+            .append_basic_block(function.proto, &format!("While exit {id}"));
+
+        drop(function_ref);
+        module.builder.build_unconditional_branch(test);
+
+        // test block
+        module.builder.position_at_end(test);
+        let cond = self.internal.test.code_gen(module)?;
+        if module.is_terminated() {
+            // the condition jumped out with break, continue or return
+            module.delete_unreachable_block(body);
+            module.delete_unreachable_block(exit);
+            return Ok(None);
+        }
+        let cond = cond.expect_bool()?;
+        module.builder.build_conditional_branch(cond, body, exit);
+
+        // body block
+        module.builder.position_at_end(body);
+        loop_body(module, &self.internal.scope, test, exit)?;
+        if !module.is_terminated() {
+            module.builder.build_unconditional_branch(test);
+        }
+
+        // exit block
+        module.builder.position_at_end(exit);
+        Ok(None)
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{
+    ast::{self, Type, TypeOf},
+    compiler::{
+        err::CompileResult,
+        interface::struct_layout,
+        module::{wrapper_name, Module},
+    },
+};
+use inkwell::{
+    types::{BasicType, BasicTypeEnum, FunctionType},
+    values::{AggregateValueEnum, BasicValue, BasicValueEnum, FunctionValue, PointerValue},
+    AddressSpace,
+};
+
+// host code calls functions through wrappers
+//
+//                 +---------+  load params  +----------+
+// args: i8* ----> | wrapper | ------------> | function |
+//                 |         |  store ret    |          |
+// ret:  i8* <---- |         | <------------ |          |
+//                 +---------+               +----------+
+
+pub(super) fn code_gen_wrapper<'ctx>(
+    module: &mut Module<'ctx>,
+    function: &ast::Function,
+    proto: FunctionValue<'ctx>,
+) -> CompileResult<()> {
+    // enums cannot be passed to or from host code yet
+    let passable = function
+        .internal
+        .params
+        .iter()
+        .map(|param| &param.ty)
+        .chain(std::iter::once(&function.type_of()))
+        .all(|ty| is_passable(module, ty));
+    if !passable {
+        return Ok(());
+    }
+
+    let name = wrapper_name(function.internal.name.value.as_str());
+    let wrapper = module
+        .module
+        .add_function(&name, wrapper_fn_type(module), None);
+
+    let entry = module.context.append_basic_block(wrapper, "entry");
+    module.builder.position_at_end(entry);
+
+    let args = wrapper.get_nth_param(0).unwrap().into_pointer_value();
+    let ret = wrapper.get_nth_param(1).unwrap().into_pointer_value();
+
+    // errors raised by earlier calls are already reported
+    let runtime_error = module.runtime_error();
+    module
+        .builder
+        .build_store(runtime_error, module.context.i32_type().const_zero());
+
+    // params are laid out like the fields of a struct
+    let params: Vec<Type> = function
+        .internal
+        .params
+        .iter()
+        .map(|param| param.ty.clone())
+        .collect();
+    let layouts: Vec<_> = params.iter().map(|ty| module.layout(ty)).collect();
+    let (_, offsets) = struct_layout(&layouts);
+    let params: Vec<BasicValueEnum> = params
+        .iter()
+        .zip(offsets)
+        .filter_map(|(ty, offset)| load(module, args, offset, ty))
+        .collect();
+
+    let value = module
+        .builder
+        .build_call(proto, &params[..], "Wrapper call")
+        .try_as_basic_value()
+        .left();
+    if let Some(value) = value {
+        // the value is garbage if the function raised an error, but the host ignores it then
+        store(module, ret, 0, &function.type_of(), value);
+    }
+    let code = module.builder.build_load(runtime_error, "Wrapper error");
+    module.builder.build_return(Some(&code));
+
+    module.functions.insert(name, wrapper);
+
+    Ok(())
+}
+
+/// `i32 (i8* args, i8* ret)`
+pub(super) fn wrapper_fn_type<'ctx>(module: &Module<'ctx>) -> FunctionType<'ctx> {
+    let ptr_ty = module.context.i8_type().ptr_type(AddressSpace::Generic);
+    module
+        .context
+        .i32_type()
+        .fn_type(&[ptr_ty.into(), ptr_ty.into()], false)
+}
+
+pub(super) fn is_passable(module: &Module, ty: &Type) -> bool {
+    match ty {
+        Type::Named(name) => match module.struct_decls.get(name) {
+            Some(fields) => fields.iter().all(|(_, field)| is_passable(module, field)),
+            None => false,
+        },
+        Type::Tuple(tys) => tys.iter().all(|ty| is_passable(module, ty)),
+        Type::Array(ty, _) => is_passable(module, ty),
+        // captured environments don't outlive the call
+        Type::Fn(..) => false,
+        _ => true,
+    }
+}
+
+fn offset_ptr<'ctx>(
+    module: &Module<'ctx>,
+    ptr: PointerValue<'ctx>,
+    offset: usize,
+    ty: &Type,
+) -> PointerValue<'ctx> {
+    let offset = module.context.i64_type().const_int(offset as u64, false);
+    let ptr = unsafe {
+        module
+            .builder
+            .build_in_bounds_gep(ptr, &[offset], "Wrapper offset")
+    };
+    let ty = match ty {
+        // bools are passed as bytes
+        Type::Bool => module.context.i8_type().into(),
+        ty => module.basic_type(ty).unwrap(),
+    };
+    module
+        .builder
+        .build_pointer_cast(ptr, ty.ptr_type(AddressSpace::Generic), "Wrapper cast")
+}
+
+/// field types and offsets of a struct, tuple or array
+fn fields(module: &Module, ty: &Type) -> (Vec<Type>, Vec<usize>) {
+    match ty {
+        Type::Named(name) => (
+            module.struct_decls[name]
+                .iter()
+                .map(|(_, field)| field.clone())
+                .collect(),
+            module.struct_layout(name).1,
+        ),
+        Type::Tuple(tys) => (tys.clone(), module.tuple_layout(tys).1),
+        Type::Array(ty, len) => {
+            let stride = module.layout(ty).size();
+            (
+                vec![*ty.clone(); *len],
+                (0..*len).map(|i| i * stride).collect(),
+            )
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// loads a value of `ty` from `ptr + offset`
+pub(super) fn load<'ctx>(
+    module: &Module<'ctx>,
+    ptr: PointerValue<'ctx>,
+    offset: usize,
+    ty: &Type,
+) -> Option<BasicValueEnum<'ctx>> {
+    match ty {
+        Type::Unit | Type::Never => None,
+        Type::Bool => {
+            let byte = module
+                .builder
+                .build_load(offset_ptr(module, ptr, offset, ty), "Wrapper load")
+                .into_int_value();
+            let zero = module.context.i8_type().const_zero();
+            Some(
+                module
+                    .builder
+                    .build_int_compare(inkwell::IntPredicate::NE, byte, zero, "Wrapper bool")
+                    .into(),
+            )
+        }
+        Type::Named(_) | Type::Tuple(_) | Type::Array(..) => {
+            let mut value: AggregateValueEnum = match module.basic_type(ty).unwrap() {
+                BasicTypeEnum::ArrayType(array_ty) => array_ty.get_undef().into(),
+                other => other.into_struct_type().get_undef().into(),
+            };
+            let (fields, offsets) = fields(module, ty);
+            for (i, (field, field_offset)) in fields.iter().zip(offsets).enumerate() {
+                let field = match load(module, ptr, offset + field_offset, field) {
+                    Some(field) => field,
+                    None => module
+                        .field_type(field)
+                        .into_struct_type()
+                        .const_zero()
+                        .into(),
+                };
+                value = module
+                    .builder
+                    .build_insert_value(value, field, i as u32, "Wrapper field")
+                    .unwrap();
+            }
+            Some(value.as_basic_value_enum())
+        }
+        ty => Some(
+            module
+                .builder
+                .build_load(offset_ptr(module, ptr, offset, ty), "Wrapper load"),
+        ),
+    }
+}
+
+/// stores `value` of `ty` to `ptr + offset`
+pub(super) fn store<'ctx>(
+    module: &Module<'ctx>,
+    ptr: PointerValue<'ctx>,
+    offset: usize,
+    ty: &Type,
+    value: BasicValueEnum<'ctx>,
+) {
+    match ty {
+        Type::Unit | Type::Never => {}
+        Type::Bool => {
+            let byte = module.builder.build_int_z_extend(
+                value.into_int_value(),
+                module.context.i8_type(),
+                "Wrapper bool",
+            );
+            module
+                .builder
+                .build_store(offset_ptr(module, ptr, offset, ty), byte);
+        }
+        Type::Named(_) | Type::Tuple(_) | Type::Array(..) => {
+            let (fields, offsets) = fields(module, ty);
+            for (i, (field, field_offset)) in fields.iter().zip(offsets).enumerate() {
+                let aggregate: AggregateValueEnum = match value {
+                    BasicValueEnum::ArrayValue(value) => value.into(),
+                    value => value.into_struct_value().into(),
+                };
+                let field_value = module
+                    .builder
+                    .build_extract_value(aggregate, i as u32, "Wrapper field")
+                    .unwrap();
+                store(module, ptr, offset + field_offset, field, field_value);
+            }
+        }
+        ty => {
+            module
+                .builder
+                .build_store(offset_ptr(module, ptr, offset, ty), value);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::ast;
+use crate::ast::{self, Type};
 use inkwell::values::{BasicValueEnum, FloatValue, IntValue};
 use std::{
     fmt::{Debug, Display},
@@ -9,6 +9,7 @@ use std::{
 
 pub enum Error {
     ExecuteError(ExecuteError),
+    RuntimeError(RuntimeError),
     CompileError(CompileError),
     IoError(io::Error),
     ParseError(ast::Error),
@@ -24,6 +25,7 @@ impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::ExecuteError(err) => err as &dyn Display,
+            Error::RuntimeError(err) => err as _,
             Error::CompileError(err) => err as _,
             Error::IoError(err) => err as _,
             Error::ParseError(err) => err as _,
@@ -38,6 +40,12 @@ impl From<ExecuteError> for Error {
     }
 }
 
+impl From<RuntimeError> for Error {
+    fn from(val: RuntimeError) -> Self {
+        Error::RuntimeError(val)
+    }
+}
+
 impl From<CompileError> for Error {
     fn from(val: CompileError) -> Self {
         Error::CompileError(val)
@@ -60,7 +68,36 @@ pub type Result<T> = core::result::Result<T, Error>;
 
 //
 
-pub struct ExecuteError;
+/// errors getting a script function to call from the host
+#[derive(Clone, PartialEq)]
+pub enum ExecuteError {
+    /// the script has no function `name` with params of these types
+    FnNotFound { name: String, params: Vec<Type> },
+    /// the host has no global `name` of this type
+    GlobalNotFound { name: String, ty: Type },
+    /// the function returns another type than the host expects
+    ReturnMismatch {
+        name: String,
+        expect: Type,
+        got: Type,
+    },
+    /// the host declares the struct differently than the script
+    StructMismatch(String),
+    /// borrowed values returned from scripts would outlive the strings they point to
+    BorrowedReturn,
+    /// the host function or global `name` would pass values borrowed
+    /// from a call that doesn't outlive them
+    BorrowedHostValue(String),
+    /// the host function `name` takes a param of type `()`
+    UnitParam(String),
+    /// the static `name` is not `static mut`, so the host can't change it
+    ImmutableGlobal(String),
+    /// the function takes or returns values that can't be passed to or from the host
+    NotPassable(String),
+    /// generic functions are instantiated from the source of the script,
+    /// which modules compiled from an ast don't have
+    NoSource,
+}
 
 impl Debug for ExecuteError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -70,7 +107,45 @@ impl Debug for ExecuteError {
 
 impl Display for ExecuteError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Exec return type mismatch")
+        match self {
+            ExecuteError::FnNotFound { name, params } => {
+                let params: Vec<String> = params.iter().map(ToString::to_string).collect();
+                write!(f, "Function '{name}({})' not found", params.join(", "))
+            }
+            ExecuteError::GlobalNotFound { name, ty } => {
+                write!(f, "Global '{name}: {ty}' not found")
+            }
+            ExecuteError::ReturnMismatch { name, expect, got } => {
+                write!(f, "Function '{name}' returns '{got}', not '{expect}'")
+            }
+            ExecuteError::StructMismatch(name) => {
+                write!(
+                    f,
+                    "Struct '{name}' is not declared like the host declares it"
+                )
+            }
+            ExecuteError::BorrowedReturn => {
+                write!(f, "Borrowed values can't be returned from scripts")
+            }
+            ExecuteError::BorrowedHostValue(name) => write!(
+                f,
+                "Host function or global '{name}' can't use values borrowed from a call"
+            ),
+            ExecuteError::UnitParam(name) => {
+                write!(f, "Host function '{name}' can't take unit params")
+            }
+            ExecuteError::ImmutableGlobal(name) => {
+                write!(f, "Global '{name}' is immutable")
+            }
+            ExecuteError::NotPassable(name) => write!(
+                f,
+                "Function '{name}' takes or returns values the host can't pass"
+            ),
+            ExecuteError::NoSource => write!(
+                f,
+                "Functions can only be instantiated in modules compiled from source"
+            ),
+        }
     }
 }
 
@@ -78,6 +153,50 @@ pub type ExecuteResult<T> = core::result::Result<T, ExecuteError>;
 
 //
 
+/// errors raised by a script while it runs
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum RuntimeError {
+    IndexOutOfBounds,
+    HostPanic,
+}
+
+impl RuntimeError {
+    /// the status scripts report this error with, `0` means no error
+    pub(super) fn code(self) -> u32 {
+        match self {
+            RuntimeError::IndexOutOfBounds => 1,
+            RuntimeError::HostPanic => 2,
+        }
+    }
+
+    pub(super) fn from_code(code: u32) -> Option<Self> {
+        match code {
+            1 => Some(RuntimeError::IndexOutOfBounds),
+            2 => Some(RuntimeError::HostPanic),
+            _ => None,
+        }
+    }
+}
+
+impl Debug for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        <Self as Display>::fmt(self, f)
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RuntimeError::IndexOutOfBounds => write!(f, "Index out of bounds"),
+            RuntimeError::HostPanic => write!(f, "Host function panicked"),
+        }
+    }
+}
+
+pub type RuntimeResult<T> = core::result::Result<T, RuntimeError>;
+
+//
+
 pub enum CompileError {
     InvalidType,
     VarNotFound,
@@ -0,0 +1,191 @@
+use super::{
+    err::{ExecuteError, ExecuteResult},
+    interface::{Buffer, ObjInterface, StructDecls},
+};
+use crate::ast::{FnSig, Type};
+use std::cell::UnsafeCell;
+
+//
+
+/// a host function as the runtime calls it: the params laid out like the
+/// fields of a `#[repr(C)]` struct and a buffer the return value is written to,
+/// see [`super::runtime::host_call`]
+pub(super) type RawHostFn = Box<dyn Fn(*const u8, *mut u8)>;
+
+/// a rust function scripts can call, see [`super::instance::Compiler::register_fn`]
+pub struct HostFn {
+    pub(super) sig: FnSig,
+    pub(super) structs: StructDecls,
+    borrowed: bool,
+    // boxed twice so scripts can point to it with a thin pointer
+    call: Box<RawHostFn>,
+}
+
+/// a value scripts can use like a global variable,
+/// see [`super::instance::Compiler::register_global`]
+pub struct HostGlobal {
+    pub(super) ty: Type,
+    pub(super) structs: StructDecls,
+    pub(super) mutable: bool,
+    // laid out like the host type, scripts load and store it directly
+    value: UnsafeCell<Buffer>,
+}
+
+/// rust functions and closures scripts can call
+///
+/// implemented for everything that is `Fn(A, B, ..) -> R` and for
+/// `extern "C" fn(A, B, ..) -> R` pointers, with up to 6 params,
+/// where the params and `R` are [`ObjInterface`]s that don't borrow from the host
+///
+/// `Args` only tells the implementations apart
+pub trait IntoHostFn<Args> {
+    fn into_host_fn(self) -> HostFn;
+}
+
+impl HostFn {
+    fn new(sig: FnSig, structs: StructDecls, borrowed: bool, call: RawHostFn) -> Self {
+        Self {
+            sig,
+            structs,
+            borrowed,
+            call: Box::new(call),
+        }
+    }
+
+    /// checks that scripts can call the function as `name`
+    pub(super) fn check(&self, name: &str) -> ExecuteResult<()> {
+        // borrowed values can't be read back, they only go into scripts
+        if self.borrowed {
+            return Err(ExecuteError::BorrowedHostValue(name.into()));
+        }
+        if self.sig.arg_ty.contains(&Type::Unit) {
+            return Err(ExecuteError::UnitParam(name.into()));
+        }
+        Ok(())
+    }
+
+    /// the pointer scripts pass to [`super::runtime::host_call`]
+    pub(super) fn as_ptr(&self) -> *const RawHostFn {
+        self.call.as_ref()
+    }
+}
+
+impl HostGlobal {
+    pub(super) fn new<T: ObjInterface>(name: &str, value: T, mutable: bool) -> ExecuteResult<Self> {
+        let mut structs = StructDecls::new();
+        T::structs(&mut structs);
+        let ty = T::ty();
+        // strings only live as long as the call that made them
+        if T::borrowed() || holds_str(&ty, &structs) {
+            return Err(ExecuteError::BorrowedHostValue(name.into()));
+        }
+
+        let mut buffer = Buffer::new(T::layout());
+        unsafe { value.write(buffer.as_mut_ptr()) };
+        Ok(Self {
+            ty,
+            structs,
+            mutable,
+            value: UnsafeCell::new(buffer),
+        })
+    }
+
+    /// the memory scripts load the value from and store it to
+    pub(super) fn as_ptr(&self) -> *mut u8 {
+        // SAFETY: scripts and the host only access the value while neither of them runs the other
+        unsafe { (*self.value.get()).as_mut_ptr() }
+    }
+
+    /// if the value is a `T`, with the host declaring its structs the same way
+    pub(super) fn is<T: ObjInterface>(&self) -> bool {
+        let mut structs = StructDecls::new();
+        T::structs(&mut structs);
+        T::ty() == self.ty && structs == self.structs
+    }
+}
+
+fn holds_str(ty: &Type, structs: &StructDecls) -> bool {
+    match ty {
+        Type::Str => true,
+        Type::Named(name) => structs[name]
+            .iter()
+            .any(|(_, field)| holds_str(field, structs)),
+        Type::Tuple(tys) => tys.iter().any(|ty| holds_str(ty, structs)),
+        Type::Array(ty, _) => holds_str(ty, structs),
+        _ => false,
+    }
+}
+
+macro_rules! impl_into_host_fn {
+    ($($name:ident $var:ident),+) => {
+        impl<Func, Ret, $($name),+> IntoHostFn<($($name,)+)> for Func
+        where
+            Func: Fn($($name),+) -> Ret + 'static,
+            Ret: ObjInterface,
+            $($name: ObjInterface,)+
+        {
+            fn into_host_fn(self) -> HostFn {
+                let sig = FnSig {
+                    arg_ty: Box::new([$($name::ty()),+]),
+                    out_ty: Ret::ty(),
+                };
+                let mut structs = StructDecls::new();
+                $($name::structs(&mut structs);)+
+                Ret::structs(&mut structs);
+                let borrowed = $($name::borrowed() ||)+ Ret::borrowed();
+
+                // the params are laid out like a tuple of them
+                let call = move |args: *const u8, ret: *mut u8| unsafe {
+                    let ($($var,)+) = <($($name,)+)>::read(args);
+                    self($($var),+).write(ret);
+                };
+
+                HostFn::new(sig, structs, borrowed, Box::new(call))
+            }
+        }
+
+        // `extern "C"` functions aren't `Fn`, but calling them through a closure is
+        impl<Ret, $($name),+> IntoHostFn<extern "C" fn($($name),+)> for extern "C" fn($($name),+) -> Ret
+        where
+            Ret: ObjInterface + 'static,
+            $($name: ObjInterface + 'static,)+
+        {
+            fn into_host_fn(self) -> HostFn {
+                (move |$($var: $name),+| self($($var),+)).into_host_fn()
+            }
+        }
+    };
+}
+
+// no params, nothing to read
+impl<Func, Ret> IntoHostFn<()> for Func
+where
+    Func: Fn() -> Ret + 'static,
+    Ret: ObjInterface,
+{
+    fn into_host_fn(self) -> HostFn {
+        let sig = FnSig {
+            arg_ty: Box::new([]),
+            out_ty: Ret::ty(),
+        };
+        let mut structs = StructDecls::new();
+        Ret::structs(&mut structs);
+
+        let call = move |_: *const u8, ret: *mut u8| unsafe { self().write(ret) };
+
+        HostFn::new(sig, structs, Ret::borrowed(), Box::new(call))
+    }
+}
+
+impl<Ret: ObjInterface + 'static> IntoHostFn<extern "C" fn()> for extern "C" fn() -> Ret {
+    fn into_host_fn(self) -> HostFn {
+        (move || self()).into_host_fn()
+    }
+}
+
+impl_into_host_fn!(A a);
+impl_into_host_fn!(A a, B b);
+impl_into_host_fn!(A a, B b, C c);
+impl_into_host_fn!(A a, B b, C c, D d);
+impl_into_host_fn!(A a, B b, C c, D d, E e);
+impl_into_host_fn!(A a, B b, C c, D d, E e, F f);
@@ -1,11 +1,19 @@
-use super::{err::Result, module::Module, optimizer::OptLevel};
+use super::{
+    err::{ExecuteError, ExecuteResult, Result},
+    host::{HostFn, HostGlobal, IntoHostFn},
+    interface::ObjInterface,
+    module::Module,
+    optimizer::OptLevel,
+};
 use crate::ast;
 use inkwell::context::Context;
-use std::path::Path;
+use std::{collections::HashMap, path::Path};
 
 pub struct Compiler {
     pub(super) context: Context,
     pub opt: OptLevel,
+    pub(super) host_fns: HashMap<String, HostFn>,
+    pub(super) host_globals: HashMap<String, HostGlobal>,
 }
 
 impl Compiler {
@@ -18,16 +26,103 @@ impl Compiler {
         self
     }
 
-    pub fn module_from_path<P: AsRef<Path>>(&self, path: P) -> Result<Module> {
+    /// makes `f` callable as `name(..)` from the modules compiled after this
+    ///
+    /// `f` can be a plain function, `extern "C"` ones included,
+    /// or a closure that captures state, like a `Rc<RefCell<_>>`
+    ///
+    /// it can't take unit params or take or return borrowed values, like `&str`
+    pub fn register_fn<Args, F: IntoHostFn<Args>>(
+        &mut self,
+        name: &str,
+        f: F,
+    ) -> ExecuteResult<()> {
+        let f = f.into_host_fn();
+        f.check(name)?;
+        self.host_fns.insert(name.into(), f);
+        Ok(())
+    }
+
+    /// makes `value` readable as the global `name` from the modules compiled after this,
+    /// the host can change it between calls with [`Compiler::set_global`]
+    ///
+    /// the value can't hold strings, they don't outlive the call that made them
+    pub fn register_global<T: ObjInterface>(&mut self, name: &str, value: T) -> ExecuteResult<()> {
+        let global = HostGlobal::new(name, value, false)?;
+        self.host_globals.insert(name.into(), global);
+        Ok(())
+    }
+
+    /// like [`Compiler::register_global`], but scripts can assign to the global too
+    pub fn register_global_mut<T: ObjInterface>(
+        &mut self,
+        name: &str,
+        value: T,
+    ) -> ExecuteResult<()> {
+        let global = HostGlobal::new(name, value, true)?;
+        self.host_globals.insert(name.into(), global);
+        Ok(())
+    }
+
+    /// the current value of the host global `name`
+    pub fn global<T: ObjInterface>(&self, name: &str) -> ExecuteResult<T> {
+        let global = self.get_global::<T>(name)?;
+        Ok(unsafe { T::read(global.as_ptr()) })
+    }
+
+    /// changes the host global `name`, scripts see the new value from their next call on
+    pub fn set_global<T: ObjInterface>(&self, name: &str, value: T) -> ExecuteResult<()> {
+        let global = self.get_global::<T>(name)?;
+        unsafe { value.write(global.as_ptr()) };
+        Ok(())
+    }
+
+    fn get_global<T: ObjInterface>(&self, name: &str) -> ExecuteResult<&HostGlobal> {
+        match self.host_globals.get(name) {
+            Some(global) if global.is::<T>() => Ok(global),
+            _ => Err(ExecuteError::GlobalNotFound {
+                name: name.into(),
+                ty: T::ty(),
+            }),
+        }
+    }
+
+    /// what scripts see of the registered host functions and globals
+    pub(super) fn host(&self) -> ast::Host {
+        let fns = self
+            .host_fns
+            .iter()
+            .map(|(name, f)| (name.clone(), f.sig.clone()));
+        let structs = self
+            .host_fns
+            .values()
+            .flat_map(|f| f.structs.iter())
+            .chain(self.host_globals.values().flat_map(|g| g.structs.iter()))
+            .map(|(name, fields)| (name.clone(), fields.clone()));
+        let globals = self.host_globals.iter().map(|(name, g)| {
+            let sig = ast::GlobalSig {
+                ty: g.ty.clone(),
+                mutable: g.mutable,
+            };
+            (name.clone(), sig)
+        });
+        ast::Host {
+            fns: fns.collect(),
+            structs: structs.collect(),
+            globals: globals.collect(),
+        }
+    }
+
+    pub fn module_from_path<P: AsRef<Path>>(&self, path: P) -> Result<Module<'_>> {
         Module::new_from_path(self, path, self.opt)
     }
 
-    pub fn module_from_source<'s, S: Into<&'s str>>(&self, source: S) -> Result<Module> {
+    pub fn module_from_source<'s, S: Into<&'s str>>(&self, source: S) -> Result<Module<'_>> {
         Module::new_from_source(self, source, self.opt)
     }
 
-    pub fn module_from_ast(&self, module: &ast::Module) -> Result<Module> {
-        Ok(Module::new_from_ast(self, module, self.opt)?)
+    pub fn module_from_ast(&self, module: &ast::Module) -> Result<Module<'_>> {
+        Module::new_from_ast(self, module, self.opt)
     }
 }
 
@@ -36,6 +131,8 @@ impl Default for Compiler {
         Self {
             context: Context::create(),
             opt: Default::default(),
+            host_fns: HashMap::new(),
+            host_globals: HashMap::new(),
         }
     }
 }
@@ -0,0 +1,311 @@
+use super::runtime::{as_bytes, store_str, RawStr};
+use crate::ast::Type;
+use std::{alloc::Layout, collections::HashMap};
+
+//
+
+/// struct declarations by name, fields in declaration order
+pub type StructDecls = HashMap<String, Vec<(String, Type)>>;
+
+/// rust types that can be passed to and returned from scripts
+///
+/// values are passed as bytes,
+/// structs are laid out like a `#[repr(C)]` struct would be, see [`struct_layout`]
+///
+/// use `#[derive(ObjInterface)]` to implement this for structs
+pub trait ObjInterface: Sized {
+    /// the matching script type
+    fn ty() -> Type;
+
+    /// size and alignment of the value when passed to or from a script
+    fn layout() -> Layout;
+
+    /// adds the declarations of all structs this type
+    /// is made of, to check them against the script
+    fn structs(_structs: &mut StructDecls) {}
+
+    /// values borrowing from the host, like `&str`,
+    /// can be passed to scripts but not returned from them
+    fn borrowed() -> bool {
+        false
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` has to point to a value of [`Self::ty`] laid out as [`Self::layout`]
+    unsafe fn read(ptr: *const u8) -> Self;
+
+    /// # Safety
+    ///
+    /// `ptr` has to be valid for writes of [`Self::layout`]
+    unsafe fn write(self, ptr: *mut u8);
+}
+
+/// the params of a script function called from the host, see [`super::module::Module::function`]
+///
+/// implemented for `()` and tuples of up to 6 [`ObjInterface`]s,
+/// the params are laid out like the fields of the tuple
+pub trait FnArgs: Sized {
+    /// the matching script types of the params
+    fn tys() -> Vec<Type>;
+
+    /// size and alignment of all params together
+    fn layout() -> Layout;
+
+    /// adds the declarations of all structs the params are made of
+    fn structs(_structs: &mut StructDecls) {}
+
+    /// # Safety
+    ///
+    /// `ptr` has to be valid for writes of [`Self::layout`]
+    unsafe fn write(self, ptr: *mut u8);
+}
+
+impl FnArgs for () {
+    fn tys() -> Vec<Type> {
+        vec![]
+    }
+
+    fn layout() -> Layout {
+        Layout::new::<()>()
+    }
+
+    unsafe fn write(self, _: *mut u8) {}
+}
+
+/// layout of a `#[repr(C)]` struct with `fields` and the offset of each field
+pub fn struct_layout(fields: &[Layout]) -> (Layout, Vec<usize>) {
+    let mut layout = Layout::new::<()>();
+    let offsets = fields
+        .iter()
+        .map(|&field| {
+            let (extended, offset) = layout.extend(field).unwrap();
+            layout = extended;
+            offset
+        })
+        .collect();
+    (layout.pad_to_align(), offsets)
+}
+
+/// aligned bytes for passing values to and from scripts
+pub(super) struct Buffer {
+    data: Vec<u64>,
+}
+
+impl Buffer {
+    pub fn new(layout: Layout) -> Self {
+        // none of the script types need more alignment than u64
+        assert!(layout.align() <= std::mem::align_of::<u64>());
+        Self {
+            data: vec![0; layout.size() / 8 + 1],
+        }
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.data.as_mut_ptr() as _
+    }
+}
+
+//
+
+macro_rules! impl_obj_interface {
+    ($($rust:ty => $script:expr),* $(,)?) => {
+        $(
+            impl ObjInterface for $rust {
+                fn ty() -> Type {
+                    $script
+                }
+
+                fn layout() -> Layout {
+                    Layout::new::<Self>()
+                }
+
+                unsafe fn read(ptr: *const u8) -> Self {
+                    (ptr as *const Self).read()
+                }
+
+                unsafe fn write(self, ptr: *mut u8) {
+                    (ptr as *mut Self).write(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_obj_interface! {
+    f32 => Type::F32,
+    f64 => Type::F64,
+    i8 => Type::I8,
+    i16 => Type::I16,
+    i32 => Type::I32,
+    i64 => Type::I64,
+    isize => Type::Isize,
+    u8 => Type::U8,
+    u16 => Type::U16,
+    u32 => Type::U32,
+    u64 => Type::U64,
+    usize => Type::Usize,
+    () => Type::Unit,
+}
+
+impl ObjInterface for bool {
+    fn ty() -> Type {
+        Type::Bool
+    }
+
+    fn layout() -> Layout {
+        Layout::new::<Self>()
+    }
+
+    unsafe fn read(ptr: *const u8) -> Self {
+        // scripts only write 0 or 1, but any other byte would not be a valid bool
+        ptr.read() != 0
+    }
+
+    unsafe fn write(self, ptr: *mut u8) {
+        ptr.write(self as u8)
+    }
+}
+
+// strings are passed as a pointer and a length,
+// see `compiler::runtime` for who owns the bytes
+
+impl ObjInterface for String {
+    fn ty() -> Type {
+        Type::Str
+    }
+
+    fn layout() -> Layout {
+        Layout::new::<RawStr>()
+    }
+
+    unsafe fn read(ptr: *const u8) -> Self {
+        let raw = (ptr as *const RawStr).read();
+        // scripts only make valid utf-8 out of valid utf-8
+        String::from_utf8_unchecked(as_bytes(raw).to_vec())
+    }
+
+    unsafe fn write(self, ptr: *mut u8) {
+        // the script can use the string until the call returns
+        let raw = store_str(self.into_boxed_str().into_boxed_bytes());
+        (ptr as *mut RawStr).write(raw)
+    }
+}
+
+impl ObjInterface for &str {
+    fn ty() -> Type {
+        Type::Str
+    }
+
+    fn layout() -> Layout {
+        Layout::new::<RawStr>()
+    }
+
+    fn borrowed() -> bool {
+        true
+    }
+
+    unsafe fn read(_: *const u8) -> Self {
+        unreachable!("borrowed strings are never returned from scripts")
+    }
+
+    unsafe fn write(self, ptr: *mut u8) {
+        // the host keeps the string alive until the call returns
+        let raw = RawStr {
+            ptr: self.as_ptr(),
+            len: self.len() as u64,
+        };
+        (ptr as *mut RawStr).write(raw)
+    }
+}
+
+// tuples are laid out like a `#[repr(C)]` struct,
+// not like rust lays out its tuples
+macro_rules! impl_obj_interface_tuple {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: ObjInterface),+> ObjInterface for ($($name,)+) {
+            fn ty() -> Type {
+                Type::Tuple(vec![$($name::ty()),+])
+            }
+
+            fn layout() -> Layout {
+                struct_layout(&[$($name::layout()),+]).0
+            }
+
+            fn structs(structs: &mut StructDecls) {
+                $($name::structs(structs);)+
+            }
+
+            fn borrowed() -> bool {
+                $($name::borrowed())||+
+            }
+
+            unsafe fn read(ptr: *const u8) -> Self {
+                let (_, offsets) = struct_layout(&[$($name::layout()),+]);
+                ($($name::read(ptr.add(offsets[$index])),)+)
+            }
+
+            unsafe fn write(self, ptr: *mut u8) {
+                let (_, offsets) = struct_layout(&[$($name::layout()),+]);
+                $(self.$index.write(ptr.add(offsets[$index]));)+
+            }
+        }
+
+        impl<$($name: ObjInterface),+> FnArgs for ($($name,)+) {
+            fn tys() -> Vec<Type> {
+                vec![$($name::ty()),+]
+            }
+
+            fn layout() -> Layout {
+                <Self as ObjInterface>::layout()
+            }
+
+            fn structs(structs: &mut StructDecls) {
+                <Self as ObjInterface>::structs(structs)
+            }
+
+            unsafe fn write(self, ptr: *mut u8) {
+                <Self as ObjInterface>::write(self, ptr)
+            }
+        }
+    };
+}
+
+impl_obj_interface_tuple!(A 0);
+impl_obj_interface_tuple!(A 0, B 1);
+impl_obj_interface_tuple!(A 0, B 1, C 2);
+impl_obj_interface_tuple!(A 0, B 1, C 2, D 3);
+impl_obj_interface_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_obj_interface_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+
+// arrays are laid out like rust arrays, element after element
+impl<T: ObjInterface, const N: usize> ObjInterface for [T; N] {
+    fn ty() -> Type {
+        Type::Array(Box::new(T::ty()), N)
+    }
+
+    fn layout() -> Layout {
+        let element = T::layout();
+        Layout::from_size_align(element.size() * N, element.align()).unwrap()
+    }
+
+    fn structs(structs: &mut StructDecls) {
+        T::structs(structs);
+    }
+
+    fn borrowed() -> bool {
+        T::borrowed()
+    }
+
+    unsafe fn read(ptr: *const u8) -> Self {
+        let stride = T::layout().size();
+        std::array::from_fn(|i| T::read(ptr.add(i * stride)))
+    }
+
+    unsafe fn write(self, ptr: *mut u8) {
+        let stride = T::layout().size();
+        for (i, element) in IntoIterator::into_iter(self).enumerate() {
+            element.write(ptr.add(i * stride));
+        }
+    }
+}
@@ -1,5 +1,8 @@
 pub mod codegen;
 pub mod err;
+pub mod host;
 pub mod instance;
+pub mod interface;
 pub mod module;
 pub mod optimizer;
+pub mod runtime;
@@ -1,32 +1,154 @@
 use super::{
-    codegen::CodeGen,
-    err::{CompileResult, ExecuteError, ExecuteResult, Result},
+    codegen::{module::code_gen_functions, CodeGen},
+    err::{ExecuteError, ExecuteResult, Result, RuntimeError, RuntimeResult},
+    host::{HostFn, HostGlobal},
     instance::Compiler,
+    interface::{struct_layout, Buffer, FnArgs, ObjInterface, StructDecls},
     optimizer::OptLevel,
+    runtime::{self, RawStr, StrFrame, ENV_ALLOC, HOST_CALL, STR_CMP, STR_CONCAT},
 };
-use crate::ast::{self, generic_mangle, Type, TypeOf};
+use crate::ast::{self, generic_mangle, Type};
 use inkwell::{
+    basic_block::BasicBlock,
     builder::Builder,
     context::Context,
     execution_engine::{ExecutionEngine, JitFunction},
     module::Module as LLModule,
     passes::{PassManager, PassManagerBuilder},
-    values::{BasicValueEnum, FunctionValue},
-    OptimizationLevel,
+    targets::TargetData,
+    types::{BasicType, BasicTypeEnum, FunctionType, StructType},
+    values::{BasicValueEnum, FunctionValue, PointerValue},
+    AddressSpace, IntPredicate, OptimizationLevel,
+};
+use std::{
+    alloc::Layout,
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    marker::PhantomData,
+    path::Path,
+    rc::Rc,
 };
-use std::{cell::RefCell, collections::HashMap, path::Path, rc::Rc};
 
 //
 
 pub(super) struct ScopeVars<'ctx> {
     pub proto: FunctionValue<'ctx>,
-    pub vars: HashMap<String, Option<BasicValueEnum<'ctx>>>,
+    pub vars: HashMap<String, Var<'ctx>>,
+    pub loops: Vec<LoopBlocks<'ctx>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Var<'ctx> {
+    /// immutable variables are just the value
+    Value(Option<BasicValueEnum<'ctx>>),
+    /// mutable variables live in a stack slot
+    Slot(PointerValue<'ctx>),
+}
+
+pub(super) struct LoopBlocks<'ctx> {
+    /// the block `continue` jumps to
+    pub repeat: BasicBlock<'ctx>,
+    /// the block `break` jumps to
+    pub exit: BasicBlock<'ctx>,
+    /// values and source blocks of all `break`s
+    pub breaks: Vec<(Option<BasicValueEnum<'ctx>>, BasicBlock<'ctx>)>,
+}
+
+/// host code calls every function through a wrapper:
+/// `i32 wrapper(i8* args, i8* ret)`
+///
+/// `args` holds the params laid out like the fields of a `#[repr(C)]`
+/// struct and the return value is written to `ret`
+///
+/// the wrapper returns the [`RuntimeError`] code the script raised, `0` if none
+type WrapperFn = unsafe extern "C" fn(*const u8, *mut u8) -> u32;
+
+struct Wrapper(JitFunction<WrapperFn>);
+
+/// a script function the host can call, see [`Module::function`]
+pub struct ScriptFn<'m, Args, Ret> {
+    wrapper: Wrapper,
+    // the module owns the compiled code
+    _module: PhantomData<&'m ()>,
+    _sig: PhantomData<fn(Args) -> Ret>,
+}
+
+impl<Args: FnArgs, Ret: ObjInterface> ScriptFn<'_, Args, Ret> {
+    pub fn call(&self, args: Args) -> RuntimeResult<Ret> {
+        let _strings = StrFrame::new();
+        let mut buffer = Buffer::new(Args::layout());
+        unsafe {
+            args.write(buffer.as_mut_ptr());
+            self.wrapper.call::<Ret>(buffer.as_mut_ptr())
+        }
+    }
+}
+
+impl Wrapper {
+    unsafe fn call<T: ObjInterface>(&self, args: *const u8) -> RuntimeResult<T> {
+        let mut ret = Buffer::new(T::layout());
+        match RuntimeError::from_code(self.0.call(args, ret.as_mut_ptr())) {
+            Some(err) => Err(err),
+            None => Ok(T::read(ret.as_mut_ptr())),
+        }
+    }
+}
+
+pub(super) fn wrapper_name(name: &str) -> String {
+    format!("__wrap{name}")
+}
+
+/// the global scripts store the code of a raised [`RuntimeError`] in
+pub(super) const RUNTIME_ERROR: &str = "__runtime_error";
+
+/// initializes the statics, called like a wrapper once the module is compiled
+pub(super) const INIT_STATICS: &str = "__init_statics";
+
+pub(super) fn static_global_name(name: &str) -> String {
+    format!("__static{name}")
+}
+
+pub(super) fn static_getter_name(name: &str) -> String {
+    format!("__get_static{name}")
+}
+
+pub(super) fn static_setter_name(name: &str) -> String {
+    format!("__set_static{name}")
+}
+
+/// what a module was compiled from, generic functions are instantiated
+/// by type checking it again, see [`Module::instantiate`]
+enum Script {
+    Source(String),
+    Files(ast::Files),
+    /// modules compiled from an ast don't own it
+    Ast,
+}
+
+impl Script {
+    fn sources(&self) -> Vec<ast::Source<'_>> {
+        match self {
+            Script::Source(source) => vec![ast::Source {
+                path: None,
+                module: "",
+                source,
+            }],
+            Script::Files(files) => files.sources(),
+            Script::Ast => vec![],
+        }
+    }
 }
 
 pub struct Module<'ctx> {
     pub(super) context: &'ctx Context,
     pub(super) module: LLModule<'ctx>,
     pub(super) builder: Builder<'ctx>,
+    /// the modules compiled before `module`, the engine runs code from all of them
+    modules: Vec<LLModule<'ctx>>,
+    compiler: &'ctx Compiler,
+    script: Rc<Script>,
+    /// the names of the generic functions, which can be instantiated
+    generics: HashSet<String>,
 
     opt: OptLevel,
     lpm: PassManager<LLModule<'ctx>>,
@@ -35,21 +157,38 @@ pub struct Module<'ctx> {
 
     engine: ExecutionEngine<'ctx>,
     // main: Option<JitFunction<unsafe extern "C" fn()>>,
-    ty: Type,
     pub label_id: u32,
 
     pub(super) functions: HashMap<String, FunctionValue<'ctx>>,
+    pub(super) returns: HashMap<String, Type>,
+    pub(super) structs: HashMap<String, StructType<'ctx>>,
+    /// the payload of every variant of an enum, stored in the enum like in a union
+    pub(super) variants: HashMap<String, Vec<StructType<'ctx>>>,
+    pub(super) struct_decls: StructDecls,
+    pub(super) host_fns: &'ctx HashMap<String, HostFn>,
+    pub(super) host_globals: &'ctx HashMap<String, HostGlobal>,
+    /// the type of every static and if it is mutable
+    pub(super) statics: HashMap<String, (Type, bool)>,
     pub(super) function: Rc<RefCell<Option<ScopeVars<'ctx>>>>, // current function and values
 }
 
 impl<'ctx> Module<'ctx> {
+    /// `mod a::b;` in the file loads `a/b.tls` from the directory of the file
     pub fn new_from_path<P: AsRef<Path>>(
         compiler: &'ctx Compiler,
         path: P,
         opt: OptLevel,
     ) -> Result<Self> {
+        let path = path.as_ref();
         let source = std::fs::read_to_string(path)?;
-        Self::new_from_source(compiler, source.as_str(), opt)
+        let mut files = ast::Files::new(path.into(), source);
+        files.load_modules()?;
+        let mut module = {
+            let ast_module = ast::parse_files(&files, &compiler.host())?;
+            Self::new_from_ast(compiler, &ast_module, opt)?
+        };
+        module.script = Rc::new(Script::Files(files));
+        Ok(module)
     }
 
     pub fn new_from_source<'s, S: Into<&'s str>>(
@@ -57,46 +196,36 @@ impl<'ctx> Module<'ctx> {
         source: S,
         opt: OptLevel,
     ) -> Result<Self> {
-        let module = ast::parse(source.into())?;
-        Ok(Self::new_from_ast(compiler, &module, opt)?)
+        let source = source.into();
+        let ast_module = ast::parse_with_host(source, &compiler.host())?;
+        let mut module = Self::new_from_ast(compiler, &ast_module, opt)?;
+        module.script = Rc::new(Script::Source(source.into()));
+        Ok(module)
     }
 
+    /// compiles the module and initializes its statics
     pub fn new_from_ast(
         compiler: &'ctx Compiler,
         ast_module: &ast::Module,
         opt: OptLevel,
-    ) -> CompileResult<Self> {
+    ) -> Result<Self> {
         let context = &compiler.context;
         let module = context.create_module("repl");
         let builder = context.create_builder();
-
-        let fpmb = PassManagerBuilder::create();
-        fpmb.set_optimization_level(opt.into());
-        fpmb.set_inliner_with_threshold(1024);
-
-        let lpm = PassManager::create(&());
-        let mpm = PassManager::create(&());
-        let fpm = PassManager::create(&module);
-
-        fpmb.populate_lto_pass_manager(&lpm, true, true);
-        fpmb.populate_module_pass_manager(&mpm);
-        fpmb.populate_function_pass_manager(&fpm);
-        fpm.initialize();
+        let (lpm, mpm, fpm) = Self::pass_managers(&module, opt);
 
         let engine = module
             .create_jit_execution_engine(OptimizationLevel::None)
             .unwrap();
 
-        let ty = ast_module
-            .functions
-            .get(&generic_mangle(&[], "__global"))
-            .unwrap()
-            .type_of();
-
         let mut module = Self {
             context,
             module,
             builder,
+            modules: vec![],
+            compiler,
+            script: Rc::new(Script::Ast),
+            generics: ast_module.function_gens.keys().cloned().collect(),
 
             opt,
             lpm,
@@ -105,16 +234,29 @@ impl<'ctx> Module<'ctx> {
 
             engine,
             // main: None,
-            ty,
             label_id: 0,
 
             functions: HashMap::new(),
+            returns: HashMap::new(),
+            structs: HashMap::new(),
+            variants: HashMap::new(),
+            struct_decls: HashMap::new(),
+            host_fns: &compiler.host_fns,
+            host_globals: &compiler.host_globals,
+            statics: HashMap::new(),
             function: Rc::new(RefCell::new(None)),
         };
 
+        module.declare_runtime();
         ast_module.code_gen(&mut module)?;
         module.finalize();
 
+        let init = unsafe { module.engine.get_function::<WrapperFn>(INIT_STATICS) }
+            .map(Wrapper)
+            .expect("Statics without an initializer?");
+        let _strings = StrFrame::new();
+        unsafe { init.call::<()>(std::ptr::null()) }?;
+
         /* // load the global function
         module.main = unsafe {
             module
@@ -126,48 +268,502 @@ impl<'ctx> Module<'ctx> {
         Ok(module)
     }
 
-    pub fn get_function_0<T>(&self, name: &str) -> JitFunction<unsafe extern "C" fn() -> T> {
+    /// type checks and compiles the instance of the generic function `name`
+    /// for params `sig`, after which [`Module::function`] finds it,
+    /// `&Args::tys()` are the params of a [`ScriptFn`] taking `Args`
+    ///
+    /// the script is type checked again, and the functions it didn't have before
+    /// are compiled into a new LLVM module that calls the ones compiled already
+    pub fn instantiate(&mut self, name: &str, sig: &[Type]) -> Result<()> {
+        if self.returns.contains_key(&generic_mangle(sig, name)) {
+            return Ok(());
+        }
+        if !self.generics.contains(name) {
+            return Err(ExecuteError::FnNotFound {
+                name: name.into(),
+                params: sig.to_vec(),
+            }
+            .into());
+        }
+        if let Script::Ast = *self.script {
+            return Err(ExecuteError::NoSource.into());
+        }
+
+        // the functions borrow from the source while they are compiled
+        let script = self.script.clone();
+        let sources = script.sources();
+        let ast_module = ast::parse_instance(&sources, &self.compiler.host(), name, sig)?;
+        let functions: Vec<_> = ast_module
+            .functions
+            .values()
+            .filter(|f| !self.returns.contains_key(&f.internal.name.value))
+            .collect();
+
+        // a failed build leaves the module as it was, without half compiled instances
+        let compiled = (self.functions.clone(), self.returns.clone());
+        self.push_module();
+        if let Err(err) = code_gen_functions(self, &functions) {
+            self.module = self.modules.pop().expect("Pushed module missing?");
+            (self.functions, self.returns) = compiled;
+            return Err(err.into());
+        }
+        self.finalize();
+        self.engine
+            .add_module(&self.module)
+            .expect("Module already in an engine?");
+
+        Ok(())
+    }
+
+    /// the script function `name` with the params `Args`, a tuple of them, returning `Ret`
+    ///
+    /// generic functions can only be called with params the script calls them with,
+    /// or that they were instantiated for with [`Module::instantiate`]
+    pub fn function<Args: FnArgs, Ret: ObjInterface>(
+        &self,
+        name: &str,
+    ) -> ExecuteResult<ScriptFn<'_, Args, Ret>> {
+        let mut structs = StructDecls::new();
+        Args::structs(&mut structs);
+        self.check_structs(&structs)?;
+
+        Ok(ScriptFn {
+            wrapper: self.get_wrapper::<Ret>(name, &Args::tys())?,
+            _module: PhantomData,
+            _sig: PhantomData,
+        })
+    }
+
+    pub fn exec<T: ObjInterface>(&self) -> Result<T> {
+        let wrapper = self.get_wrapper::<T>("__global", &[])?;
+        let _strings = StrFrame::new();
+        Ok(unsafe { wrapper.call::<T>(std::ptr::null_mut()) }?)
+    }
+
+    /// the current value of the static `name`
+    pub fn global<T: ObjInterface>(&self, name: &str) -> ExecuteResult<T> {
+        let getter = self.get_static::<T>(name, &static_getter_name(name))?;
+        let mut ret = Buffer::new(T::layout());
+        unsafe {
+            getter.0.call(std::ptr::null(), ret.as_mut_ptr());
+            Ok(T::read(ret.as_mut_ptr()))
+        }
+    }
+
+    /// changes the static `name`, which has to be `static mut`
+    pub fn set_global<T: ObjInterface>(&self, name: &str, value: T) -> ExecuteResult<()> {
+        if let Some((_, false)) = self.statics.get(name) {
+            return Err(ExecuteError::ImmutableGlobal(name.into()));
+        }
+        let setter = self.get_static::<T>(name, &static_setter_name(name))?;
+        let mut buffer = Buffer::new(T::layout());
+        unsafe {
+            value.write(buffer.as_mut_ptr());
+            setter.0.call(buffer.as_mut_ptr(), std::ptr::null_mut());
+        }
+        Ok(())
+    }
+
+    /// the accessor `accessor` of the static `name`, if it holds a `T`
+    fn get_static<T: ObjInterface>(&self, name: &str, accessor: &str) -> ExecuteResult<Wrapper> {
+        let not_found = || ExecuteError::GlobalNotFound {
+            name: name.into(),
+            ty: T::ty(),
+        };
+        let (ty, _) = self.statics.get(name).ok_or_else(not_found)?;
+
+        let mut structs = StructDecls::new();
+        T::structs(&mut structs);
+        self.check_structs(&structs)?;
+        if *ty != T::ty() {
+            return Err(not_found());
+        }
+
         unsafe {
             self.engine
-                .get_function::<unsafe extern "C" fn() -> T>(&generic_mangle(&[], name))
+                .get_function::<WrapperFn>(accessor)
+                .map(Wrapper)
+                .map_err(|_| ExecuteError::NotPassable(name.into()))
         }
-        .unwrap()
     }
 
-    pub fn get_function_1<P1, T>(&self, name: &str) -> JitFunction<unsafe extern "C" fn(P1) -> T> {
+    /// the wrapper of the function `name` with params `sig`, if it returns a `T`
+    fn get_wrapper<T: ObjInterface>(&self, name: &str, sig: &[Type]) -> ExecuteResult<Wrapper> {
+        let mangled = generic_mangle(sig, name);
+        let got = self
+            .returns
+            .get(&mangled)
+            .ok_or_else(|| ExecuteError::FnNotFound {
+                name: name.into(),
+                params: sig.to_vec(),
+            })?;
+
+        let mut structs = StructDecls::new();
+        T::structs(&mut structs);
+        self.check_structs(&structs)?;
+        let expect = T::ty();
+        if *got != expect {
+            return Err(ExecuteError::ReturnMismatch {
+                name: name.into(),
+                expect,
+                got: got.clone(),
+            });
+        }
+        if T::borrowed() {
+            return Err(ExecuteError::BorrowedReturn);
+        }
+
         unsafe {
             self.engine
-                .get_function::<unsafe extern "C" fn(P1) -> T>(&generic_mangle(&[], name))
+                .get_function::<WrapperFn>(&wrapper_name(&mangled))
+                .map(Wrapper)
+                .map_err(|_| ExecuteError::NotPassable(name.into()))
+        }
+    }
+
+    /// checks that the script declares the structs of the host the same way
+    fn check_structs(&self, structs: &StructDecls) -> ExecuteResult<()> {
+        match structs
+            .iter()
+            .find(|(name, fields)| self.struct_decls.get(*name) != Some(fields))
+        {
+            Some((name, _)) => Err(ExecuteError::StructMismatch(name.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// size and alignment of a value of `ty` when passed to or from the host
+    pub(super) fn layout(&self, ty: &Type) -> Layout {
+        match ty {
+            Type::F32 => Layout::new::<f32>(),
+            Type::F64 => Layout::new::<f64>(),
+            Type::I8 => Layout::new::<i8>(),
+            Type::I16 => Layout::new::<i16>(),
+            Type::I32 => Layout::new::<i32>(),
+            Type::I64 => Layout::new::<i64>(),
+            Type::Isize => Layout::new::<isize>(),
+            Type::U8 => Layout::new::<u8>(),
+            Type::U16 => Layout::new::<u16>(),
+            Type::U32 => Layout::new::<u32>(),
+            Type::U64 => Layout::new::<u64>(),
+            Type::Usize => Layout::new::<usize>(),
+            Type::Bool => Layout::new::<bool>(),
+            Type::Str => Layout::new::<RawStr>(),
+            Type::Named(name) => self.struct_layout(name).0,
+            Type::Tuple(tys) => self.tuple_layout(tys).0,
+            Type::Array(ty, len) => {
+                let layout = self.layout(ty);
+                Layout::from_size_align(layout.size() * len, layout.align()).unwrap()
+            }
+            // never passed, see `is_passable`
+            Type::Fn(..) => Layout::new::<(*const u8, *const u8)>(),
+            Type::Unit | Type::Never => Layout::new::<()>(),
+            Type::Unresolved => unreachable!(),
         }
-        .unwrap()
     }
 
-    pub fn get_function_2<P1, P2, T>(
+    /// layout of the struct `name` and the offset of each field
+    pub(super) fn struct_layout(&self, name: &str) -> (Layout, Vec<usize>) {
+        let fields: Vec<Layout> = self.struct_decls[name]
+            .iter()
+            .map(|(_, ty)| self.layout(ty))
+            .collect();
+        struct_layout(&fields)
+    }
+
+    /// layout of a tuple of `tys` and the offset of each field
+    pub(super) fn tuple_layout(&self, tys: &[Type]) -> (Layout, Vec<usize>) {
+        let fields: Vec<Layout> = tys.iter().map(|ty| self.layout(ty)).collect();
+        struct_layout(&fields)
+    }
+
+    /// LLVM type of a value of `ty`, `None` for types without a value
+    pub(super) fn basic_type(&self, ty: &Type) -> Option<BasicTypeEnum<'ctx>> {
+        match ty {
+            Type::F32 => Some(self.context.f32_type().into()),
+            Type::F64 => Some(self.context.f64_type().into()),
+            Type::I8
+            | Type::I16
+            | Type::I32
+            | Type::I64
+            | Type::Isize
+            | Type::U8
+            | Type::U16
+            | Type::U32
+            | Type::U64
+            | Type::Usize => Some(
+                self.context
+                    .custom_width_int_type(ty.bits().unwrap())
+                    .into(),
+            ),
+            Type::Bool => Some(self.context.bool_type().into()),
+            Type::Str => Some(self.str_type().into()),
+            Type::Named(name) => Some(self.structs[name].into()),
+            Type::Tuple(tys) => {
+                let fields: Vec<BasicTypeEnum> = tys.iter().map(|ty| self.field_type(ty)).collect();
+                Some(self.context.struct_type(&fields[..], false).into())
+            }
+            Type::Array(ty, len) => Some(self.field_type(ty).array_type(*len as u32).into()),
+            Type::Fn(params, ret) => Some(self.closure_type(params, ret).into()),
+            Type::Unit | Type::Never => None,
+            Type::Unresolved => unreachable!(),
+        }
+    }
+
+    /// LLVM type of function values: `{ ret (i8*, params..)*, i8* }`,
+    /// the function pointer and the captured environment it gets as the first argument
+    pub(super) fn closure_type(&self, params: &[Type], ret: &Type) -> StructType<'ctx> {
+        let ptr = self.closure_fn_type(params, ret);
+        let env = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        self.context.struct_type(
+            &[ptr.ptr_type(AddressSpace::Generic).into(), env.into()],
+            false,
+        )
+    }
+
+    /// LLVM type of the functions behind function values
+    pub(super) fn closure_fn_type(&self, params: &[Type], ret: &Type) -> FunctionType<'ctx> {
+        let env = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let params: Vec<BasicTypeEnum> = std::iter::once(env.into())
+            .chain(params.iter().map(|ty| self.field_type(ty)))
+            .collect();
+        match self.basic_type(ret) {
+            Some(ty) => ty.fn_type(&params[..], false),
+            None => self.context.void_type().fn_type(&params[..], false),
+        }
+    }
+
+    /// LLVM type of strings: `{ i8*, i64 }`
+    pub(super) fn str_type(&self) -> StructType<'ctx> {
+        let ptr = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        self.context
+            .struct_type(&[ptr.into(), self.context.i64_type().into()], false)
+    }
+
+    /// declares the host functions scripts can call, see [`runtime`]
+    fn declare_runtime(&mut self) {
+        let i8_ptr = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let i64_ty = self.context.i64_type();
+        let params = [i8_ptr.into(), i64_ty.into(), i8_ptr.into(), i64_ty.into()];
+
+        let concat = self
+            .module
+            .add_function(STR_CONCAT, i8_ptr.fn_type(&params, false), None);
+        self.engine
+            .add_global_mapping(&concat, runtime::str_concat as *const () as usize);
+
+        let cmp = self.module.add_function(
+            STR_CMP,
+            self.context.i32_type().fn_type(&params, false),
+            None,
+        );
+        self.engine
+            .add_global_mapping(&cmp, runtime::str_cmp as *const () as usize);
+
+        let alloc =
+            self.module
+                .add_function(ENV_ALLOC, i8_ptr.fn_type(&[i64_ty.into()], false), None);
+        self.engine
+            .add_global_mapping(&alloc, runtime::env_alloc as *const () as usize);
+
+        let host_call = self.module.add_function(
+            HOST_CALL,
+            self.context
+                .i32_type()
+                .fn_type(&[i8_ptr.into(), i8_ptr.into(), i8_ptr.into()], false),
+            None,
+        );
+        self.engine
+            .add_global_mapping(&host_call, runtime::host_call as *const () as usize);
+    }
+
+    /// LLVM type of a struct field of `ty`,
+    /// fields without a value are kept as empty structs
+    pub(super) fn field_type(&self, ty: &Type) -> BasicTypeEnum<'ctx> {
+        self.basic_type(ty)
+            .unwrap_or_else(|| self.context.struct_type(&[], false).into())
+    }
+
+    /// sizes and alignments of types on the target the engine runs code for
+    pub(super) fn target_data(&self) -> &TargetData {
+        self.engine.get_target_data()
+    }
+
+    /// allocates a stack slot in the entry block of the current function,
+    /// where LLVM can promote it back into a register
+    pub(super) fn build_entry_alloca(
         &self,
+        ty: BasicTypeEnum<'ctx>,
         name: &str,
-    ) -> JitFunction<unsafe extern "C" fn(P1, P2) -> T> {
-        unsafe {
-            self.engine
-                .get_function::<unsafe extern "C" fn(P1, P2) -> T>(&generic_mangle(&[], name))
+    ) -> PointerValue<'ctx> {
+        let function = self.function.borrow();
+        let function = function.as_ref().expect("Alloca outside of any function?");
+        let entry = function.proto.get_first_basic_block().unwrap();
+
+        let builder = self.context.create_builder();
+        match entry.get_first_instruction() {
+            Some(first) => builder.position_before(&first),
+            None => builder.position_at_end(entry),
         }
-        .unwrap()
+        builder.build_alloca(ty, name)
     }
 
-    pub fn exec<T: 'static>(&self) -> ExecuteResult<T> {
-        if !self.ty.matches::<T>() {
-            return Err(ExecuteError);
+    /// raises `err` and returns from the current function
+    pub(super) fn build_runtime_error(&self, err: RuntimeError) {
+        let code = self.context.i32_type().const_int(err.code() as u64, false);
+        self.builder.build_store(self.runtime_error(), code);
+        self.build_error_return();
+    }
+
+    /// returns from the current function if the last call raised an error
+    pub(super) fn build_runtime_error_check(&mut self) {
+        let proto = self
+            .function
+            .borrow()
+            .as_ref()
+            .expect("Call outside of any function?")
+            .proto;
+
+        let id = self.label_id;
+        self.label_id += 1;
+
+        let raised = self
+            .context
+            .append_basic_block(proto, &format!("Runtime error {id}"));
+        let ok = self
+            .context
+            .append_basic_block(proto, &format!("Runtime ok {id}"));
+
+        let code = self
+            .builder
+            .build_load(self.runtime_error(), "Runtime error code")
+            .into_int_value();
+        let zero = self.context.i32_type().const_zero();
+        let cond =
+            self.builder
+                .build_int_compare(IntPredicate::NE, code, zero, "Runtime error raised");
+        self.builder.build_conditional_branch(cond, raised, ok);
+
+        self.builder.position_at_end(raised);
+        self.build_error_return();
+
+        self.builder.position_at_end(ok);
+    }
+
+    pub(super) fn runtime_error(&self) -> PointerValue<'ctx> {
+        self.module
+            .get_global(RUNTIME_ERROR)
+            .unwrap()
+            .as_pointer_value()
+    }
+
+    /// returns some value from the current function, the caller only checks for the error
+    fn build_error_return(&self) {
+        let proto = self
+            .function
+            .borrow()
+            .as_ref()
+            .expect("Runtime error outside of any function?")
+            .proto;
+
+        let value: Option<BasicValueEnum> = proto.get_type().get_return_type().map(|ty| match ty {
+            BasicTypeEnum::ArrayType(ty) => ty.get_undef().into(),
+            BasicTypeEnum::FloatType(ty) => ty.get_undef().into(),
+            BasicTypeEnum::IntType(ty) => ty.get_undef().into(),
+            BasicTypeEnum::PointerType(ty) => ty.get_undef().into(),
+            BasicTypeEnum::StructType(ty) => ty.get_undef().into(),
+            BasicTypeEnum::VectorType(ty) => ty.get_undef().into(),
+        });
+        match value {
+            Some(value) => self.builder.build_return(Some(&value)),
+            None => self.builder.build_return(None),
+        };
+    }
+
+    /// the current block already jumps somewhere else,
+    /// so no more instructions can be added to it
+    pub(super) fn is_terminated(&self) -> bool {
+        match self.builder.get_insert_block() {
+            Some(block) => block.get_terminator().is_some(),
+            None => true,
         }
+    }
 
-        Ok(unsafe {
-            self.engine
-                .get_function::<unsafe extern "C" fn() -> T>(&generic_mangle(&[], "__global"))
-                .unwrap()
-                .call()
-        })
+    /// removes a block that turned out to have no predecessors,
+    /// the builder stays in the terminated block it was in
+    pub(super) fn delete_unreachable_block(&self, block: BasicBlock<'ctx>) {
+        // SAFETY: nothing jumps to the block and the builder isn't positioned in it
+        unsafe { block.delete() }.expect("Block without a parent function?");
+    }
+
+    /// the function passes for `module`, and the module and LTO passes
+    fn pass_managers(
+        module: &LLModule<'ctx>,
+        opt: OptLevel,
+    ) -> (
+        PassManager<LLModule<'ctx>>,
+        PassManager<LLModule<'ctx>>,
+        PassManager<FunctionValue<'ctx>>,
+    ) {
+        let fpmb = PassManagerBuilder::create();
+        fpmb.set_optimization_level(opt.into());
+        fpmb.set_inliner_with_threshold(1024);
+
+        let lpm = PassManager::create(&());
+        let mpm = PassManager::create(&());
+        let fpm = PassManager::create(module);
+
+        fpmb.populate_lto_pass_manager(&lpm, true, true);
+        fpmb.populate_module_pass_manager(&mpm);
+        fpmb.populate_function_pass_manager(&fpm);
+        fpm.initialize();
+
+        (lpm, mpm, fpm)
+    }
+
+    /// code can't be added to a module the engine already compiled,
+    /// so later code goes into a new one that declares everything compiled before
+    fn push_module(&mut self) {
+        let module = self.context.create_module("instance");
+        let module = std::mem::replace(&mut self.module, module);
+        self.modules.push(module);
+
+        // the passes drop declarations nothing calls,
+        // so the type comes from the newest module that still has the function
+        for (name, f) in self.functions.iter_mut() {
+            let ty = self
+                .modules
+                .iter()
+                .rev()
+                .find_map(|module| module.get_function(name))
+                .expect("Function missing from every module?")
+                .get_type();
+            *f = self.module.add_function(name, ty, None);
+        }
+        // the engine links them to the ones the first module defines
+        self.module
+            .add_global(self.context.i32_type(), None, RUNTIME_ERROR);
+        for (name, (ty, _)) in self.statics.iter() {
+            if let Some(ty) = self.basic_type(ty) {
+                self.module.add_global(ty, None, &static_global_name(name));
+            }
+        }
+        self.declare_runtime();
+
+        let (lpm, mpm, fpm) = Self::pass_managers(&self.module, self.opt);
+        self.lpm = lpm;
+        self.mpm = mpm;
+        self.fpm = fpm;
     }
 
     fn finalize(&self) {
-        for f in self.functions.values() {
+        // functions compiled into earlier modules are only declared
+        let functions = self
+            .functions
+            .values()
+            .filter(|f| f.count_basic_blocks() > 0);
+
+        for f in functions.clone() {
             assert!(
                 f.verify(true),
                 "{}",
@@ -180,7 +776,7 @@ impl<'ctx> Module<'ctx> {
             self.module.print_to_string().to_string()
         );
 
-        for f in self.functions.values() {
+        for f in functions {
             if !matches!(self.opt, OptLevel::O0) {
                 self.fpm.run_on(f);
             }
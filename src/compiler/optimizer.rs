@@ -1,19 +1,14 @@
 use inkwell::OptimizationLevel;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum OptLevel {
     O0,
     O1,
+    #[default]
     O2,
     O3,
 }
 
-impl Default for OptLevel {
-    fn default() -> Self {
-        OptLevel::O2
-    }
-}
-
 impl From<OptLevel> for OptimizationLevel {
     fn from(val: OptLevel) -> Self {
         match val {
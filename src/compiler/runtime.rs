@@ -0,0 +1,140 @@
+use super::{err::RuntimeError, host::RawHostFn};
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    panic::{self, AssertUnwindSafe},
+    slice,
+};
+
+// scripts call into these host functions for everything
+// that is too much work to generate code for
+//
+// strings are immutable slices, a pointer and a length
+//
+// - literals live in the compiled module as long as the module does
+// - strings built while a script runs and `String`s passed in from the host
+//   live in an arena until the call from the host returns
+// - borrowed `&str`s passed in from the host stay owned by the host,
+//   which can't drop them before the call returns
+//
+// strings returned to the host are copied into a `String` before the arena is cleared
+//
+// the captured environments of closures live in the same arena,
+// closures can't be returned to the host so they never outlive it
+
+/// the script string concatenation: `fn(a: i8*, a_len: i64, b: i8*, b_len: i64) -> i8*`
+pub(super) const STR_CONCAT: &str = "__str_concat";
+
+/// the script string comparison: `fn(a: i8*, a_len: i64, b: i8*, b_len: i64) -> i32`
+pub(super) const STR_CMP: &str = "__str_cmp";
+
+/// the closure environment allocation: `fn(size: i64) -> i8*`
+pub(super) const ENV_ALLOC: &str = "__env_alloc";
+
+/// the call of a registered host function: `fn(f: i8*, args: i8*, ret: i8*) -> i32`,
+/// returns the runtime error code of the call
+pub(super) const HOST_CALL: &str = "__host_call";
+
+/// a script string as it is passed to and from host code
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+pub(super) struct RawStr {
+    pub ptr: *const u8,
+    pub len: u64,
+}
+
+thread_local! {
+    static STRINGS: RefCell<Vec<Box<[u8]>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// strings stored while a frame is alive are freed when it is dropped
+///
+/// frames nest, so scripts calling host code calling scripts only free their own strings
+pub(super) struct StrFrame {
+    len: usize,
+}
+
+impl StrFrame {
+    pub fn new() -> Self {
+        Self {
+            len: STRINGS.with(|strings| strings.borrow().len()),
+        }
+    }
+}
+
+impl Drop for StrFrame {
+    fn drop(&mut self) {
+        STRINGS.with(|strings| strings.borrow_mut().truncate(self.len));
+    }
+}
+
+/// keeps `bytes` alive until the current [`StrFrame`] is dropped
+pub(super) fn store_str(bytes: Box<[u8]>) -> RawStr {
+    let raw = RawStr {
+        ptr: bytes.as_ptr(),
+        len: bytes.len() as u64,
+    };
+    // moving the box keeps its contents where they are
+    STRINGS.with(|strings| strings.borrow_mut().push(bytes));
+    raw
+}
+
+/// # Safety
+///
+/// `raw` has to point to `raw.len` bytes that are still alive
+pub(super) unsafe fn as_bytes<'a>(raw: RawStr) -> &'a [u8] {
+    if raw.len == 0 {
+        // empty strings might come with a dangling pointer
+        return &[];
+    }
+    slice::from_raw_parts(raw.ptr, raw.len as usize)
+}
+
+//
+
+pub(super) unsafe extern "C" fn str_concat(
+    a: *const u8,
+    a_len: u64,
+    b: *const u8,
+    b_len: u64,
+) -> *const u8 {
+    let a = as_bytes(RawStr { ptr: a, len: a_len });
+    let b = as_bytes(RawStr { ptr: b, len: b_len });
+    store_str([a, b].concat().into_boxed_slice()).ptr
+}
+
+pub(super) extern "C" fn env_alloc(size: u64) -> *mut u8 {
+    // none of the script types need more alignment than u64
+    let align = std::mem::align_of::<u64>();
+    let mut bytes = vec![0; size as usize + align].into_boxed_slice();
+    let offset = bytes.as_ptr().align_offset(align);
+    let ptr = bytes[offset..].as_mut_ptr();
+    store_str(bytes);
+    ptr
+}
+
+pub(super) unsafe extern "C" fn str_cmp(a: *const u8, a_len: u64, b: *const u8, b_len: u64) -> i32 {
+    let a = as_bytes(RawStr { ptr: a, len: a_len });
+    let b = as_bytes(RawStr { ptr: b, len: b_len });
+    match a.cmp(b) {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    }
+}
+
+/// # Safety
+///
+/// `f` has to point to a [`RawHostFn`] that is still alive,
+/// `args` and `ret` have to be laid out like it expects
+pub(super) unsafe extern "C" fn host_call(
+    f: *const RawHostFn,
+    args: *const u8,
+    ret: *mut u8,
+) -> u32 {
+    // unwinding out of an `extern "C"` function aborts the process
+    match panic::catch_unwind(AssertUnwindSafe(|| (*f)(args, ret))) {
+        Ok(()) => 0,
+        Err(_) => RuntimeError::HostPanic.code(),
+    }
+}
@@ -7,8 +7,11 @@ extern crate pest_derive;
 pub mod ast;
 pub mod compiler;
 
+pub use compiler::interface::ObjInterface;
+pub use toy_lang_derive::ObjInterface;
+
 pub fn run_code<'s, S: Into<&'s str>>(source: S) -> Result<i64> {
     let compiler = Compiler::new();
     let result = compiler.module_from_source(source)?;
-    Ok(result.exec()?)
+    result.exec()
 }
@@ -1,5 +1,14 @@
 use rand::{distributions::Alphanumeric, Rng};
-use toy_lang::{compiler::instance::Compiler, run_code};
+use std::{cell::RefCell, fs, path::PathBuf, rc::Rc};
+use toy_lang::{
+    ast::Type,
+    compiler::{
+        err::{Error, ExecuteError, RuntimeError},
+        instance::Compiler,
+        interface::FnArgs,
+    },
+    run_code, ObjInterface,
+};
 
 #[test]
 fn fuzz() {
@@ -35,3 +44,1811 @@ fn fuzz_2() {
         let _ = run_code(buf.as_ref());
     }
 }
+
+#[test]
+fn loops() {
+    let result = run_code(
+        r#"
+            fn pick(x) {
+                loop {
+                    if x > 3 { break x * 10 } else { continue }
+                }
+            }
+
+            let a = loop { break 2 };
+            let b = while false { 1 };
+            let breakfast = 3;
+            a + pick(4) + breakfast
+        "#,
+    );
+    assert_eq!(result.unwrap(), 45);
+}
+
+#[test]
+fn loop_errors() {
+    assert!(run_code("break").is_err());
+    assert!(run_code("continue").is_err());
+    assert!(run_code("while true { break 5 }; 1").is_err());
+    assert!(run_code("while 1 { break }; 1").is_err());
+    assert!(run_code("loop { if true { break 1 } else { break 2.0 } }").is_err());
+}
+
+#[test]
+fn mutable_vars() {
+    let result = run_code(
+        r#"
+            fn fib(n) {
+                let mut a = 0;
+                let mut b = 1;
+                let mut i = 0;
+                while i < n {
+                    let t = a + b;
+                    a = b;
+                    b = t;
+                    i = i + 1
+                };
+                a
+            }
+
+            let mut x = 1;
+            let y = if x > 0 { let x = 5; x } else { x = 9; 0 };
+            x = x + y;
+            fib(10) + x
+        "#,
+    );
+    assert_eq!(result.unwrap(), 61);
+}
+
+#[test]
+fn mutable_var_errors() {
+    assert!(run_code("let x = 1; x = 2; x").is_err());
+    assert!(run_code("let mut x = 1; x = 2.0; x").is_err());
+    assert!(run_code("let x: f64 = 1; 1").is_err());
+    assert!(run_code("y = 3; 1").is_err());
+}
+
+#[test]
+fn branches() {
+    let result = run_code(
+        r#"
+            fn sign(x) {
+                if x > 0 {
+                    1
+                } else if x < 0 {
+                    0 - 1
+                } else {
+                    0
+                }
+            }
+
+            let mut x = 1;
+            if sign(x) == 1 { x = 4 };
+            if false { x = 100 };
+            sign(0 - 4) * 100 + sign(3) * 10 + sign(0) + x
+        "#,
+    );
+    assert_eq!(result.unwrap(), -86);
+}
+
+#[test]
+fn branch_errors() {
+    assert!(run_code("if true { 1 }").is_err());
+    assert!(run_code("if 1 { 2 } else { 3 }").is_err());
+    assert!(run_code("if true { 1 } else if false { 2.0 } else { 3 }").is_err());
+}
+
+#[test]
+fn block_statements() {
+    let result = run_code(
+        r#"
+            fn count(n) {
+                let mut i = 0;
+                loop {
+                    if i >= n { break }
+                    i += 1
+                }
+                let mut j = 0;
+                while j < n { j += 2 }
+                for x in [1, 2, 3] { j += x }
+                match j { 0 => 1, _ => 2 }
+                i * 100 + j
+            }
+
+            let mut i = 0;
+            while i < 3 { i += 1 }
+            if i < 5 { i += 10 }
+            count(4) + i
+        "#,
+    );
+    assert_eq!(result.unwrap(), 423);
+
+    // a block keeps its value when it ends the scope
+    assert_eq!(
+        run_code("fn f() -> i64 { if true { 1 } else { 2 } } f()").unwrap(),
+        1
+    );
+    let err = run_code("fn f() -> i64 { if true { 1 } else { 2 }; } f()")
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(
+        err.contains("expected type: 'i64' but got: '()'"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn structs() {
+    let result = run_code(
+        r#"
+            struct Vec2 { x: i64, y: i64 }
+            struct Line { a: Vec2, b: Vec2 }
+
+            fn len2(v: Vec2) -> i64 { v.x * v.x + v.y * v.y }
+            fn flip(l: Line) -> Line { Line { b: l.a, a: l.b } }
+            fn pick(first, l) { if first { l.a } else { l.b } }
+
+            let l = flip(Line { a: Vec2 { x: 1, y: 2 }, b: Vec2 { x: 3, y: 4 } });
+            let mut v: Vec2 = pick(true, l);
+            v = Vec2 { y: v.y, x: v.x + 10 };
+            len2(v) + l.b.y
+        "#,
+    );
+    assert_eq!(result.unwrap(), 187);
+}
+
+/// asserts compiling or running `source` fails with an error containing `msg`
+fn assert_err(source: &str, msg: &str) {
+    match run_code(source) {
+        Ok(result) => panic!("expected an error, got {}", result),
+        Err(err) => {
+            let err = err.to_string();
+            assert!(err.contains(msg), "{}", err);
+        }
+    }
+}
+
+#[test]
+fn struct_errors() {
+    assert_err(
+        "struct A { x: i64 } let a = A { x: 1, x: 2 }; 1",
+        "field 'x' specified more than once",
+    );
+    assert_err(
+        "struct A { x: i64 } let a = A { }; 1",
+        "missing field 'x' in initializer of 'A'",
+    );
+    assert_err(
+        "struct A { x: i64 } let a = A { x: 1.0 }; 1",
+        "expected type: 'i64' but got: 'f64'",
+    );
+    assert_err(
+        "struct A { x: i64 } let a = A { x: 1 }; a.y",
+        "no field 'y' on type 'A'",
+    );
+    assert_err(
+        "struct A { x: i64 } let a = A { x: 1 }; a == a",
+        "binary operator: '==' cannot be applied to lhs: 'A' and rhs: 'A'",
+    );
+    assert_err("struct A { x: B } struct B { a: A } 1", "has infinite size");
+    assert_err(
+        "struct A { x: i64 } struct A { y: i64 } 1",
+        "type 'A' is defined multiple times",
+    );
+    assert_err(
+        "fn f(a: Foo) -> i64 { 1 } 1",
+        "type 'Foo' not found within accessible scopes",
+    );
+    assert_err("let x = 1; x.y", "no field 'y' on type 'i64'");
+}
+
+#[test]
+fn enums() {
+    let result = run_code(
+        r#"
+            enum Shape { Square(i64), Rect(i64, i64), Empty }
+            enum State { Run(i64), Done }
+
+            fn area(s: Shape) -> i64 {
+                match s {
+                    Shape::Square(a) => a * a,
+                    Shape::Rect(w, h) => w * h,
+                    Shape::Empty => 0,
+                }
+            }
+            fn step(s: State) -> State {
+                match s {
+                    State::Run(0) => State::Done,
+                    State::Run(n) => State::Run(n - 1),
+                    State::Done => State::Done,
+                }
+            }
+            fn wrap(b: bool, n: i64) -> i64 {
+                match b {
+                    true => match n { 0 => 100, x => x },
+                    false => 0,
+                }
+            }
+
+            let mut s = State::Run(4);
+            let mut steps = 0;
+            loop {
+                match s {
+                    State::Done => break,
+                    _ => { s = step(s); steps = steps + 1; }
+                }
+            };
+            area(Shape::Rect(2, 3)) + area(Shape::Square(4)) + area(Shape::Empty)
+                + steps * 1000 + wrap(true, 0) + wrap(false, 7)
+        "#,
+    );
+    assert_eq!(result.unwrap(), 5122);
+
+    // variants of different sizes share the storage of the enum
+    let result = run_code(
+        r#"
+            enum Big { A(i64, i64, i64, i64), B(u8), C }
+            enum Outer { X(Big, bool), Y(f32) }
+            struct S { o: Outer, n: i64 }
+
+            fn get(o: Outer) -> i64 {
+                match o {
+                    Outer::X(Big::A(a, b, c, d), true) => a + b + c + d,
+                    Outer::X(Big::B(x), _) => x as i64,
+                    Outer::X(Big::C, _) => 7,
+                    Outer::X(_, false) => 8,
+                    Outer::Y(f) => f as i64,
+                }
+            }
+
+            let s = S { o: Outer::X(Big::A(1, 2, 3, 4), true), n: 1 };
+            let mut t = Outer::Y(2.5f32);
+            let u = t;
+            t = Outer::X(Big::B(200u8), false);
+            get(s.o) * 1000 + get(t) + get(u) + get(Outer::X(Big::C, true)) * 100000 + s.n
+        "#,
+    );
+    assert_eq!(result.unwrap(), 710203);
+}
+
+#[test]
+fn enum_errors() {
+    assert_err(
+        "enum E { A(i64), B } match E::B { E::A(0) => 1, E::B => 2 }",
+        "non-exhaustive patterns: 'E::A(_)' not covered",
+    );
+    assert_err(
+        "enum E { A(bool), B } match E::B { E::A(true) => 1, E::B => 2 }",
+        "non-exhaustive patterns: 'E::A(false)' not covered",
+    );
+    assert_err(
+        "match true { true => 1 }",
+        "non-exhaustive patterns: 'false' not covered",
+    );
+    assert_err("enum E { A, B } E::C", "no variant 'C' on type 'E'");
+    assert_err(
+        "enum E { A(i64) } E::A(1, 2)",
+        "function got 2 arguments but expected 1",
+    );
+    assert_err(
+        "enum E { A(i64) } E::A(1.0)",
+        "expected type: 'i64' but got: 'f64'",
+    );
+    assert_err("enum E { A, A } 1", "variant 'A' is defined multiple times");
+    assert_err("enum E { A(E) } 1", "recursive type 'E' has infinite size");
+    assert_err(
+        "enum E { A } struct E { x: i64 } 1",
+        "type 'E' is defined multiple times",
+    );
+    assert_err(
+        "enum E { A(i64) } match E::A(1) { E::A(true) => 1 }",
+        "pattern 'true' cannot match values of type 'i64'",
+    );
+    assert_err(
+        "match 1 { 1 => 1, _ => 2.0 }",
+        "expected type: 'i64' but got: 'f64'",
+    );
+}
+
+#[test]
+fn tuples() {
+    let result = run_code(
+        r#"
+            fn div_rem(a: i64, b: i64) -> (i64, i64) { (a / b, a - a / b * b) }
+            fn pick(t) { match t { (true, x) => x, (false, _) => 0 } }
+
+            let (q, r) = div_rem(17, 5);
+            let nested = ((q, r), (true, 100));
+            let ((a, _), (mut flag, b)) = nested;
+            flag = !flag;
+            let single: (i64,) = (7,);
+            a * 1000 + r * 100 + nested.1.1 + single.0 + pick((!flag, 10)) + pick((flag, b))
+        "#,
+    );
+    assert_eq!(result.unwrap(), 3317);
+}
+
+#[test]
+fn tuple_errors() {
+    assert!(run_code("let (a, b) = (1, 2, 3); a").is_err());
+    assert!(run_code("let (1, a) = (1, 2); a").is_err());
+    assert!(run_code("let t = (1, 2); t.2").is_err());
+    assert!(run_code("let t = (1, 2); t == t").is_err());
+    assert!(run_code("let t: (i64, f64) = (1, 2); 1").is_err());
+    assert!(run_code("match (1, true) { (0, _) => 1, (_, true) => 2 }").is_err());
+    assert!(run_code("struct A { t: (i64, A) } 1").is_err());
+}
+
+#[test]
+fn casts() {
+    let result = run_code(
+        r#"
+            fn half(x) { x as f64 / 2.0 }
+
+            let big = 100000000000000000000000000.0;
+            let nan = 0.0 / 0.0;
+            let a = (half(3) + half(1.0)) as i64;
+            let b = (0.0 - 3.9) as i64 + true as i64 + false as i64;
+            let c = (0 - 1) as u64 as f64 as u64 as i64;
+            let saturated = big as i64 == 9223372036854775807;
+            let nan_zero = nan as i64 == 0;
+            (a * 100) + b * 10 + c + saturated as i64 + nan_zero as i64
+        "#,
+    );
+    assert_eq!(result.unwrap(), 181);
+}
+
+#[test]
+fn cast_errors() {
+    assert!(run_code("1 as bool").is_err());
+    assert!(run_code("let a = 1.5 as bool; 1").is_err());
+    assert!(run_code("(1, 2) as i64").is_err());
+    assert!(run_code("\"1\" as i64").is_err());
+    assert!(run_code("fn f(x) { x as i64 } f(true) + f((1,))").is_err());
+}
+
+#[test]
+fn sized_ints() {
+    let result = run_code(
+        r#"
+            fn wrap(a: u8, b: u8) -> u8 { a + b }
+
+            let a = wrap(200u8, 100u8) as i64;
+            let b = (-1i8 as u32) as i64 - 4294967000;
+            let c = (300 as u8) as i64 + (1000.0 as i8) as i64 + (-1.0f32 as u16) as i64;
+            let d = (1.5f32 * 2.0f32) as f64 == 3.0;
+            let e = [1, 2, 3][2usize] + -128i8 as i64;
+            let f = match 5u16 { 0u16 => 0, 5u16 => 1, _ => 2 };
+            a * 1000000 + b * 1000 + c + d as i64 + e + f
+        "#,
+    );
+    assert_eq!(result.unwrap(), 44295048);
+}
+
+#[test]
+fn sized_int_errors() {
+    assert!(run_code("1i64 + 2i32").is_err());
+    assert!(run_code("let x = 1; x + 2i32").is_err());
+    assert!(run_code("1u8 + 2u16").is_err());
+    assert!(run_code("1.0f32 + 2.0f64").is_err());
+    assert!(run_code("-1u8; 1").is_err());
+    assert!(run_code("256u8; 1").is_err());
+    assert!(run_code("-129i8; 1").is_err());
+    assert!(run_code("99999999999999999999; 1").is_err());
+    assert!(run_code("match 1u8 { 1i64 => 1, _ => 2 }").is_err());
+    assert!(run_code("fn f(x: u32) -> u32 { x } f(1i32)").is_err());
+
+    // errors point at the expression mixing the widths
+    let err = run_code("let x: i32 = 5i32; let y: i64 = 3; x + y")
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(err.contains("--> 1:36"), "{}", err);
+    let err = run_code("let x = 1u8; -x").err().unwrap().to_string();
+    assert!(err.contains("--> 1:14"), "{}", err);
+}
+
+#[test]
+fn sized_ints_host() {
+    let compiler = Compiler::new();
+    let module = compiler
+        .module_from_source(
+            r#"
+                fn bright(px: (u8, u8, u8)) -> u8 { px.0 / 3u8 + px.1 / 3u8 + px.2 / 3u8 }
+                fn scale(x: f32, s: i16) -> f32 { x * s as f32 }
+                fn count(a: [u16; 3]) -> usize { (a[0] + a[1] + a[2]) as usize }
+                0
+            "#,
+        )
+        .unwrap();
+
+    let bright = module.function::<((u8, u8, u8),), u8>("bright").unwrap();
+    assert_eq!(bright.call(((255, 30, 3),)).unwrap(), 96);
+
+    let scale = module.function::<(f32, i16), f32>("scale").unwrap();
+    assert_eq!(scale.call((1.5, -4)).unwrap(), -6.0);
+
+    let count = module.function::<([u16; 3],), usize>("count").unwrap();
+    assert_eq!(count.call(([1, 2, 300],)).unwrap(), 303);
+
+    assert!(module.function::<((u8, u8, u8),), i64>("bright").is_err());
+    assert!(module.function::<(f64, i16), f32>("scale").is_err());
+}
+
+#[test]
+fn unsigned() {
+    let result = run_code(
+        r#"
+            fn half(x: u32) -> u32 { x / 2 }
+
+            let max = 18446744073709551615u64;
+            let a = ((max / 2) == 9223372036854775807) as i64;
+            let b = (max > 1) as i64 * 10;
+            let c = (half(4000000000) > 1999999999) as i64 * 100;
+            let d = match 200u8 { 0 => 0, 200 => 1000, _ => 2 };
+            let e = (200u8 / 3) as i64 * 10000 + (max as f64 > 0.0) as i64;
+            let mut f: u16 = 1;
+            f = 65535;
+            a + b + c + d + e + (f < 2) as i64
+        "#,
+    );
+    assert_eq!(result.unwrap(), 661112);
+}
+
+#[test]
+fn unsigned_errors() {
+    assert!(run_code("let x: u8 = 256; 1").is_err());
+    assert!(run_code("let x: u8 = -1; 1").is_err());
+    assert!(run_code("1u8 > -1").is_err());
+    assert!(run_code("let x: u64 = 18446744073709551616; 1").is_err());
+    assert!(run_code("match 1u8 { 256 => 1, _ => 2 }").is_err());
+    assert!(run_code("[1u8, 300][0]").is_err());
+    assert_err("9223372036854775808; 1", "literal out of range for 'i64'");
+    assert_err("let x: u8 = 256 - 1; 1", "literal out of range for 'u8'");
+    assert_err(
+        "let x = 5; let y: u8 = x + 1; 1",
+        "expected type: 'u8' but got: 'i64'",
+    );
+}
+
+#[test]
+fn int_inference() {
+    let result = run_code(
+        r#"
+            fn f(c: bool) -> u8 { if c { 1 + 1 } else { 200 } }
+            let g = |x: u16| -> u16 { x * 2 + 60000 };
+
+            let x: u8 = 1 + 2;
+            let a: [u8; 3] = [1, 2, 3];
+            let c = x > 2;
+            let b = if c { x } else { 3 };
+            let m: i8 = match x { 3 => -(1 << 2), _ => 5 };
+            let s: u32 = if c { 4000000000 } else { 0 } / 2;
+            (x + a[2] + b + f(false)) as i64 * 10 + m as i64 + g(1u16) as i64 - s as i64
+        "#,
+    );
+    assert_eq!(result.unwrap(), 2090 - 4 + 60002 - 2000000000);
+}
+
+#[test]
+fn float_inference() {
+    let result = run_code(
+        r#"
+            fn half(x: f32) -> f32 { x / 2.0 }
+
+            let x: f32 = 1.5;
+            let a: [f32; 2] = [0.25, x * 2.0];
+            let y = if x > 1.0 { x } else { -0.5 };
+            let z: f32 = match 1 { 0 => 0.5, _ => half(9.0) + 0.5 };
+            ((a[0] + a[1] + y + z) * 100.0) as i64
+        "#,
+    );
+    assert_eq!(result.unwrap(), 975);
+
+    assert_err("let x: f32 = 1; 1", "expected type: 'f32' but got: 'i64'");
+    assert_err(
+        "let x: f32 = 1.5f64; 1",
+        "expected type: 'f32' but got: 'f64'",
+    );
+}
+
+#[test]
+fn operators() {
+    let result = run_code(
+        r#"
+            let precedence = (1 + 2 * 3 == 7 && 10 - 4 / 2 >= 8) as i64;
+            let rem = 7 % 3 + -7 % 3 * 10 + (7.5 % 2.0) as i64 * 100 + (200u8 % 7) as i64;
+            let bits = 6 & 3 | 8 ^ 1;
+            let shifts = (1 << 4 >> 2) + (-16 >> 2) + (255u8 >> 4) as i64 + (1 << 65);
+            let mut x = 5;
+            x += 3;
+            x *= 2;
+            x <<= 1;
+            x %= 7;
+            x |= 8;
+            let mut flag = true;
+            flag &= false;
+            flag ^= true;
+            precedence + rem * 10 + bits * 1000 + shifts * 100000 + x * 10000000 + flag as i64
+        "#,
+    );
+    assert_eq!(result.unwrap(), 121_711_952);
+
+    let result = run_code(
+        "(true > false) as i64 + (false < true) as i64 * 10 + (true >= false) as i64 * 100",
+    );
+    assert_eq!(result.unwrap(), 111);
+}
+
+#[test]
+fn operator_errors() {
+    assert!(run_code("1.0 & 2.0").is_err());
+    assert!(run_code("1.0 << 2").is_err());
+    assert!(run_code("1 << 2.0").is_err());
+    assert!(run_code("1u8 | 2u16").is_err());
+    assert!(run_code("let x = 1; x += 1; x").is_err());
+    assert!(run_code("let mut x = 1; x += 1.0; x").is_err());
+    assert!(run_code("let mut x = 1; x + = 1; x").is_err());
+}
+
+#[test]
+fn short_circuit() {
+    let result = run_code(
+        r#"
+            fn out_of_bounds() -> bool { [true][5] }
+
+            let a = [1, 2, 3];
+            let i = 3;
+            let x = 0;
+            let mut loops = 0;
+            while true { if loops > 2 || break { loops += 1 } else { loops += 1 } };
+            let skipped = (i < len(a) && a[i] == 2) as i64
+                + (x != 0 && 10 / x > 1) as i64 * 10
+                + (true || out_of_bounds()) as i64 * 100
+                + (false && out_of_bounds()) as i64 * 1000;
+            skipped + (false && true || true) as i64 * 10000 + loops * 100000
+        "#,
+    );
+    assert_eq!(result.unwrap(), 10100);
+
+    let result =
+        run_code("fn out_of_bounds() -> bool { [true][5] } (false || out_of_bounds()) as i64");
+    assert!(matches!(
+        result,
+        Err(Error::RuntimeError(RuntimeError::IndexOutOfBounds))
+    ));
+}
+
+#[test]
+fn short_circuit_errors() {
+    assert!(run_code("1.0 || 2.0").is_err());
+    assert!(run_code("1 && 2").is_err());
+    assert!(run_code("true && 1").is_err());
+    assert!(run_code("\"a\" || \"b\"").is_err());
+
+    let err = run_code("let a = 1.0; a || 2.0").err().unwrap().to_string();
+    assert!(err.contains("--> 1:14"), "{}", err);
+}
+
+#[test]
+fn returns() {
+    let result = run_code(
+        r#"
+            fn alive(hp: i64) -> bool {
+                if hp <= 0 { return false };
+                true
+            }
+            fn isqrt(x: i64) -> i64 {
+                let mut i = 0;
+                loop { if i * i >= x { return i }; i += 1 }
+            }
+            fn sign(x) {
+                match x > 0 { true => return 1, false => return 0 - 1 }
+            }
+            fn small(x: i64) -> u8 {
+                let y: i64 = if x < 256 { x } else { return 0 };
+                y as u8 + (return 200)
+            }
+
+            alive(0) as i64
+                + alive(3) as i64 * 10
+                + isqrt(50) * 100
+                + sign(5) * 1000
+                + sign(0 - 5) * 10000
+                + small(1000) as i64 * 100000
+                + small(1) as i64 * 1000000
+        "#,
+    );
+    assert_eq!(result.unwrap(), 200_000_000 - 10000 + 1000 + 800 + 10);
+}
+
+#[test]
+fn return_errors() {
+    assert!(run_code("fn f(x: i64) -> bool { return 3 } 1").is_err());
+    assert!(run_code("fn f(x: i64) -> i64 { if x > 0 { return true }; 1 } f(1)").is_err());
+    assert!(run_code("fn f(x) { if x > 0 { return 1.0 }; 1 } f(1)").is_err());
+    assert!(run_code("fn f(x: i64) -> i64 { return } 1").is_err());
+}
+
+#[test]
+fn unit() {
+    let result = run_code(
+        r#"
+            fn nothing() {}
+            fn unit() -> () { () }
+            fn checked(a: i64) { if a > 0 { nothing() } }
+            fn early(a: i64) { if a > 0 { return }; unit() }
+
+            let u = ();
+            let v: () = checked(3);
+            early(1);
+            early(0);
+            let t = (nothing(), 2);
+            match unit() { _ => t.1 }
+        "#,
+    );
+    assert_eq!(result.unwrap(), 2);
+
+    let compiler = Compiler::new();
+    let module = compiler
+        .module_from_source("fn touch(a: i64) { let b = a; } fn five() { 5; } five(); ()")
+        .unwrap();
+    module.exec::<()>().unwrap();
+    let touch = module.function::<(i64,), ()>("touch").unwrap();
+    touch.call((4,)).unwrap();
+    assert!(module.function::<(i64,), i64>("touch").is_err());
+}
+
+#[test]
+fn unit_errors() {
+    assert!(run_code("fn f(x: ()) -> i64 { 1 } f(())").is_err());
+    assert!(run_code("fn f(x) { 1 } f(())").is_err());
+    assert!(run_code("fn f(x) { x } fn g() {} f(g())").is_err());
+    assert!(run_code("fn f() -> () { 1 } f(); 1").is_err());
+    assert!(run_code("() == ()").is_err());
+    assert!(run_code("").is_err());
+}
+
+#[test]
+fn closures() {
+    let result = run_code(
+        r#"
+            fn apply(f: fn(i64) -> i64, x: i64) -> i64 { f(x) }
+            fn map(f, x) { f(x) }
+            fn adder(n: i64) -> fn(i64) -> i64 { |x| x + n }
+            fn fact(n: i64) -> i64 { if n < 2 { 1 } else { n * fact(n - 1) } }
+
+            let k = 3;
+            let neg = apply(|x: i64| 0 - x - 1, k);
+            let inc: fn(i64) -> i64 = |x| x + 1;
+            let mapped = map(inc, 100) + map(|x: i64| x * 2, 4);
+            let add = adder(400);
+            let outer = |a: i64| {
+                let inner = |b: i64| a + b;
+                inner(10)
+            };
+            let nested = outer(1);
+            let mut c = 5;
+            let by_value = || c * 3;
+            c = 100;
+            let f = fact;
+            neg * 10000000000 + mapped * 10000000 + add(11) * 10000 + nested * 1000 + by_value() * 10 + f(5) / 120
+        "#,
+    );
+    assert_eq!(
+        result.unwrap(),
+        -4 * 10000000000 + 109 * 10000000 + 411 * 10000 + 11 * 1000 + 15 * 10 + 1
+    );
+}
+
+#[test]
+fn closure_errors() {
+    assert_err(
+        "let f = |x| x + 1; f(1)",
+        "type annotations needed for parameter 'x'",
+    );
+    assert_err(
+        "let mut c = 1; let f = || { c = 2 }; 1",
+        "cannot assign twice to immutable variable 'c'",
+    );
+    assert_err("let a = 1; a(1)", "variable 'a' is not callable");
+    assert_err(
+        "let f = |x: i64| x; f(1.0)",
+        "expected type: 'i64' but got: 'f64'",
+    );
+    assert_err(
+        "let f = |x: i64| x; f(1, 2)",
+        "function got 2 arguments but expected 1",
+    );
+    assert_err(
+        "fn g(x) { x } let f = g; 1",
+        "generic function 'g' cannot be used as a value",
+    );
+    assert_err("let f = |x: i64| x; f == f", "binary operator: '==' cannot be applied to lhs: 'fn(i64) -> i64' and rhs: 'fn(i64) -> i64'");
+    assert_err(
+        "let f = |x: ()| 1; 1",
+        "parameter 'x' cannot have type '()'",
+    );
+    assert_err(
+        "let f: fn() -> i64 = || -> f64 { 1.0 }; 1",
+        "expected type: 'fn() -> i64' but got: 'fn() -> f64'",
+    );
+    assert_err(
+        "loop { let f = || { break }; 1 }; 1",
+        "'break' outside of a loop",
+    );
+}
+
+#[test]
+fn generics() {
+    let result = run_code(
+        r#"
+            fn lerp<T: Num>(a: T, b: T, t: f64) -> T {
+                a + ((b as f64 - a as f64) * t) as T
+            }
+            fn max<T: Ord>(a: T, b: T) -> T { if a > b { a } else { b } }
+            fn first<T: Num, U>(a: [(T, U); 2]) -> T {
+                let (x, _): (T, U) = a[0];
+                x
+            }
+            fn apply<T>(f: fn(T) -> T, x: T) -> T { f(x) }
+            fn inc(x: ?) -> ? { x + 1 }
+
+            let a = lerp(10, 20, 0.5) + lerp(1.0, 3.0, 0.5) as i64;
+            let b = max(3, 9) + max(2u8, 1u8) as i64;
+            let c = first([(7u8, true), (1u8, false)]) as i64;
+            a * 10000 + b * 100 + c * 10 + apply(|x: i64| x - 1, inc(0))
+        "#,
+    );
+    assert_eq!(result.unwrap(), 17 * 10000 + 11 * 100 + 7 * 10);
+}
+
+#[test]
+fn generic_errors() {
+    assert_err(
+        "fn f<T: Num>(a: T) -> T { a } f(\"a\"); 1",
+        "type 'str' does not satisfy the bound 'Num' of type parameter 'T'",
+    );
+    assert_err(
+        "fn f<T: Num>(a: T, b: T) -> T { a } f(1, 2.0)",
+        "expected type: 'i64' but got: 'f64'",
+    );
+    assert_err(
+        "fn f<T: Foo>(a: T) -> T { a } 1",
+        "trait 'Foo' not found within accessible scopes",
+    );
+    assert_err(
+        "fn f<T>() -> T { 1 } f()",
+        "type annotations needed, cannot infer type parameter 'T'",
+    );
+    assert_err(
+        "fn f<T, T>(a: T) -> T { a } 1",
+        "type parameter 'T' is defined multiple times",
+    );
+    assert_err(
+        "fn f<T: Num>(a: T) -> i64 { a } f(2.0)",
+        "expected type: 'i64' but got: 'f64'",
+    );
+    assert_err(
+        "fn f<T>(a: T, b: f64) -> T { a } f(1, 2)",
+        "expected type: 'f64' but got: 'i64'",
+    );
+    assert_err(
+        "fn f<T>(a: T) -> T { a } let g = f; 1",
+        "generic function 'f' cannot be used as a value",
+    );
+}
+
+#[test]
+fn recursive_generics() {
+    // recursive calls see the return type once the other paths decided it
+    let result = run_code(
+        r#"
+            fn fib(x) { if x < 2.0 { x } else { fib(x - 1.0) + fib(x - 2.0) } }
+            fn half(x) { if x > 1.0 { twice(x / 2.0) } else { x } }
+            fn twice(x) { half(x) * 2.0 }
+
+            fib(10.0) as i64 * 100 + twice(12.0) as i64
+        "#,
+    );
+    assert_eq!(result.unwrap(), 5524);
+
+    assert_err(
+        "fn f(x) { f(x) } f(1)",
+        "type annotations needed for the return type of function 'f'",
+    );
+}
+
+#[test]
+fn bench_script() {
+    let compiler = Compiler::new();
+    let module = compiler.module_from_path("examples/bench.tls").unwrap();
+    assert_eq!(module.exec::<f64>().unwrap(), 987.0);
+}
+
+#[test]
+fn methods() {
+    let result = run_code(
+        r#"
+            struct Vec2 { x: f64, y: f64 }
+            struct Circle { r: f64 }
+
+            trait Shape {
+                fn area(self) -> f64;
+                fn double_area(self) -> f64 { self.area() * 2.0 }
+            }
+
+            impl Vec2 {
+                fn dot(self, other: Self) -> f64 { self.x * other.x + self.y * other.y }
+                fn scale(self, k: f64) -> Vec2 { Vec2 { x: self.x * k, y: self.y * k } }
+                fn len2(self) -> f64 { let s: Self = self; s.dot(s) }
+            }
+            impl Shape for Circle { fn area(self) -> f64 { 3.0 * self.r * self.r } }
+            impl Shape for Vec2 { fn area(self) -> f64 { self.x * self.y } }
+            impl Shape for i64 { fn area(self) -> f64 { self as f64 } }
+            impl i64 { fn add<T: Num>(self, y: T) -> i64 { self + y as i64 } }
+
+            fn total<T: Shape>(a: T, b: T) -> f64 { a.area() + b.area() }
+
+            let v = Vec2 { x: 1.0, y: 2.0 };
+            let c = Circle { r: 2.0 };
+            let shapes = v.scale(2.0).dot(v) + c.double_area() + total(v, v) + 7.area() + v.len2();
+            shapes as i64 * 100 + [1, 2].len() * 10 + 3.add(2.5) + "abc".len()
+        "#,
+    );
+    assert_eq!(result.unwrap(), 50 * 100 + 2 * 10 + 5 + 3);
+}
+
+#[test]
+fn method_errors() {
+    assert_err(
+        "trait A { fn f(self) -> i64; } impl A for i64 {} 1",
+        "method 'f' of trait 'A' is not implemented for 'i64'",
+    );
+    assert_err(
+        "trait A { fn f(self) -> i64; } \
+         impl A for i64 { fn f(self) -> i64 { 1 } fn g(self) -> i64 { 2 } } 1",
+        "method 'g' is not a member of trait 'A'",
+    );
+    assert_err(
+        "trait A { fn f(self) -> i64; } impl A for i64 { fn f(self) -> f64 { 1.0 } } 1",
+        "expected type: 'i64' but got: 'f64'",
+    );
+    assert_err(
+        "trait A { fn f(self) -> i64; } fn g<T: A>(x: T) -> i64 { x.f() } g(1)",
+        "type 'i64' does not satisfy the bound 'A' of type parameter 'T'",
+    );
+    assert_err(
+        "trait A { fn f(self); } \
+         impl A for i64 { fn f(self) {} } impl A for i64 { fn f(self) {} } 1",
+        "conflicting implementations of trait 'A' for type 'i64'",
+    );
+    assert_err(
+        "impl i64 { fn f(x) -> i64 { 1 } } 1",
+        "method 'f' has no 'self' parameter",
+    );
+    assert_err(
+        "impl i64 { fn f(self) -> i64 { 1 } } impl i64 { fn f(self) -> i64 { 2 } } 1",
+        "method 'f' is defined multiple times for 'i64'",
+    );
+    assert_err("1.foo()", "no method 'foo' on type 'i64'");
+    assert_err(
+        "impl Foo { fn f(self) -> i64 { 1 } } 1",
+        "type 'Foo' not found within accessible scopes",
+    );
+    assert_err(
+        "impl A for i64 { fn f(self) -> i64 { 1 } } 1",
+        "trait 'A' not found within accessible scopes",
+    );
+}
+
+/// writes the files of a script to a new directory, returns the path of `main.tls`
+fn write_script(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("toy_lang_{name}_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    for (path, source) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, source).unwrap();
+    }
+    dir.join("main.tls")
+}
+
+#[test]
+fn modules() {
+    let path = write_script(
+        "modules",
+        &[
+            (
+                "main.tls",
+                r#"
+                    mod physics;
+                    mod render::sprite;
+                    use physics::step;
+                    use physics::twice;
+                    use physics::Body;
+                    use render::sprite::update as draw;
+
+                    fn update(x: i64) -> i64 { x + 1 }
+
+                    let b = Body { x: 1, v: 2 };
+                    update(draw(step(b.x, b.v))) + twice(3)
+                "#,
+            ),
+            (
+                "physics.tls",
+                r#"
+                    struct Body { x: i64, v: i64 }
+                    enum State { Still, Moving(i64) }
+
+                    fn update(b: Body) -> Body { Body { x: b.x + b.v, v: b.v } }
+                    fn state(b: Body) -> State {
+                        match b.v == 0 { true => State::Still, false => State::Moving(b.v) }
+                    }
+                    fn speed(b: Body) -> i64 {
+                        match state(b) { State::Still => 0, State::Moving(v) => v }
+                    }
+                    fn twice<T: Num>(a: T) -> T { let f = update; f(Body { x: 0, v: 0 }); a + a }
+
+                    impl Body { fn moved(self) -> Body { update(self) } }
+
+                    fn step(x: i64, v: i64) -> i64 {
+                        let b: Body = update(Body { x: x, v: v }).moved();
+                        b.x + speed(b)
+                    }
+                "#,
+            ),
+            ("render/sprite.tls", "fn update(x: i64) -> i64 { x * 10 }"),
+        ],
+    );
+
+    let compiler = Compiler::new();
+    let module = compiler.module_from_path(path).unwrap();
+    assert_eq!(module.exec::<i64>().unwrap(), 71 + 6);
+}
+
+#[test]
+fn module_errors() {
+    let compile = |name, files: &[(&str, &str)]| {
+        let path = write_script(name, files);
+        Compiler::new()
+            .module_from_path(path)
+            .err()
+            .unwrap()
+            .to_string()
+    };
+
+    let err = compile(
+        "cycle",
+        &[
+            ("main.tls", "mod a; 1"),
+            ("a.tls", "mod b;"),
+            ("b.tls", "mod a;"),
+        ],
+    );
+    assert!(err.contains("b.tls") && err.contains("declare each other"));
+    let err = compile("missing", &[("main.tls", "mod a; 1")]);
+    assert!(err.contains("main.tls") && err.contains("module 'a' could not be loaded"));
+    let err = compile(
+        "type",
+        &[
+            ("main.tls", "mod a; use a::f; f()"),
+            ("a.tls", "fn f() -> i64 { true }"),
+        ],
+    );
+    assert!(err.contains("a.tls") && err.contains("expected type: 'i64' but got: 'bool'"));
+    let err = compile(
+        "generic",
+        &[
+            ("main.tls", "mod a; use a::f; f(1)"),
+            ("a.tls", "fn f(x) { x + true }"),
+        ],
+    );
+    assert!(err.contains("a.tls") && err.contains("binary operator: '+'"));
+    let err = compile(
+        "field",
+        &[
+            ("main.tls", "mod a; 1"),
+            ("a.tls", "struct S { x: i64, x: i64 }"),
+        ],
+    );
+    assert!(err.contains("a.tls") && err.contains("field 'x' specified more than once"));
+    let err = compile("syntax", &[("main.tls", "mod a; 1"), ("a.tls", "fn f( {")]);
+    assert!(err.contains("a.tls") && !err.contains("main.tls"));
+    let err = compile(
+        "private",
+        &[
+            ("main.tls", "mod a; update(1)"),
+            ("a.tls", "fn update(x: i64) -> i64 { x }"),
+        ],
+    );
+    assert!(err.contains("main.tls") && err.contains("function 'update' not found"));
+    let err = compile(
+        "item",
+        &[
+            ("main.tls", "mod a; use a::g; 1"),
+            ("a.tls", "fn f() -> i64 { 1 }"),
+        ],
+    );
+    assert!(err.contains("'g' not found in module 'a'"));
+    let err = compile(
+        "alias",
+        &[
+            ("main.tls", "mod a; use a::f; fn f() -> i64 { 2 } 1"),
+            ("a.tls", "fn f() -> i64 { 1 }"),
+        ],
+    );
+    assert!(err.contains("name 'f' is defined multiple times"));
+    let err = compile("statement", &[("main.tls", "mod a; 1"), ("a.tls", "1")]);
+    assert!(err.contains("a.tls") && err.contains("statement outside of a function"));
+    let err = compile("undeclared", &[("main.tls", "use a::f; 1")]);
+    assert!(err.contains("module 'a' not found, declare it with 'mod a;'"));
+
+    assert_err(
+        "mod a; 1",
+        "modules can only be used by scripts loaded from a file",
+    );
+}
+
+#[test]
+fn arrays() {
+    let result = run_code(
+        r#"
+            fn sum(a: [i64; 4]) -> i64 {
+                let mut s = 0;
+                for x in a { s = s + x };
+                s
+            }
+            fn first_odd(a) {
+                let mut found = 0;
+                for (i, odd) in a { if odd { found = i; break } };
+                found
+            }
+
+            let a = [1, 2, 3, 4];
+            let b = [10; 3];
+            let mut i = 0;
+            let mut t = 0;
+            while i < len(b) { t = t + b[i]; i = i + 1 };
+            let odd = first_odd([(2, false), (3, true), (5, true)]);
+            sum(a) * 1000 + t + a[3] * 100 + [[1, 2], [3, 4]][1][0] + odd
+        "#,
+    );
+    assert_eq!(result.unwrap(), 10436);
+}
+
+#[test]
+fn array_errors() {
+    assert_err("let a = [1, 2.0]; 1", "expected type: 'i64' but got: 'f64'");
+    assert_err("let a = 1; a[0]", "cannot index into a value of type 'i64'");
+    assert_err(
+        "let a = [1, 2]; a[1.0]",
+        "expected type: 'i64' but got: 'f64'",
+    );
+    assert_err(
+        "let a: [i64; 3] = [1, 2]; 1",
+        "expected type: '[i64; 3]' but got: '[i64; 2]'",
+    );
+    assert_err(
+        "for x in 5 { x }; 1",
+        "a value of type 'i64' is not iterable",
+    );
+    assert_err(
+        "for 1 in [1, 2] { 1 }; 1",
+        "refutable pattern in local binding: '_' not covered",
+    );
+    assert_err(
+        "let a = [1, 2]; a == a",
+        "binary operator: '==' cannot be applied to lhs: '[i64; 2]' and rhs: '[i64; 2]'",
+    );
+}
+
+#[test]
+fn array_out_of_bounds() {
+    let err = run_code("let a = [1, 2]; a[2]").unwrap_err();
+    assert!(matches!(
+        err,
+        Error::RuntimeError(RuntimeError::IndexOutOfBounds)
+    ));
+
+    // negative indices are out of bounds too
+    let err = run_code("let a = [1, 2]; a[0 - 1]").unwrap_err();
+    assert!(matches!(
+        err,
+        Error::RuntimeError(RuntimeError::IndexOutOfBounds)
+    ));
+
+    // errors skip the rest of the calling functions
+    let err = run_code(
+        r#"
+            fn get(a: [i64; 2], i: i64) -> i64 { a[i] }
+            fn twice(i: i64) -> i64 { get([1, 2], i) + get([1, 2], i) }
+            twice(1) + twice(5)
+        "#,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::RuntimeError(RuntimeError::IndexOutOfBounds)
+    ));
+}
+
+#[test]
+fn strings() {
+    let compiler = Compiler::new();
+    let module = compiler
+        .module_from_source(
+            r#"
+                fn greet(name: str) -> str { "Hello, " + name + "!" }
+                fn count(a: str, b: str) -> i64 {
+                    let mut n = 0;
+                    if a < b { n = n + 1 };
+                    if a == b { n = n + 10 };
+                    if a != b { n = n + 100 };
+                    if a > b { n = n + 1000 };
+                    n
+                }
+
+                let mut s = "";
+                for part in ["a", "\u{e9}", "\"\t\\\n"] { s = s + part };
+                (len(s) * 1000) + count("abc", "abd") + count("ab" + "c", "abc") + count("", "")
+            "#,
+        )
+        .unwrap();
+
+    assert_eq!(module.exec::<i64>().unwrap(), 7121);
+
+    let greet = module.function::<(String,), String>("greet").unwrap();
+    assert_eq!(
+        greet.call(("w\u{f6}rld".into(),)).unwrap(),
+        "Hello, w\u{f6}rld!"
+    );
+
+    let count = module.function::<(&str, &str), i64>("count").unwrap();
+    let b = String::from("abd");
+    assert_eq!(count.call(("abc", &b)).unwrap(), 101);
+    assert_eq!(count.call((&b, "abc")).unwrap(), 1100);
+
+    // borrowed strings can only be passed in
+    assert!(module.function::<(&str,), &str>("greet").is_err());
+    assert!(module.function::<(String,), (&str, i64)>("greet").is_err());
+}
+
+#[test]
+fn string_errors() {
+    assert!(run_code("\"a\" + 1").is_err());
+    assert!(run_code("let a = \"a\" - \"b\"; 1").is_err());
+    assert!(run_code("let a = \"a\" && \"b\"; 1").is_err());
+    assert!(run_code("let a = \"\\q\"; 1").is_err());
+    assert!(run_code("let a = \"\\u{d800}\"; 1").is_err());
+    assert!(run_code("let a: str = 1; 1").is_err());
+}
+
+#[derive(Debug, PartialEq, ObjInterface)]
+struct Vec2 {
+    x: f64,
+    y: f64,
+}
+
+#[derive(Debug, PartialEq, ObjInterface)]
+struct Obj {
+    flag: bool,
+    pos: Vec2,
+    val: i64,
+}
+
+#[test]
+fn obj_interface() {
+    let compiler = Compiler::new();
+    let module = compiler
+        .module_from_source(
+            r#"
+                struct Vec2 { x: f64, y: f64 }
+                struct Obj { flag: bool, pos: Vec2, val: i64 }
+
+                fn step(obj: Obj) -> Obj {
+                    Obj {
+                        flag: !obj.flag,
+                        pos: Vec2 { x: obj.pos.x + 1.0, y: obj.pos.y * 2.0 },
+                        val: obj.val + 1,
+                    }
+                }
+
+                fn val(obj: Obj, offset: i64) -> i64 { obj.val + offset }
+
+                step(Obj { flag: true, pos: Vec2 { x: 1.0, y: 2.0 }, val: 3 })
+            "#,
+        )
+        .unwrap();
+
+    let obj = Obj {
+        flag: false,
+        pos: Vec2 { x: 2.0, y: 4.0 },
+        val: 4,
+    };
+    assert_eq!(module.exec::<Obj>().unwrap(), obj);
+
+    let step = module.function::<(Obj,), Obj>("step").unwrap();
+    assert_eq!(
+        step.call((obj,)).unwrap(),
+        Obj {
+            flag: true,
+            pos: Vec2 { x: 3.0, y: 8.0 },
+            val: 5,
+        }
+    );
+
+    let val = module.function::<(Obj, i64), i64>("val").unwrap();
+    let obj = Obj {
+        flag: true,
+        pos: Vec2 { x: 0.0, y: 0.0 },
+        val: 40,
+    };
+    assert_eq!(val.call((obj, 2)).unwrap(), 42);
+
+    assert!(module.exec::<i64>().is_err());
+    assert!(module.function::<(Vec2,), Obj>("step").is_err());
+}
+
+#[test]
+fn obj_interface_tuples() {
+    let compiler = Compiler::new();
+    let module = compiler
+        .module_from_source(
+            r#"
+                struct Vec2 { x: f64, y: f64 }
+
+                fn hit(pos: (Vec2, bool), r: f64) -> (bool, f64) {
+                    let (v, active) = pos;
+                    let d = v.x * v.x + v.y * v.y;
+                    let r2 = r * r;
+                    (active && (d < r2), d)
+                }
+
+                (1, (true, 2.5))
+            "#,
+        )
+        .unwrap();
+
+    assert_eq!(
+        module.exec::<(i64, (bool, f64))>().unwrap(),
+        (1, (true, 2.5))
+    );
+
+    let hit = module
+        .function::<((Vec2, bool), f64), (bool, f64)>("hit")
+        .unwrap();
+    assert_eq!(
+        hit.call(((Vec2 { x: 1.0, y: 2.0 }, true), 3.0)).unwrap(),
+        (true, 5.0)
+    );
+    assert_eq!(
+        hit.call(((Vec2 { x: 1.0, y: 2.0 }, false), 3.0)).unwrap(),
+        (false, 5.0)
+    );
+
+    assert!(module.exec::<(i64, bool)>().is_err());
+    assert!(module
+        .function::<((Vec2, f64), f64), (bool, f64)>("hit")
+        .is_err());
+}
+
+#[test]
+fn obj_interface_arrays() {
+    let compiler = Compiler::new();
+    let module = compiler
+        .module_from_source(
+            r#"
+                struct Vec2 { x: f64, y: f64 }
+
+                fn get(a: [i64; 3], i: i64) -> i64 { a[i] }
+                fn flip(a: [Vec2; 2]) -> [Vec2; 2] { [a[1], a[0]] }
+
+                [true, false, true]
+            "#,
+        )
+        .unwrap();
+
+    assert_eq!(module.exec::<[bool; 3]>().unwrap(), [true, false, true]);
+
+    let get = module.function::<([i64; 3], i64), i64>("get").unwrap();
+    assert_eq!(get.call(([4, 5, 6], 1)).unwrap(), 5);
+    assert!(matches!(
+        get.call(([4, 5, 6], 3)),
+        Err(RuntimeError::IndexOutOfBounds)
+    ));
+
+    let flip = module.function::<([Vec2; 2],), [Vec2; 2]>("flip").unwrap();
+    let a = Vec2 { x: 1.0, y: 2.0 };
+    let b = Vec2 { x: 3.0, y: 4.0 };
+    assert_eq!(
+        flip.call(([a, b],)).unwrap(),
+        [Vec2 { x: 3.0, y: 4.0 }, Vec2 { x: 1.0, y: 2.0 }]
+    );
+
+    assert!(module.exec::<[bool; 2]>().is_err());
+}
+
+#[derive(ObjInterface)]
+struct Mismatch {
+    x: f64,
+    z: f64,
+}
+
+#[test]
+fn obj_interface_mismatch() {
+    let compiler = Compiler::new();
+    let module = compiler
+        .module_from_source("struct Mismatch { x: f64, y: f64 } Mismatch { x: 1.0, y: 2.0 }")
+        .unwrap();
+    assert!(module.exec::<Mismatch>().is_err());
+}
+
+#[test]
+fn script_fns() {
+    let compiler = Compiler::new();
+    let module = compiler
+        .module_from_source(
+            r#"
+                struct Vec2 { x: f64, y: f64 }
+
+                fn sum(a: i64, b: u8, c: f64, d: bool) -> f64 {
+                    match d { true => a as f64 + b as f64 + c, false => 0.0 }
+                }
+                fn answer() -> i64 { 42 }
+                fn id(x) { x }
+
+                id(1)
+            "#,
+        )
+        .unwrap();
+
+    let sum = module.function::<(i64, u8, f64, bool), f64>("sum").unwrap();
+    assert_eq!(sum.call((1, 2, 0.5, true)).unwrap(), 3.5);
+    let answer = module.function::<(), i64>("answer").unwrap();
+    assert_eq!(answer.call(()).unwrap(), 42);
+    let id = module.function::<(i64,), i64>("id").unwrap();
+    assert_eq!(id.call((5,)).unwrap(), 5);
+
+    assert_eq!(
+        module
+            .function::<(f64,), i64>("answer")
+            .err()
+            .unwrap()
+            .to_string(),
+        "Function 'answer(f64)' not found"
+    );
+    assert!(matches!(
+        module.function::<(), f64>("answer"),
+        Err(ExecuteError::ReturnMismatch { .. })
+    ));
+    assert!(matches!(
+        module.function::<(Mismatch,), i64>("answer"),
+        Err(ExecuteError::StructMismatch(name)) if name == "Mismatch"
+    ));
+    assert!(matches!(
+        module.function::<(f64,), f64>("id"),
+        Err(ExecuteError::FnNotFound { .. })
+    ));
+}
+
+#[test]
+fn instantiate() {
+    let compiler = Compiler::new();
+    let mut module = compiler
+        .module_from_source(
+            r#"
+                fn fib_rec<T>(n: T, one: T) -> T {
+                    if n < one + one { n } else { fib_rec(n - one, one) + fib_rec(n - one - one, one) }
+                }
+                fn twice<T>(x: T) -> T { let f = |y: T| double(y); f(x) + double(x) }
+                fn double(x) { x + x }
+                fn greet(name) { "hi " + name }
+                fn fib(n) { if n < 2.0 { n } else { fib(n - 1.0) + fib(n - 2.0) } }
+
+                fib_rec(10, 1)
+            "#,
+        )
+        .unwrap();
+
+    assert!(matches!(
+        module.function::<(f64, f64), f64>("fib_rec"),
+        Err(ExecuteError::FnNotFound { .. })
+    ));
+    module
+        .instantiate("fib_rec", &[Type::F64, Type::F64])
+        .unwrap();
+    let fib = module.function::<(f64, f64), f64>("fib_rec").unwrap();
+    assert_eq!(fib.call((10.0, 1.0)).unwrap(), 55.0);
+
+    // the float literals decide the type of the recursive call
+    module.instantiate("fib", &[Type::F64]).unwrap();
+    let fib = module.function::<(f64,), f64>("fib").unwrap();
+    assert_eq!(fib.call((10.0,)).unwrap(), 55.0);
+
+    // instances call the ones compiled before them
+    module.instantiate("double", &<(i32,)>::tys()).unwrap();
+    module.instantiate("twice", &<(i32,)>::tys()).unwrap();
+    module.instantiate("twice", &<(u8,)>::tys()).unwrap();
+    let twice = module.function::<(i32,), i32>("twice").unwrap();
+    assert_eq!(twice.call((3,)).unwrap(), 12);
+    let twice = module.function::<(u8,), u8>("twice").unwrap();
+    assert_eq!(twice.call((2,)).unwrap(), 8);
+
+    module.instantiate("greet", &[Type::Str]).unwrap();
+    let greet = module.function::<(&str,), String>("greet").unwrap();
+    assert_eq!(greet.call(("you",)).unwrap(), "hi you");
+
+    let fib = module.function::<(i64, i64), i64>("fib_rec").unwrap();
+    assert_eq!(fib.call((10, 1)).unwrap(), 55);
+    assert_eq!(module.exec::<i64>().unwrap(), 55);
+}
+
+#[test]
+fn instantiate_errors() {
+    let compiler = Compiler::new();
+    let source = r#"
+        trait Area { fn area(self) -> f64; }
+        fn total<T: Area>(x: T) -> f64 { x.area() }
+        fn answer() -> i64 { 42 }
+        fn id(x) { x }
+        fn spin(n) { spin(n) }
+        1
+    "#;
+    let mut module = compiler.module_from_source(source).unwrap();
+
+    assert!(matches!(
+        module.instantiate("total", &[Type::F64]),
+        Err(Error::ParseError(_))
+    ));
+    assert!(matches!(
+        module.instantiate("id", &[Type::F64, Type::F64]),
+        Err(Error::ParseError(_))
+    ));
+    assert!(matches!(
+        module.instantiate("spin", &[Type::F64]),
+        Err(Error::ParseError(_))
+    ));
+    module.instantiate("id", &[Type::F64]).unwrap();
+    let id = module.function::<(f64,), f64>("id").unwrap();
+    assert_eq!(id.call((2.5,)).unwrap(), 2.5);
+    assert!(matches!(
+        module.instantiate("missing", &[Type::F64]),
+        Err(Error::ExecuteError(ExecuteError::FnNotFound { .. }))
+    ));
+    assert!(matches!(
+        module.instantiate("answer", &[Type::F64]),
+        Err(Error::ExecuteError(ExecuteError::FnNotFound { .. }))
+    ));
+    // already compiled
+    module.instantiate("answer", &[]).unwrap();
+
+    let ast = toy_lang::ast::parse(source).unwrap();
+    let mut module = compiler.module_from_ast(&ast).unwrap();
+    assert!(matches!(
+        module.instantiate("id", &[Type::F64]),
+        Err(Error::ExecuteError(ExecuteError::NoSource))
+    ));
+}
+
+extern "C" fn square(x: i64) -> i64 {
+    x * x
+}
+
+#[test]
+fn host_fns() {
+    let spawned = Rc::new(RefCell::new(vec![]));
+    let list = spawned.clone();
+
+    let mut compiler = Compiler::new();
+    compiler
+        .register_fn("spawn_enemy", move |x: f64, y: f64| -> i64 {
+            list.borrow_mut().push((x, y));
+            list.borrow().len() as i64
+        })
+        .unwrap();
+    compiler
+        .register_fn("square", square as extern "C" fn(i64) -> i64)
+        .unwrap();
+    compiler
+        .register_fn("greet", |name: String| format!("hi {name}"))
+        .unwrap();
+    compiler
+        .register_fn("flip", |v: Vec2| Vec2 { x: v.y, y: v.x })
+        .unwrap();
+    compiler.register_fn("frame", || 7u8).unwrap();
+
+    let module = compiler
+        .module_from_source(
+            r#"
+                struct Vec2 { x: f64, y: f64 }
+
+                fn spawn_row(n: i64) -> i64 {
+                    let mut i = 0;
+                    let mut last = 0;
+                    while i < n {
+                        last = spawn_enemy(i as f64, 2.0);
+                        i = i + 1;
+                    };
+                    last
+                }
+
+                let v = flip(Vec2 { x: 1.0, y: 2.0 });
+                spawn_row(3) + square(4) * 10 + len(greet("bob")) * 1000
+                    + frame() as i64 * 10000 + v.x as i64 * 100000
+            "#,
+        )
+        .unwrap();
+
+    assert_eq!(module.exec::<i64>().unwrap(), 276163);
+    assert_eq!(*spawned.borrow(), [(0.0, 2.0), (1.0, 2.0), (2.0, 2.0)]);
+}
+
+#[test]
+fn host_fn_errors() {
+    let mut compiler = Compiler::new();
+    compiler
+        .register_fn("square", square as extern "C" fn(i64) -> i64)
+        .unwrap();
+    compiler
+        .register_fn("flip", |v: Vec2| Vec2 { x: v.y, y: v.x })
+        .unwrap();
+
+    assert!(compiler.module_from_source("square(1.0)").is_err());
+    assert!(compiler.module_from_source("square(1, 2)").is_err());
+    assert!(compiler
+        .module_from_source("fn square(x: i64) -> i64 { x } 1")
+        .is_err());
+    assert!(compiler.module_from_source("let f = square; 1").is_err());
+    assert!(compiler
+        .module_from_source("struct Vec2 { x: f64 } flip(Vec2 { x: 1.0 }); 1")
+        .is_err());
+    assert!(compiler.module_from_source("flip(1); 1").is_err());
+    assert!(run_code("square(2)").is_err());
+
+    assert_eq!(
+        compiler.register_fn("unit", |_: ()| 1i64),
+        Err(ExecuteError::UnitParam("unit".into()))
+    );
+    assert_eq!(
+        compiler.register_fn("len", |s: &str| s.len() as i64),
+        Err(ExecuteError::BorrowedHostValue("len".into()))
+    );
+    assert!(compiler.module_from_source("unit(()); len(\"a\")").is_err());
+}
+
+#[test]
+fn host_fn_panics() {
+    let mut compiler = Compiler::new();
+    compiler
+        .register_fn("check", |x: i64| -> i64 {
+            assert!(x >= 0, "negative");
+            x
+        })
+        .unwrap();
+
+    let module = compiler
+        .module_from_source(
+            r#"
+                fn run(x: i64) -> i64 { check(x) + 1 }
+                run(-1)
+            "#,
+        )
+        .unwrap();
+    let err = module.exec::<i64>().unwrap_err();
+    assert!(
+        err.to_string().contains("Host function panicked"),
+        "{}",
+        err
+    );
+
+    // the module is still usable after the panic
+    let run = module.function::<(i64,), i64>("run").unwrap();
+    assert_eq!(run.call((2,)).unwrap(), 3);
+    assert!(run.call((-2,)).is_err());
+}
+
+#[test]
+fn builtin_shadowing() {
+    let result = run_code(
+        r#"
+            fn len(a: [i64; 3]) -> i64 { a[0] + a[1] + a[2] }
+            len([1, 2, 3]) * 100 + [1, 2, 3].len() * 10 + "ab".len()
+        "#,
+    );
+    assert_eq!(result.unwrap(), 632);
+    assert_err(
+        "fn len(x: i64) -> i64 { x } len(\"ab\")",
+        "function 'len' not found",
+    );
+
+    let mut compiler = Compiler::new();
+    compiler
+        .register_fn("len", |s: String| s.len() as i64 * 10)
+        .unwrap();
+    let module = compiler
+        .module_from_source(r#"len("abc") + "abc".len()"#)
+        .unwrap();
+    assert_eq!(module.exec::<i64>().unwrap(), 33);
+}
+
+#[test]
+fn host_globals() {
+    let mut compiler = Compiler::new();
+    compiler.register_global("gravity", 9.8).unwrap();
+    compiler.register_global_mut("frame", 0u64).unwrap();
+    compiler
+        .register_global_mut("wind", Vec2 { x: 1.0, y: 0.0 })
+        .unwrap();
+
+    let module = compiler
+        .module_from_source(
+            r#"
+                struct Vec2 { x: f64, y: f64 }
+
+                fn fall(t: f64) -> f64 { gravity * t * t / 2.0 }
+                fn tick() -> u64 {
+                    frame += 1;
+                    let push = |w: Vec2| Vec2 { x: w.x * 2.0, y: w.y };
+                    wind = push(wind);
+                    frame
+                }
+                fn shadow() -> f64 { let gravity = 1.0; gravity }
+
+                fall(1.0)
+            "#,
+        )
+        .unwrap();
+
+    assert_eq!(module.exec::<f64>().unwrap(), 4.9);
+    let fall = module.function::<(f64,), f64>("fall").unwrap();
+    let tick = module.function::<(), u64>("tick").unwrap();
+    let shadow = module.function::<(), f64>("shadow").unwrap();
+
+    // host updates are visible without recompiling
+    compiler.set_global("gravity", 2.0).unwrap();
+    assert_eq!(fall.call((2.0,)).unwrap(), 4.0);
+    assert_eq!(shadow.call(()).unwrap(), 1.0);
+
+    assert_eq!(tick.call(()).unwrap(), 1);
+    assert_eq!(tick.call(()).unwrap(), 2);
+    compiler.set_global("frame", 10u64).unwrap();
+    assert_eq!(tick.call(()).unwrap(), 11);
+    assert_eq!(compiler.global::<u64>("frame").unwrap(), 11);
+    assert_eq!(
+        compiler.global::<Vec2>("wind").unwrap(),
+        Vec2 { x: 8.0, y: 0.0 }
+    );
+}
+
+#[test]
+fn host_global_errors() {
+    let mut compiler = Compiler::new();
+    compiler.register_global("gravity", 9.8).unwrap();
+    compiler
+        .register_global("wind", Vec2 { x: 1.0, y: 0.0 })
+        .unwrap();
+
+    assert!(compiler.module_from_source("gravity = 1.0; 1").is_err());
+    assert!(compiler.module_from_source("gravity += 1.0; 1").is_err());
+    assert!(compiler
+        .module_from_source("let x: i64 = gravity; x")
+        .is_err());
+    assert!(compiler
+        .module_from_source("struct Vec2 { x: f64 } wind.x; 1")
+        .is_err());
+    assert!(run_code("gravity; 1").is_err());
+    assert!(compiler
+        .module_from_source("let mut gravity = 1.0; gravity = 2.0; 1")
+        .is_ok());
+
+    assert!(matches!(
+        compiler.global::<i64>("gravity"),
+        Err(ExecuteError::GlobalNotFound { .. })
+    ));
+    assert_eq!(
+        compiler
+            .set_global("frame", 1u64)
+            .err()
+            .unwrap()
+            .to_string(),
+        "Global 'frame: u64' not found"
+    );
+    assert!(matches!(
+        compiler.global::<Mismatch>("wind"),
+        Err(ExecuteError::GlobalNotFound { .. })
+    ));
+
+    assert_eq!(
+        compiler.register_global("name", String::from("x")),
+        Err(ExecuteError::BorrowedHostValue("name".into()))
+    );
+    assert_eq!(
+        compiler.register_global_mut("names", [String::new(), String::new()]),
+        Err(ExecuteError::BorrowedHostValue("names".into()))
+    );
+    assert!(compiler.module_from_source("name; 1").is_err());
+}
+
+#[test]
+fn statics() {
+    let compiler = Compiler::new();
+    let module = compiler
+        .module_from_source(
+            r#"
+                struct Vec2 { x: f64, y: f64 }
+
+                static limit: i64 = 10;
+                static mut score = start();
+                static mut wind = Vec2 { x: 1.0, y: 0.0 };
+
+                fn start() -> i64 { limit * 2 }
+                fn add(n: i64) -> i64 {
+                    score += n;
+                    let push = |w: Vec2| Vec2 { x: w.x * 2.0, y: w.y };
+                    wind = push(wind);
+                    score
+                }
+                fn shadow() -> i64 { let score = 1; score }
+
+                score += 1;
+                score + limit
+            "#,
+        )
+        .unwrap();
+
+    // initialized once, top level statements run on every exec
+    assert_eq!(module.global::<i64>("score").unwrap(), 20);
+    assert_eq!(module.exec::<i64>().unwrap(), 31);
+    assert_eq!(module.exec::<i64>().unwrap(), 32);
+
+    let add = module.function::<(i64,), i64>("add").unwrap();
+    let shadow = module.function::<(), i64>("shadow").unwrap();
+    assert_eq!(add.call((3,)).unwrap(), 25);
+    assert_eq!(shadow.call(()).unwrap(), 1);
+    module.set_global("score", 100i64).unwrap();
+    assert_eq!(add.call((1,)).unwrap(), 101);
+    assert_eq!(
+        module.global::<Vec2>("wind").unwrap(),
+        Vec2 { x: 4.0, y: 0.0 }
+    );
+    assert_eq!(module.global::<i64>("limit").unwrap(), 10);
+}
+
+#[test]
+fn static_errors() {
+    assert!(run_code(r#"static s = "a"; 1"#).is_err());
+    assert!(run_code("static f = |x: i64| x; 1").is_err());
+    assert!(run_code("static a = 1; fn f() { a = 2; } 1").is_err());
+    assert!(run_code("static a = 1; static a = 2; 1").is_err());
+    assert!(run_code("fn a() {} static a = 1; 1").is_err());
+    assert!(run_code("static a: i64 = b; static b = 1; a").is_err());
+    assert!(run_code("static a: i64 = true; 1").is_err());
+    assert!(matches!(
+        run_code("static a = [1][2]; 1"),
+        Err(Error::RuntimeError(RuntimeError::IndexOutOfBounds))
+    ));
+
+    let mut compiler = Compiler::new();
+    compiler.register_global("gravity", 9.8).unwrap();
+    assert!(compiler
+        .module_from_source("static gravity = 1.0; 1")
+        .is_err());
+
+    let module = compiler
+        .module_from_source("static limit = 10; static mut score = 0; limit")
+        .unwrap();
+    assert!(matches!(
+        module.global::<i32>("score"),
+        Err(ExecuteError::GlobalNotFound { .. })
+    ));
+    assert!(matches!(
+        module.global::<f64>("gravity"),
+        Err(ExecuteError::GlobalNotFound { .. })
+    ));
+    assert_eq!(
+        module.set_global("limit", 1i64).err().unwrap().to_string(),
+        "Global 'limit' is immutable"
+    );
+}
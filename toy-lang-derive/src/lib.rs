@@ -0,0 +1,91 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Error, Fields, Result};
+
+//
+
+/// implements `toy_lang::ObjInterface` for a struct with named fields
+///
+/// the script has to declare a struct with the same name
+/// and the same fields in the same order
+#[proc_macro_derive(ObjInterface)]
+pub fn derive_obj_interface(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    obj_interface(&input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+fn obj_interface(input: &DeriveInput) -> Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "ObjInterface can't be derived for generic structs",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(DataStruct {
+            fields: Fields::Named(fields),
+            ..
+        }) => &fields.named,
+        _ => {
+            return Err(Error::new_spanned(
+                input,
+                "ObjInterface can only be derived for structs with named fields",
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let name_str = name.to_string();
+    let idents: Vec<_> = fields.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+    let names: Vec<_> = idents.iter().map(|ident| ident.to_string()).collect();
+    let tys: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    let indices: Vec<_> = (0..fields.len()).map(Literal::usize_unsuffixed).collect();
+
+    let interface = quote! { ::toy_lang::compiler::interface };
+
+    Ok(quote! {
+        impl #interface::ObjInterface for #name {
+            fn ty() -> ::toy_lang::ast::Type {
+                ::toy_lang::ast::Type::Named(#name_str.into())
+            }
+
+            fn layout() -> ::std::alloc::Layout {
+                #interface::struct_layout(&[
+                    #(<#tys as #interface::ObjInterface>::layout()),*
+                ])
+                .0
+            }
+
+            fn structs(structs: &mut #interface::StructDecls) {
+                if structs.contains_key(#name_str) {
+                    return;
+                }
+                structs.insert(
+                    #name_str.into(),
+                    vec![#((#names.into(), <#tys as #interface::ObjInterface>::ty())),*],
+                );
+                #(<#tys as #interface::ObjInterface>::structs(structs);)*
+            }
+
+            unsafe fn read(ptr: *const u8) -> Self {
+                let (_, offsets) = #interface::struct_layout(&[
+                    #(<#tys as #interface::ObjInterface>::layout()),*
+                ]);
+                Self {
+                    #(#idents: <#tys as #interface::ObjInterface>::read(ptr.add(offsets[#indices]))),*
+                }
+            }
+
+            unsafe fn write(self, ptr: *mut u8) {
+                let (_, offsets) = #interface::struct_layout(&[
+                    #(<#tys as #interface::ObjInterface>::layout()),*
+                ]);
+                #(<#tys as #interface::ObjInterface>::write(self.#idents, ptr.add(offsets[#indices]));)*
+            }
+        }
+    })
+}